@@ -9,6 +9,7 @@ fn main() -> Result<()> {
     // Tell Cargo to rerun this build script if the source changes
     println!("cargo:rerun-if-changed=../wasm/overlay");
     println!("cargo:rerun-if-changed=../wasm/quiz");
+    println!("cargo:rerun-if-changed=../wasm/search");
     println!("cargo:rerun-if-changed=../../ui/common/src");
     println!("cargo:rerun-if-changed=../../ui/embed/src");
     println!("cargo:rerun-if-changed=../../ui/embed/embed.html");
@@ -72,6 +73,22 @@ fn main() -> Result<()> {
         ],
     )?;
 
+    // Build search wasm module
+    run(
+        "wasm-pack",
+        &[
+            "build",
+            "--target",
+            "web",
+            "--out-dir",
+            "../../../ui/webapp/wasm/search",
+            wasm_profile,
+            "../wasm/search",
+            "--target-dir",
+            &wasm_target_dir,
+        ],
+    )?;
+
     // Build common
     run("yarn", &["--cwd", "../../ui/common", "install"])?;
     run("yarn", &["--cwd", "../../ui/common", "build"])?;
@@ -0,0 +1,177 @@
+//! This module provides the functionality used to prepare the aliases of the
+//! items whose id has changed (i.e. because they were renamed or moved to a
+//! different category or subcategory).
+//!
+//! Ids changes are detected by comparing the current landscape data against
+//! the full dataset of the previous build. The aliases available next to it
+//! are carried forward, so that links using any of the previous ids of an item
+//! keep working across builds.
+
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::{Context, Result};
+use landscape2_core::{
+    data::{ItemsAliases, LandscapeData},
+    datasets::full::Full,
+    diff::items_ids_changes,
+};
+use tracing::{debug, instrument, warn};
+
+use super::{
+    DATASETS_PATH,
+    changes::{read_location, sibling_location},
+};
+
+/// Name of the items aliases file (written to the datasets directory).
+pub(crate) const ITEMS_ALIASES_FILE: &str = "aliases.json";
+
+/// Prepare the items aliases from the baseline provided. When the baseline
+/// does not exist (i.e. first build), no aliases are returned.
+#[instrument(skip(landscape_data), err)]
+pub(crate) async fn prepare_items_aliases(
+    baseline: &str,
+    landscape_data: &LandscapeData,
+) -> Result<ItemsAliases> {
+    debug!("preparing items aliases");
+
+    // Load baseline full dataset and the aliases available next to it
+    let Some(baseline_data) = read_location(baseline).await? else {
+        debug!("baseline not found, no items aliases will be available");
+        return Ok(ItemsAliases::default());
+    };
+    let baseline_full: Full = serde_json::from_slice(&baseline_data).context("invalid baseline")?;
+    let prev_aliases: ItemsAliases =
+        match read_location(&sibling_location(baseline, ITEMS_ALIASES_FILE)?).await {
+            Ok(Some(data)) => serde_json::from_slice(&data).unwrap_or_else(|err| {
+                warn!(?err, "invalid baseline items aliases, ignoring them");
+                ItemsAliases::default()
+            }),
+            Ok(None) => ItemsAliases::default(),
+            Err(err) => {
+                warn!(?err, "error reading baseline items aliases, ignoring them");
+                ItemsAliases::default()
+            }
+        };
+
+    // Merge the aliases of the ids changes found with the previous ones
+    let baseline_data = LandscapeData {
+        items: baseline_full.items,
+        ..Default::default()
+    };
+    let ids_changes = items_ids_changes(&baseline_data, landscape_data);
+    let aliases = merge_items_aliases(prev_aliases, ids_changes, landscape_data);
+
+    Ok(aliases)
+}
+
+/// Write the items aliases provided to the output directory, returning the
+/// path of the file written. The file is always written (even when there are
+/// no aliases), so that the aliases of a previous build left in the output
+/// directory are replaced.
+pub(crate) fn write_items_aliases(aliases: &ItemsAliases, output_dir: &Path) -> Result<String> {
+    let path = Path::new(DATASETS_PATH).join(ITEMS_ALIASES_FILE);
+    fs::write(output_dir.join(&path), serde_json::to_vec(aliases)?)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Load the items aliases available in the landscape directory provided.
+/// Returns an empty set of aliases if they are not available or invalid.
+pub(crate) fn load_items_aliases(landscape_dir: &Path) -> ItemsAliases {
+    fs::read(landscape_dir.join(DATASETS_PATH).join(ITEMS_ALIASES_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Merge the previous aliases with the ids changes provided. Previous aliases
+/// are updated to point to the current ids, and those that are now used by an
+/// item or whose target no longer exists are dropped.
+fn merge_items_aliases(
+    prev_aliases: ItemsAliases,
+    ids_changes: ItemsAliases,
+    landscape_data: &LandscapeData,
+) -> ItemsAliases {
+    let current_ids: HashSet<&str> = landscape_data.items.iter().map(|i| i.id.as_str()).collect();
+
+    let mut aliases = ItemsAliases::new();
+    for (prev_id, target_id) in prev_aliases {
+        let target_id = ids_changes.get(&target_id).cloned().unwrap_or(target_id);
+        aliases.insert(prev_id, target_id);
+    }
+    aliases.extend(ids_changes);
+    aliases.retain(|prev_id, target_id| {
+        !current_ids.contains(prev_id.as_str()) && current_ids.contains(target_id.as_str())
+    });
+
+    aliases
+}
+
+#[cfg(test)]
+mod tests {
+    use landscape2_core::data::Item;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn merge_items_aliases_succeeds() {
+        let landscape_data = LandscapeData {
+            items: ["c--s2--item1", "c--s--item2", "c--s--item3"]
+                .into_iter()
+                .map(|id| Item {
+                    id: id.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let prev_aliases = ItemsAliases::from([
+            // Target moved again, alias must point to the current id
+            ("c--s0--item1".to_string(), "c--s1--item1".to_string()),
+            // Id in use again, alias must be dropped
+            ("c--s--item2".to_string(), "c--s--item2-renamed".to_string()),
+            // Target removed, alias must be dropped
+            ("c--s--item4".to_string(), "c--s--item5".to_string()),
+        ]);
+        let ids_changes = ItemsAliases::from([
+            ("c--s1--item1".to_string(), "c--s2--item1".to_string()),
+            ("c--s--item2-renamed".to_string(), "c--s--item2".to_string()),
+        ]);
+
+        assert_eq!(
+            merge_items_aliases(prev_aliases, ids_changes, &landscape_data),
+            ItemsAliases::from([
+                ("c--s0--item1".to_string(), "c--s2--item1".to_string()),
+                ("c--s--item2-renamed".to_string(), "c--s--item2".to_string()),
+                ("c--s1--item1".to_string(), "c--s2--item1".to_string()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn prepare_items_aliases_baseline_not_found() {
+        let tmp = tempdir().unwrap();
+        let baseline = tmp.path().join("missing").join("full.json");
+
+        let aliases = prepare_items_aliases(&baseline.to_string_lossy(), &LandscapeData::default())
+            .await
+            .unwrap();
+
+        assert!(aliases.is_empty());
+    }
+
+    #[test]
+    fn write_items_aliases_replaces_previous_aliases() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::create_dir_all(output_dir.join(DATASETS_PATH)).unwrap();
+        let prev_aliases = ItemsAliases::from([("c--s1--item".to_string(), "c--s2--item".to_string())]);
+        write_items_aliases(&prev_aliases, output_dir).unwrap();
+        assert_eq!(load_items_aliases(output_dir), prev_aliases);
+
+        let path = write_items_aliases(&ItemsAliases::default(), output_dir).unwrap();
+
+        assert_eq!(path, "data/aliases.json");
+        assert!(load_items_aliases(output_dir).is_empty());
+    }
+}
@@ -10,17 +10,26 @@ use std::collections::{BTreeMap, HashMap};
 
 use chrono::{DateTime, NaiveDate, Utc};
 use itertools::Itertools;
+use landscape2_core::normalize_name;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 
 use super::{
     LandscapeData, LandscapeSettings,
-    data::{self, AdditionalCategory, ItemAudit, ItemLink, ItemSummary},
+    changes::Changelog,
+    crunchbase::get_permalink,
+    data::{self, AdditionalCategory, ItemAudit, ItemLink, ItemSummary, ItemsAliases},
+    openapi::{OPENAPI_ENDPOINT, generate_json_schemas, generate_openapi},
 };
 
+/// Endpoint that lists all the endpoints available.
+const INDEX_ENDPOINT: &str = "index.json";
+
 /// Sources of information used to generate the landscape API data.
 #[derive(Debug, Clone)]
 pub(crate) struct ApiSources<'a> {
+    pub changelog: Option<&'a Changelog>,
+    pub items_aliases: &'a ItemsAliases,
     pub landscape_data: &'a LandscapeData,
     pub settings: &'a LandscapeSettings,
 }
@@ -90,6 +99,14 @@ impl<'a> Api<'a> {
             }
         }
 
+        // Changes
+        if let Some(changelog) = self.sources.changelog {
+            self.endpoints.insert("changes.json".to_string(), to_json(changelog));
+        }
+
+        // Items aliases
+        self.endpoints.insert("aliases.json".to_string(), to_json(self.sources.items_aliases));
+
         // Members
         self.endpoints.insert(
             "members/all.json".to_string(),
@@ -104,6 +121,12 @@ impl<'a> Api<'a> {
             to_json(&self.members_end_users()),
         );
 
+        // Maturity levels, members subcategories, organizations (items grouped
+        // by their Crunchbase organization) and TAGs
+        for (path, items) in self.items_groups() {
+            self.insert_all_and_count(&path, &self.api_items(items));
+        }
+
         // Projects
         let projects = self.projects_all();
         self.endpoints.insert("projects/all.json".to_string(), to_json(&projects));
@@ -111,6 +134,82 @@ impl<'a> Api<'a> {
         for project in &projects {
             self.endpoints.insert(format!("projects/{}.json", project.id), to_json(project));
         }
+
+        // Projects served from their previous ids, so that existing links
+        // keep working after a project is renamed or moved
+        for (prev_id, id) in self.sources.items_aliases {
+            if let Some(project) = projects.iter().find(|p| p.id == *id) {
+                self.endpoints.insert(format!("projects/{prev_id}.json"), to_json(project));
+            }
+        }
+
+        // API description endpoints
+        self.setup_description_endpoints();
+    }
+
+    /// Setup the endpoints that describe the API (JSON Schemas, index and
+    /// OpenAPI description). This must be done once all the other endpoints
+    /// have been set up.
+    fn setup_description_endpoints(&mut self) {
+        // JSON Schemas of the main data types exposed
+        for (endpoint, schema) in generate_json_schemas() {
+            self.endpoints.insert(endpoint, to_json(&schema));
+        }
+
+        // Index of all the endpoints available and OpenAPI description
+        let mut index: Vec<Endpoint> = self.endpoints.keys().cloned().collect();
+        index.extend([INDEX_ENDPOINT.to_string(), OPENAPI_ENDPOINT.to_string()]);
+        index.sort();
+        let openapi = generate_openapi(&index, self.sources.settings);
+        self.endpoints.insert(INDEX_ENDPOINT.to_string(), to_json(&index));
+        self.endpoints.insert(OPENAPI_ENDPOINT.to_string(), to_json(&openapi));
+    }
+
+    /// Insert the all and count endpoints for the items provided, using the
+    /// path given as prefix.
+    fn insert_all_and_count(&mut self, path: &str, items: &[Item]) {
+        self.endpoints.insert(format!("{path}/all.json"), to_json(items));
+        self.endpoints.insert(format!("{path}/count.json"), count_to_json(items.len()));
+    }
+
+    /// Group the landscape items by maturity level, members subcategory,
+    /// organization and TAG (in a single pass). The groups are keyed by the
+    /// path of their endpoints, so items whose values map to the same path
+    /// (i.e. Crunchbase urls with the same permalink) are grouped together.
+    fn items_groups(&self) -> BTreeMap<String, Vec<&'a data::Item>> {
+        let members = self.sources.settings.members_category.as_ref();
+        let mut groups: BTreeMap<String, Vec<&data::Item>> = BTreeMap::new();
+        for item in &self.sources.landscape_data.items {
+            if let Some(maturity) = &item.maturity {
+                groups.entry(format!("maturity/{}", normalize_name(maturity))).or_default().push(item);
+            }
+            if members.is_some_and(|members| item.category == *members)
+                && let Some(member_subcategory) = &item.member_subcategory
+            {
+                groups
+                    .entry(format!("members/{}", normalize_name(member_subcategory)))
+                    .or_default()
+                    .push(item);
+            }
+            if let Some(crunchbase_url) = &item.crunchbase_url
+                && let Ok(permalink) = get_permalink(crunchbase_url)
+            {
+                groups.entry(format!("organizations/{permalink}")).or_default().push(item);
+            }
+            for tag in item.tag.iter().flatten().unique() {
+                groups.entry(format!("tags/{tag}")).or_default().push(item);
+            }
+        }
+        groups
+    }
+
+    /// Convert the landscape items provided to API items, sorted by name.
+    fn api_items(&self, items: Vec<&data::Item>) -> Vec<Item> {
+        items
+            .into_iter()
+            .map(|i| Item::from_data_item(i, &self.sources.settings.url))
+            .sorted_by(|a, b| Ord::cmp(&a.name.to_lowercase(), &b.name.to_lowercase()))
+            .collect()
     }
 
     /// Return all the items in the category provided.
@@ -190,7 +289,7 @@ impl<'a> Api<'a> {
 }
 
 /// Landscape item data that will be exposed from the API.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub(crate) struct Item {
     pub category: String,
     pub homepage_url: String,
@@ -277,6 +376,9 @@ pub(crate) struct Item {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub linkedin_url: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_derivatives: Option<Vec<LogoDerivative>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mailing_list_url: Option<String>,
 
@@ -334,7 +436,7 @@ pub(crate) struct Item {
 
 impl Item {
     /// Create a new item from the data::Item instance provided.
-    fn from_data_item(item: &data::Item, landscape_url: &str) -> Self {
+    pub(crate) fn from_data_item(item: &data::Item, landscape_url: &str) -> Self {
         Self {
             accepted_at: item.accepted_at,
             additional_categories: item.additional_categories.clone(),
@@ -365,11 +467,17 @@ impl Item {
             latest_annual_review_url: item.latest_annual_review_url.clone(),
             lfx_slug: item.lfx_slug.clone(),
             linkedin_url: item.linkedin_url.clone(),
-            logo_url: format!(
-                "{}/{}",
-                landscape_url.strip_suffix('/').unwrap_or(landscape_url),
-                item.logo,
-            ),
+            logo_derivatives: item.logo_derivatives.as_ref().map(|derivatives| {
+                derivatives
+                    .iter()
+                    .map(|d| LogoDerivative {
+                        format: d.format.clone(),
+                        size: d.size,
+                        url: absolute_url(landscape_url, &d.path),
+                    })
+                    .collect()
+            }),
+            logo_url: absolute_url(landscape_url, &item.logo),
             mailing_list_url: item.mailing_list_url.clone(),
             maturity: item.maturity.clone(),
             name: item.name.clone(),
@@ -394,8 +502,22 @@ impl Item {
     }
 }
 
+/// Number of items that will be exposed from the count endpoints.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub(crate) struct Count {
+    pub count: usize,
+}
+
+/// Logo raster derivative information that will be exposed from the API.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub(crate) struct LogoDerivative {
+    pub format: String,
+    pub size: u32,
+    pub url: String,
+}
+
 /// Repository information that will be exposed from the API.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub(crate) struct Repository {
     pub url: String,
 
@@ -439,9 +561,18 @@ impl From<&data::Repository> for Repository {
     }
 }
 
+/// Helper function to build an absolute url from the landscape url and the
+/// path provided.
+pub(crate) fn absolute_url(landscape_url: &str, path: &str) -> String {
+    format!(
+        "{}/{path}",
+        landscape_url.strip_suffix('/').unwrap_or(landscape_url)
+    )
+}
+
 /// Helper function to serialize the count value provided as a json string.
 fn count_to_json(count: usize) -> String {
-    json!({"count": count}).to_string()
+    to_json(&Count { count })
 }
 
 /// Helper function to serialize the given data structure as a json string
@@ -452,3 +583,154 @@ where
 {
     serde_json::to_string(value).expect("serialization to succeed")
 }
+
+#[cfg(test)]
+mod tests {
+    use landscape2_core::data::{Category, Subcategory};
+
+    use super::*;
+
+    const CB_URL: &str = "https://www.crunchbase.com/organization/org";
+
+    fn landscape_data(items: Vec<data::Item>) -> LandscapeData {
+        let category = |name: &str| Category {
+            name: name.to_string(),
+            normalized_name: normalize_name(name),
+            subcategories: vec![Subcategory {
+                name: "Subcategory".to_string(),
+                normalized_name: "subcategory".to_string(),
+            }],
+        };
+        LandscapeData {
+            categories: vec![category("Category"), category("Members")],
+            items,
+        }
+    }
+
+    fn project() -> data::Item {
+        data::Item {
+            category: "Category".to_string(),
+            crunchbase_url: Some(CB_URL.to_string()),
+            id: "category--subcategory--project".to_string(),
+            maturity: Some("graduated".to_string()),
+            name: "Project".to_string(),
+            subcategory: "Subcategory".to_string(),
+            tag: Some(vec!["app-definition".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    fn member() -> data::Item {
+        data::Item {
+            category: "Members".to_string(),
+            crunchbase_url: Some(CB_URL.to_string()),
+            id: "members--subcategory--member".to_string(),
+            member_subcategory: Some("Gold".to_string()),
+            name: "Member".to_string(),
+            subcategory: "Subcategory".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Generate the API endpoints for the landscape data provided.
+    fn endpoints(landscape_data: &LandscapeData) -> HashMap<Endpoint, Data> {
+        let settings = LandscapeSettings {
+            members_category: Some("Members".to_string()),
+            ..Default::default()
+        };
+        let sources = ApiSources {
+            changelog: None,
+            items_aliases: &data::ItemsAliases::default(),
+            landscape_data,
+            settings: &settings,
+        };
+        Api::new(&sources).endpoints
+    }
+
+    /// Return the names of the items in the endpoint provided.
+    fn names(endpoints: &HashMap<Endpoint, Data>, endpoint: &str) -> Vec<String> {
+        let items: Vec<Item> = serde_json::from_str(&endpoints[endpoint]).unwrap();
+        items.into_iter().map(|i| i.name).collect()
+    }
+
+    #[test]
+    fn api_grouped_items_endpoints() {
+        let endpoints = endpoints(&landscape_data(vec![project(), member()]));
+
+        assert_eq!(endpoints["maturity/graduated/count.json"], count_to_json(1));
+        assert_eq!(endpoints["members/gold/count.json"], count_to_json(1));
+        assert_eq!(endpoints["organizations/org/count.json"], count_to_json(2));
+        assert_eq!(
+            names(&endpoints, "organizations/org/all.json"),
+            vec!["Member", "Project"]
+        );
+        assert_eq!(endpoints["tags/app-definition/count.json"], count_to_json(1));
+    }
+
+    #[test]
+    fn api_members_category_excluded_from_categories() {
+        let endpoints = endpoints(&landscape_data(vec![project(), member()]));
+
+        assert_eq!(endpoints["categories/category/count.json"], count_to_json(1));
+        assert_eq!(
+            endpoints["categories/category/subcategory/count.json"],
+            count_to_json(1)
+        );
+        assert!(!endpoints.keys().any(|e| e.starts_with("categories/members/")));
+        assert_eq!(names(&endpoints, "members/all.json"), vec!["Member"]);
+    }
+
+    #[test]
+    fn api_items_without_grouping_fields() {
+        let item = data::Item {
+            category: "Category".to_string(),
+            id: "category--subcategory--item".to_string(),
+            name: "Item".to_string(),
+            subcategory: "Subcategory".to_string(),
+            ..Default::default()
+        };
+        let endpoints = endpoints(&landscape_data(vec![project(), item]));
+
+        // The item is only listed in its category and subcategory endpoints
+        assert_eq!(endpoints["categories/category/count.json"], count_to_json(2));
+        assert_eq!(endpoints["maturity/graduated/count.json"], count_to_json(1));
+        assert_eq!(endpoints["organizations/org/count.json"], count_to_json(1));
+        assert_eq!(endpoints["tags/app-definition/count.json"], count_to_json(1));
+        assert_eq!(endpoints["projects/count.json"], count_to_json(1));
+        for endpoint in endpoints.keys().filter(|e| e.ends_with("/all.json")) {
+            let in_endpoint = names(&endpoints, endpoint).contains(&"Item".to_string());
+            assert_eq!(
+                in_endpoint,
+                endpoint.starts_with("categories/category/"),
+                "{endpoint}"
+            );
+        }
+    }
+
+    #[test]
+    fn api_organizations_with_same_permalink_are_merged() {
+        let other_project = data::Item {
+            crunchbase_url: Some(format!("{CB_URL}/")),
+            id: "category--subcategory--other-project".to_string(),
+            name: "Other project".to_string(),
+            ..project()
+        };
+        let endpoints = endpoints(&landscape_data(vec![project(), other_project]));
+
+        assert_eq!(endpoints["organizations/org/count.json"], count_to_json(2));
+        assert_eq!(
+            names(&endpoints, "organizations/org/all.json"),
+            vec!["Other project", "Project"]
+        );
+    }
+
+    #[test]
+    fn api_index_lists_all_endpoints() {
+        let endpoints = endpoints(&landscape_data(vec![project(), member()]));
+
+        let index: Vec<String> = serde_json::from_str(&endpoints[INDEX_ENDPOINT]).unwrap();
+        assert_eq!(index.len(), endpoints.len());
+        assert!(index.contains(&"tags/app-definition/all.json".to_string()));
+        assert!(index.contains(&OPENAPI_ENDPOINT.to_string()));
+    }
+}
@@ -0,0 +1,279 @@
+//! This module provides the functionality used to render a social card image
+//! per item, which is displayed when the item page link is shared.
+//!
+//! Cards are composed from an SVG template that is rendered natively using
+//! resvg, so no headless browser is required to generate them.
+
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use askama::Template;
+use futures::stream::{self, StreamExt};
+use image::{ImageFormat, imageops};
+use landscape2_core::{
+    data::{Item, LandscapeData},
+    settings::LandscapeSettings,
+};
+use resvg::tiny_skia::{Pixmap, Transform};
+use tracing::{debug, error, instrument, warn};
+use usvg::{TreeParsing, TreeTextToPath, fontdb};
+
+use super::{
+    IMAGES_PATH,
+    logos::{pixmap_to_image, render_logo},
+};
+
+/// Path where the cards will be written to in the output directory.
+const CARDS_PATH: &str = "cards";
+
+/// Width of the cards (in pixels).
+pub(crate) const CARD_WIDTH: u32 = 1200;
+
+/// Height of the cards (in pixels).
+pub(crate) const CARD_HEIGHT: u32 = 630;
+
+/// Default primary color used in the cards.
+const DEFAULT_COLOR1: &str = "rgba(0, 107, 204, 1)";
+
+/// Default secondary color used in the cards.
+const DEFAULT_COLOR2: &str = "rgba(214, 34, 147, 1)";
+
+/// Font families used in the text rendered, in order of preference.
+pub(crate) const FONT_FAMILY: &str =
+    "Inter, Roboto, Helvetica Neue, Arial, DejaVu Sans, Liberation Sans, sans-serif";
+
+/// Position (x, y) and size of the area where the logo is drawn.
+const LOGO_AREA: (u32, u32, u32) = (80, 155, 320);
+
+/// Padding around the logo within its area.
+const LOGO_PADDING: u32 = 40;
+
+/// Maximum number of characters of the item name displayed.
+const NAME_MAX_CHARS: usize = 22;
+
+/// Maximum number of characters of the category line displayed.
+const CATEGORY_MAX_CHARS: usize = 45;
+
+/// Maximum number of cards to render concurrently.
+const RENDER_CARDS_MAX_CONCURRENCY: usize = 20;
+
+/// Template for the item social card.
+#[derive(Debug, Clone, Template)]
+#[template(path = "social-card.svg")]
+struct SocialCardSvg<'a> {
+    category: String,
+    color1: &'a str,
+    color2: &'a str,
+    font_family: &'a str,
+    foundation: &'a str,
+    name: String,
+    stars_y: u32,
+
+    maturity: Option<&'a str>,
+    stars: Option<String>,
+}
+
+impl<'a> SocialCardSvg<'a> {
+    /// Create a new social card from the item provided.
+    fn new(item: &'a Item, settings: &'a LandscapeSettings) -> Self {
+        let colors = settings.colors.as_ref();
+        let maturity = item.maturity.as_deref();
+        Self {
+            category: truncate(
+                &format!("{} / {}", item.category, item.subcategory),
+                CATEGORY_MAX_CHARS,
+            ),
+            color1: colors.map_or(DEFAULT_COLOR1, |c| c.color1.as_str()),
+            color2: colors.map_or(DEFAULT_COLOR2, |c| c.color2.as_str()),
+            font_family: FONT_FAMILY,
+            foundation: &settings.foundation,
+            name: truncate(&item.name, NAME_MAX_CHARS),
+            stars_y: if maturity.is_some() { 430 } else { 375 },
+            maturity,
+            stars: item
+                .primary_repository()
+                .and_then(|repo| repo.github_data.as_ref())
+                .map(|gh_data| format_number(gh_data.stars)),
+        }
+    }
+}
+
+/// Render the items social cards, writing them to the output directory and
+/// returning their paths. Items whose card cannot be rendered are skipped.
+#[instrument(skip_all, err)]
+pub(crate) async fn render_items_cards(
+    landscape_data: &LandscapeData,
+    settings: &LandscapeSettings,
+    output_dir: &Path,
+) -> Result<Vec<String>> {
+    debug!("rendering items cards");
+
+    fs::create_dir_all(output_dir.join(IMAGES_PATH).join(CARDS_PATH))?;
+
+    // Load the system fonts (only once for all cards)
+    let fontdb = load_system_fonts().await?;
+
+    // Render cards
+    let mut concurrency = num_cpus::get();
+    if concurrency > RENDER_CARDS_MAX_CONCURRENCY {
+        concurrency = RENDER_CARDS_MAX_CONCURRENCY;
+    }
+    let mut outputs: Vec<String> = stream::iter(landscape_data.items.iter())
+        .map(|item| {
+            let fontdb = fontdb.clone();
+            let path = card_path(&item.id);
+            let svg = SocialCardSvg::new(item, settings).render();
+            let logo = (!item.logo.is_empty()).then(|| output_dir.join(&item.logo));
+            async move {
+                let svg = match svg {
+                    Ok(svg) => svg,
+                    Err(err) => {
+                        error!(?err, ?item.id, "error rendering card template");
+                        return None;
+                    }
+                };
+                match tokio::task::spawn_blocking(move || render_card(&svg, logo, &fontdb)).await {
+                    Ok(Ok(card)) => {
+                        if let Err(err) = fs::write(output_dir.join(&path), card) {
+                            error!(?err, ?path, "error writing card to file in output dir");
+                            return None;
+                        }
+                        Some(path)
+                    }
+                    Ok(Err(err)) => {
+                        warn!(?err, ?item.id, "error rendering card");
+                        None
+                    }
+                    Err(err) => {
+                        error!(?err, ?item.id, "error executing render_card task");
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(concurrency)
+        .filter_map(|path| async { path })
+        .collect()
+        .await;
+    outputs.sort();
+
+    Ok(outputs)
+}
+
+/// Load the fonts available in the system, used to render the text in the
+/// images generated.
+pub(crate) async fn load_system_fonts() -> Result<Arc<fontdb::Database>> {
+    let fontdb = tokio::task::spawn_blocking(|| {
+        let mut fontdb = fontdb::Database::new();
+        fontdb.load_system_fonts();
+        fontdb
+    })
+    .await?;
+    if fontdb.is_empty() {
+        warn!("no system fonts found, text will not be displayed in the images rendered");
+    }
+    Ok(Arc::new(fontdb))
+}
+
+/// Return the path of the card of the item provided (relative to the output
+/// directory).
+pub(crate) fn card_path(item_id: &str) -> String {
+    format!("{IMAGES_PATH}/{CARDS_PATH}/{item_id}.png")
+}
+
+/// Render the card SVG provided to PNG, drawing the logo (if any) on it.
+fn render_card(svg: &str, logo: Option<PathBuf>, fontdb: &fontdb::Database) -> Result<Vec<u8>> {
+    // Render card
+    let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default())?;
+    tree.convert_text(fontdb);
+    let mut pixmap = Pixmap::new(CARD_WIDTH, CARD_HEIGHT).context("invalid pixmap size")?;
+    resvg::Tree::from_usvg(&tree).render(Transform::default(), &mut pixmap.as_mut());
+    let mut card = pixmap_to_image(&pixmap)?;
+
+    // Draw logo centered in the logo area
+    if let Some(logo) = logo {
+        let extension = logo.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+        let logo = render_logo(&fs::read(&logo)?, &extension, LOGO_AREA.2 - 2 * LOGO_PADDING)?;
+        let x = LOGO_AREA.0 + (LOGO_AREA.2 - logo.width()) / 2;
+        let y = LOGO_AREA.1 + (LOGO_AREA.2 - logo.height()) / 2;
+        imageops::overlay(&mut card, &logo, i64::from(x), i64::from(y));
+    }
+
+    let mut data = vec![];
+    card.write_to(&mut Cursor::new(&mut data), ImageFormat::Png)?;
+    Ok(data)
+}
+
+/// Format the number provided using a comma as thousands separator.
+fn format_number(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    if n < 0 {
+        formatted.insert(0, '-');
+    }
+    formatted
+}
+
+/// Truncate the text provided to the number of characters given, appending
+/// an ellipsis when it's truncated.
+pub(crate) fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated = truncated.trim_end().to_string();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_number_works() {
+        assert_eq!(format_number(0), "0");
+        assert_eq!(format_number(999), "999");
+        assert_eq!(format_number(1000), "1,000");
+        assert_eq!(format_number(1_234_567), "1,234,567");
+        assert_eq!(format_number(-12345), "-12,345");
+    }
+
+    #[test]
+    fn truncate_works() {
+        assert_eq!(truncate("Item", 10), "Item");
+        assert_eq!(truncate("Some very long name", 10), "Some very…");
+    }
+
+    #[test]
+    fn render_card_succeeds() {
+        let item = Item {
+            id: "category--subcategory--item".to_string(),
+            category: "Category".to_string(),
+            subcategory: "Subcategory".to_string(),
+            name: "Item <1>".to_string(),
+            maturity: Some("graduated".to_string()),
+            ..Default::default()
+        };
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            ..Default::default()
+        };
+
+        let svg = SocialCardSvg::new(&item, &settings).render().unwrap();
+        let card = render_card(&svg, None, &fontdb::Database::new()).unwrap();
+        let card = image::load_from_memory(&card).unwrap();
+        assert_eq!((card.width(), card.height()), (CARD_WIDTH, CARD_HEIGHT));
+    }
+}
@@ -0,0 +1,352 @@
+//! This module provides the functionality used to generate the changelog of
+//! the landscape (items added, removed and maturity transitions), as well as
+//! an Atom feed of the changes.
+//!
+//! Changes are computed by comparing the current landscape data against a
+//! baseline, usually the full dataset (`data/full.json`) of the previously
+//! deployed landscape. The changelog available next to the baseline (if any)
+//! is extended with the new changes found, so that the history is preserved
+//! across builds.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use askama::Template;
+use chrono::{DateTime, SecondsFormat, Utc};
+use landscape2_core::{
+    data::{Item, LandscapeData},
+    datasets::full::Full,
+    diff::{ItemKey, LandscapeDataDiff},
+};
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+use url::Url;
+
+use super::DATASETS_PATH;
+
+/// Name of the changelog file (written to the datasets directory).
+const CHANGELOG_FILE: &str = "changes.json";
+
+/// Name of the changes Atom feed file (written to the output directory root).
+const CHANGES_FEED_FILE: &str = "changes.atom";
+
+/// Maximum number of entries kept in the changelog.
+const CHANGELOG_MAX_ENTRIES: usize = 100;
+
+/// Landscape changelog.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub(crate) struct Changelog {
+    pub entries: Vec<ChangelogEntry>,
+}
+
+/// Changes found in a build.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub(crate) struct ChangelogEntry {
+    pub date: DateTime<Utc>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<ChangedItem>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<ChangedItem>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maturity: Vec<MaturityChange>,
+}
+
+impl ChangelogEntry {
+    /// Check if the entry contains any changes.
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.maturity.is_empty()
+    }
+
+    /// Return a title summarizing the changes in the entry.
+    pub(crate) fn title(&self) -> String {
+        let mut parts = vec![];
+        let mut add = |count: usize, singular: &str, plural: &str| {
+            if count > 0 {
+                parts.push(format!("{count} {}", if count == 1 { singular } else { plural }));
+            }
+        };
+        add(self.added.len(), "item added", "items added");
+        add(self.removed.len(), "item removed", "items removed");
+        add(self.maturity.len(), "maturity change", "maturity changes");
+        parts.join(", ")
+    }
+}
+
+/// Item information included in the changelog.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub(crate) struct ChangedItem {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub subcategory: String,
+}
+
+impl From<&Item> for ChangedItem {
+    fn from(item: &Item) -> Self {
+        Self {
+            id: item.id.clone(),
+            name: item.name.clone(),
+            category: item.category.clone(),
+            subcategory: item.subcategory.clone(),
+        }
+    }
+}
+
+/// Maturity transition of an item.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub(crate) struct MaturityChange {
+    pub item: ChangedItem,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+/// Template for the changes Atom feed.
+#[derive(Debug, Clone, Template)]
+#[template(path = "changes.atom", escape = "html")]
+struct ChangesFeed<'a> {
+    changelog: &'a Changelog,
+    foundation: &'a str,
+    updated: String,
+    url: &'a str,
+}
+
+/// Prepare the landscape changelog from the baseline provided, writing it to
+/// the output directory along with the changes Atom feed. When the baseline
+/// does not exist (i.e. first deploy), an empty changelog is written so that
+/// the changelog and the feed are always available.
+#[instrument(skip(landscape_data, output_dir), err)]
+pub(crate) async fn prepare_changes(
+    baseline: &str,
+    landscape_data: &LandscapeData,
+    foundation: &str,
+    url: &str,
+    output_dir: &Path,
+) -> Result<Changelog> {
+    debug!("preparing changes");
+
+    // Load baseline full dataset
+    let Some(baseline_data) = read_location(baseline).await? else {
+        warn!("baseline not found, starting a new changelog");
+        let changelog = Changelog::default();
+        write_changes(&changelog, foundation, url, output_dir)?;
+        return Ok(changelog);
+    };
+    let baseline_full: Full = serde_json::from_slice(&baseline_data).context("invalid baseline")?;
+
+    // Load the changelog available next to the baseline
+    let mut changelog: Changelog = match read_location(&sibling_location(baseline, CHANGELOG_FILE)?).await {
+        Ok(Some(data)) => serde_json::from_slice(&data).unwrap_or_else(|err| {
+            warn!(?err, "invalid baseline changelog, starting a new one");
+            Changelog::default()
+        }),
+        Ok(None) => Changelog::default(),
+        Err(err) => {
+            warn!(?err, "error reading baseline changelog, starting a new one");
+            Changelog::default()
+        }
+    };
+
+    // Add a new entry to the changelog with the changes found (if any)
+    let baseline_data = LandscapeData {
+        items: baseline_full.items,
+        ..Default::default()
+    };
+    let entry = new_changelog_entry(&baseline_data, landscape_data, Utc::now());
+    if !entry.is_empty() {
+        changelog.entries.insert(0, entry);
+        changelog.entries.truncate(CHANGELOG_MAX_ENTRIES);
+    }
+
+    write_changes(&changelog, foundation, url, output_dir)?;
+    Ok(changelog)
+}
+
+/// Write the changelog provided to the output directory, along with the
+/// changes Atom feed.
+fn write_changes(changelog: &Changelog, foundation: &str, url: &str, output_dir: &Path) -> Result<()> {
+    let changelog_path = output_dir.join(DATASETS_PATH).join(CHANGELOG_FILE);
+    fs::write(changelog_path, serde_json::to_vec(changelog)?)?;
+    let url = url.trim_end_matches('/');
+    let feed = ChangesFeed {
+        changelog,
+        foundation,
+        updated: changelog
+            .entries
+            .first()
+            .map_or_else(Utc::now, |entry| entry.date)
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+        url,
+    }
+    .render()?;
+    fs::write(output_dir.join(CHANGES_FEED_FILE), feed)?;
+
+    Ok(())
+}
+
+/// Create a new changelog entry from the changes between the baseline and the
+/// current landscape data provided.
+fn new_changelog_entry(
+    baseline: &LandscapeData,
+    current: &LandscapeData,
+    date: DateTime<Utc>,
+) -> ChangelogEntry {
+    let diff = LandscapeDataDiff::new(baseline, current);
+    let find = |data: &LandscapeData, key: &ItemKey| -> Option<ChangedItem> {
+        data.items
+            .iter()
+            .find(|i| i.category == key.category && i.subcategory == key.subcategory && i.name == key.name)
+            .map(ChangedItem::from)
+    };
+
+    ChangelogEntry {
+        date,
+        added: diff.added.iter().filter_map(|key| find(current, key)).collect(),
+        removed: diff.removed.iter().filter_map(|key| find(baseline, key)).collect(),
+        maturity: diff
+            .changed
+            .iter()
+            .filter_map(|item_changes| {
+                let change = item_changes.changes.iter().find(|c| c.field == "maturity")?;
+                Some(MaturityChange {
+                    item: find(current, &item_changes.item)?,
+                    from: change.old.clone(),
+                    to: change.new.clone(),
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Read the content of the location provided (local path or url). Returns
+/// `None` if it does not exist.
+pub(super) async fn read_location(location: &str) -> Result<Option<Vec<u8>>> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        let resp = reqwest::get(location).await?;
+        match resp.status() {
+            StatusCode::OK => Ok(Some(resp.bytes().await?.to_vec())),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => bail!("unexpected status code getting {location}: {status}"),
+        }
+    } else {
+        let path = Path::new(location);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read(path)?))
+    }
+}
+
+/// Return the location of the file provided in the same directory as the
+/// location given (local path or url).
+pub(super) fn sibling_location(location: &str, file_name: &str) -> Result<String> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        Ok(Url::parse(location)?.join(file_name)?.to_string())
+    } else {
+        Ok(Path::new(location).with_file_name(file_name).to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn item(name: &str, maturity: Option<&str>) -> Item {
+        Item {
+            id: format!("category--subcategory--{}", name.to_lowercase()),
+            category: "Category".to_string(),
+            subcategory: "Subcategory".to_string(),
+            name: name.to_string(),
+            homepage_url: format!("https://{}.example.com", name.to_lowercase()),
+            maturity: maturity.map(ToString::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn new_changelog_entry_succeeds() {
+        let baseline = LandscapeData {
+            items: vec![item("Item1", Some("sandbox")), item("Item2", None)],
+            ..Default::default()
+        };
+        let current = LandscapeData {
+            items: vec![item("Item1", Some("incubating")), item("Item3", None)],
+            ..Default::default()
+        };
+        let date = Utc::now();
+
+        let entry = new_changelog_entry(&baseline, &current, date);
+        assert_eq!(
+            entry,
+            ChangelogEntry {
+                date,
+                added: vec![ChangedItem::from(&item("Item3", None))],
+                removed: vec![ChangedItem::from(&item("Item2", None))],
+                maturity: vec![MaturityChange {
+                    item: ChangedItem::from(&item("Item1", None)),
+                    from: Some("sandbox".to_string()),
+                    to: Some("incubating".to_string()),
+                }],
+            }
+        );
+        assert_eq!(entry.title(), "1 item added, 1 item removed, 1 maturity change");
+    }
+
+    #[tokio::test]
+    async fn prepare_changes_baseline_not_found() {
+        // Setup output directory
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::create_dir_all(output_dir.join(DATASETS_PATH)).unwrap();
+
+        // Prepare changes using a baseline that does not exist
+        let baseline = output_dir.join("missing").join("full.json");
+        let landscape_data = LandscapeData {
+            items: vec![item("Item", None)],
+            ..Default::default()
+        };
+        let changelog = prepare_changes(
+            &baseline.to_string_lossy(),
+            &landscape_data,
+            "CNCF",
+            "https://landscape.example.com/",
+            output_dir,
+        )
+        .await
+        .unwrap();
+
+        // An empty changelog and feed are written to the output directory
+        let changelog_file = fs::read(output_dir.join(DATASETS_PATH).join(CHANGELOG_FILE)).unwrap();
+        let feed = fs::read_to_string(output_dir.join(CHANGES_FEED_FILE)).unwrap();
+
+        assert_eq!(changelog, Changelog::default());
+        assert_eq!(
+            serde_json::from_slice::<Changelog>(&changelog_file).unwrap(),
+            changelog
+        );
+        assert!(feed.contains("<feed"));
+        assert!(!feed.contains("<entry>"));
+    }
+
+    #[test]
+    fn sibling_location_succeeds() {
+        assert_eq!(
+            sibling_location("https://landscape.example.com/data/full.json", CHANGELOG_FILE).unwrap(),
+            "https://landscape.example.com/data/changes.json"
+        );
+        assert_eq!(
+            sibling_location("build/data/full.json", CHANGELOG_FILE).unwrap(),
+            "build/data/changes.json"
+        );
+    }
+}
@@ -383,7 +383,7 @@ pub(crate) static CRUNCHBASE_URL: LazyLock<Regex> = LazyLock::new(|| {
 });
 
 /// Extract the organization permalink from the crunchbase url provided.
-fn get_permalink(cb_url: &str) -> Result<String> {
+pub(crate) fn get_permalink(cb_url: &str) -> Result<String> {
     let c = CRUNCHBASE_URL.captures(cb_url).ok_or_else(|| format_err!("invalid crunchbase url"))?;
     Ok(c["permalink"].to_string())
 }
@@ -1,19 +1,23 @@
 //! This module provides some helper functions to prepare logos to be displayed
-//! on the landscape web application.
+//! on the landscape web application, as well as to audit them to detect some
+//! common problems.
 
 use std::{
     fs,
+    io::Cursor,
     path::{Path, PathBuf},
     sync::LazyLock,
 };
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail, format_err};
 use clap::Args;
+use image::{DynamicImage, ImageFormat, RgbaImage, imageops::FilterType};
 use regex::bytes::Regex;
 use reqwest::StatusCode;
+use resvg::tiny_skia::{IntSize, Pixmap, Transform};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use usvg::{NodeExt, Rect, TreeParsing};
+use usvg::{ImageKind, NodeExt, NodeKind, Rect, TreeParsing, TreeWriting, XmlOptions};
 
 use super::settings::LogosViewbox;
 
@@ -25,6 +29,28 @@ pub static SVG_TITLE: LazyLock<Regex> =
 pub static SVG_VIEWBOX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"viewBox="[0-9. ]*""#).expect("expr in SVG_VIEWBOX to be valid"));
 
+/// Regular expression used to extract the SVG logos' root element viewbox.
+static SVG_ROOT_VIEWBOX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<svg\b[^>]*?\sviewBox\s*=\s*["']([^"']*)["']"#)
+        .expect("expr in SVG_ROOT_VIEWBOX to be valid")
+});
+
+/// Maximum size of a logo file before it's considered oversized (in bytes).
+const LOGO_MAX_SIZE: usize = 200 * 1024;
+
+/// Raster formats (and their extensions) used for the logos derivatives.
+const RASTER_DERIVATIVES_FORMATS: [(ImageFormat, &str); 2] =
+    [(ImageFormat::Png, "png"), (ImageFormat::WebP, "webp")];
+
+/// Extensions of the raster logos supported.
+const RASTER_EXTENSIONS: [&str; 5] = ["gif", "jpeg", "jpg", "png", "webp"];
+
+/// Numeric precision used for coordinates when optimizing SVG logos.
+const OPTIMIZE_COORDINATES_PRECISION: u8 = 3;
+
+/// Numeric precision used for transforms when optimizing SVG logos.
+const OPTIMIZE_TRANSFORMS_PRECISION: u8 = 5;
+
 /// Landscape logos source.
 #[derive(Args, Clone, Default)]
 #[group(required = true, multiple = false)]
@@ -46,31 +72,30 @@ pub(crate) struct Logo {
     pub digest: String,
 }
 
-/// Get logo from the source provided and apply some modifications to it when
-/// applicable.
-pub(crate) async fn prepare_logo(
-    http_client: reqwest::Client,
-    logos_source: &LogosSource,
+/// Represents a raster derivative of a logo.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct LogoDerivative {
+    pub data: Vec<u8>,
+    pub extension: String,
+    pub size: u32,
+}
+
+/// Apply some modifications to the logo data provided when applicable.
+pub(crate) fn prepare_logo(
+    mut logo_data: Vec<u8>,
     logos_viewbox: &LogosViewbox,
+    optimize: bool,
     file_name: &str,
-) -> Result<Logo> {
-    // Get logo from the source provided
-    let mut logo_data = get_logo(http_client.clone(), logos_source, file_name).await?;
-
+) -> Logo {
     // Apply some modifications to the logo if it is an SVG file
-    let extension = Path::new(file_name)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or_default()
-        .to_lowercase();
+    let extension = get_logo_extension(file_name);
     if extension == "svg" {
         // Remove title if present (some identical logos are using a different
         // title, so we do this before computing the digest)
         logo_data = SVG_TITLE.replace(&logo_data, b"").into_owned();
 
         // Update viewbox to the smallest rectangle in which the object fits
-        if logos_viewbox.adjust
-            && !logos_viewbox.exclude.contains(&file_name.to_string())
+        if should_adjust_viewbox(logos_viewbox, file_name)
             && let Ok(Some(bounding_box)) = get_svg_bounding_box(&logo_data)
             && bounding_box.left() >= 0.0
             && bounding_box.top() >= 0.0
@@ -85,21 +110,71 @@ pub(crate) async fn prepare_logo(
             let new_viewbox = format!(r#"viewBox="{new_viewbox_bounds}""#);
             logo_data = SVG_VIEWBOX.replace(&logo_data, new_viewbox.as_bytes()).into_owned();
         }
+
+        // Optimize logo if requested
+        if optimize {
+            logo_data = optimize_svg(logo_data);
+        }
     }
 
     // Calculate digest
     let digest = hex::encode(Sha256::digest(&logo_data));
 
-    Ok(Logo {
+    Logo {
         data: logo_data,
         extension,
         digest,
-    })
+    }
+}
+
+/// Calculate the digest of the logo source provided. This digest identifies
+/// the source data as well as the modifications that will be applied to it.
+pub(crate) fn get_logo_source_digest(
+    logo_data: &[u8],
+    logos_viewbox: &LogosViewbox,
+    optimize: bool,
+    raster_sizes: &[u32],
+    file_name: &str,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(logo_data);
+    hasher.update([u8::from(should_adjust_viewbox(logos_viewbox, file_name))]);
+    hasher.update([u8::from(optimize)]);
+    for size in raster_sizes {
+        hasher.update(size.to_le_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Generate the raster derivatives (PNG and WebP) of the logo provided for
+/// each of the sizes requested. SVG logos are rendered, whereas raster logos
+/// are resized. Derivatives are fitted into a square of the corresponding
+/// size, preserving the logo's aspect ratio.
+pub(crate) fn prepare_logo_derivatives(logo: &Logo, sizes: &[u32]) -> Result<Vec<LogoDerivative>> {
+    // Parse logo (only once for all sizes)
+    let source = LogoSource::new(&logo.data, &logo.extension)?;
+
+    // Prepare derivatives
+    let mut derivatives = vec![];
+    for size in sizes {
+        let image = source.render(*size)?;
+        for (format, extension) in RASTER_DERIVATIVES_FORMATS {
+            let mut data = vec![];
+            image.write_to(&mut Cursor::new(&mut data), format)?;
+            derivatives.push(LogoDerivative {
+                data,
+                extension: extension.to_string(),
+                size: *size,
+            });
+        }
+    }
+
+    Ok(derivatives)
 }
 
 /// Get logo content from the corresponding source.
 #[allow(clippy::similar_names)]
-async fn get_logo(
+pub(crate) async fn get_logo(
     http_client: reqwest::Client,
     logos_source: &LogosSource,
     file_name: &str,
@@ -123,6 +198,150 @@ async fn get_logo(
     bail!("logos path or url not provided");
 }
 
+/// Audit the logo provided, returning the problems found (if any).
+///
+/// The following problems are detected in SVG logos: embedded raster images,
+/// text elements (the fonts used may not be available when the logo is
+/// displayed), missing or degenerate viewbox and oversized files. Raster logos
+/// are checked to be oversized or not decodable.
+pub(crate) fn audit_logo(logo_data: &[u8], file_name: &str) -> Vec<String> {
+    let mut problems = vec![];
+
+    // Size
+    if logo_data.len() > LOGO_MAX_SIZE {
+        problems.push(format!(
+            "logo is too big ({} KB, max {} KB)",
+            logo_data.len().div_ceil(1024),
+            LOGO_MAX_SIZE / 1024
+        ));
+    }
+
+    // Raster logos only need to be decodable
+    let extension = get_logo_extension(file_name);
+    if RASTER_EXTENSIONS.contains(&extension.as_str()) {
+        if let Err(err) = image::load_from_memory(logo_data) {
+            problems.push(format!("logo could not be decoded: {err}"));
+        }
+        return problems;
+    }
+
+    // The remaining checks only apply to SVG logos
+    if extension != "svg" {
+        return problems;
+    }
+
+    // Viewbox
+    match SVG_ROOT_VIEWBOX.captures(logo_data).and_then(|c| c.get(1)) {
+        Some(viewbox) => {
+            let viewbox = String::from_utf8_lossy(viewbox.as_bytes());
+            let values: Vec<f64> = viewbox
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|v| !v.is_empty())
+                .filter_map(|v| v.parse().ok())
+                .collect();
+            if values.len() != 4 || values[2] <= 0.0 || values[3] <= 0.0 {
+                problems.push(format!("logo viewBox is degenerate ({viewbox})"));
+            }
+        }
+        None => problems.push("logo viewBox is missing".to_string()),
+    }
+
+    // Content
+    match usvg::Tree::from_data(logo_data, &usvg::Options::default()) {
+        Ok(tree) => {
+            if has_raster_images(&tree.root) {
+                problems.push("logo embeds raster images".to_string());
+            }
+            if tree.has_text_nodes() {
+                problems.push("logo uses text elements (the fonts may not be available)".to_string());
+            }
+        }
+        Err(err) => problems.push(format!("logo could not be parsed: {err}")),
+    }
+
+    problems
+}
+
+/// Render the logo provided (SVG or raster), fitting it into a square of the
+/// size provided and preserving its aspect ratio.
+pub(crate) fn render_logo(logo_data: &[u8], extension: &str, size: u32) -> Result<RgbaImage> {
+    LogoSource::new(logo_data, extension)?.render(size)
+}
+
+/// Logo source ready to be used to generate raster derivatives.
+enum LogoSource {
+    Svg(resvg::Tree, IntSize),
+    Raster(DynamicImage),
+}
+
+impl LogoSource {
+    /// Parse the logo data provided based on its extension.
+    fn new(logo_data: &[u8], extension: &str) -> Result<Self> {
+        if extension == "svg" {
+            let tree = usvg::Tree::from_data(logo_data, &usvg::Options::default())?;
+            Ok(LogoSource::Svg(
+                resvg::Tree::from_usvg(&tree),
+                tree.size.to_int_size(),
+            ))
+        } else if RASTER_EXTENSIONS.contains(&extension) {
+            Ok(LogoSource::Raster(image::load_from_memory(logo_data)?))
+        } else {
+            bail!("unsupported logo format: {extension}");
+        }
+    }
+
+    /// Render the logo, fitting it into a square of the size provided.
+    fn render(&self, size: u32) -> Result<RgbaImage> {
+        match self {
+            LogoSource::Svg(tree, tree_size) => render_svg(tree, *tree_size, size),
+            LogoSource::Raster(image) => Ok(image.resize(size, size, FilterType::Lanczos3).to_rgba8()),
+        }
+    }
+}
+
+/// Render the SVG tree provided, fitting it into a square of the size
+/// provided.
+#[allow(clippy::cast_precision_loss)]
+fn render_svg(tree: &resvg::Tree, tree_size: IntSize, size: u32) -> Result<RgbaImage> {
+    let target_size = tree_size.scale_to(IntSize::from_wh(size, size).context("invalid size")?);
+    let mut pixmap = Pixmap::new(target_size.width(), target_size.height()).context("invalid pixmap size")?;
+    let transform = Transform::from_scale(
+        target_size.width() as f32 / tree_size.width() as f32,
+        target_size.height() as f32 / tree_size.height() as f32,
+    );
+    tree.render(transform, &mut pixmap.as_mut());
+    pixmap_to_image(&pixmap)
+}
+
+/// Convert the pixmap provided into an image.
+pub(crate) fn pixmap_to_image(pixmap: &Pixmap) -> Result<RgbaImage> {
+    // Pixmaps use premultiplied alpha, so we need to demultiply the pixels
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixels)
+        .ok_or_else(|| format_err!("error converting rendered image"))
+}
+
+/// Get logo extension (lowercased) from its file name.
+fn get_logo_extension(file_name: &str) -> String {
+    Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Check if the viewbox of the logo provided should be adjusted.
+fn should_adjust_viewbox(logos_viewbox: &LogosViewbox, file_name: &str) -> bool {
+    logos_viewbox.adjust && !logos_viewbox.exclude.contains(&file_name.to_string())
+}
+
 /// Get SVG bounding box (smallest rectangle in which the object fits).
 fn get_svg_bounding_box(svg_data: &[u8]) -> Result<Option<Rect>> {
     let opt = usvg::Options::default();
@@ -131,3 +350,214 @@ fn get_svg_bounding_box(svg_data: &[u8]) -> Result<Option<Rect>> {
 
     Ok(bounding_box)
 }
+
+/// Optimize the SVG logo provided by re-serializing it from its usvg tree. This
+/// removes metadata, editor namespaces and comments, collapses redundant
+/// groups and rounds coordinates. The original logo is returned if it cannot
+/// be parsed or the optimized version isn't smaller.
+fn optimize_svg(svg_data: Vec<u8>) -> Vec<u8> {
+    let Ok(tree) = usvg::Tree::from_data(&svg_data, &usvg::Options::default()) else {
+        return svg_data;
+    };
+    collapse_groups(&tree.root);
+    let xml_options = XmlOptions {
+        coordinates_precision: OPTIMIZE_COORDINATES_PRECISION,
+        transforms_precision: OPTIMIZE_TRANSFORMS_PRECISION,
+        writer_opts: xmlwriter::Options {
+            indent: xmlwriter::Indent::None,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let optimized_data = tree.to_string(&xml_options).into_bytes();
+
+    if optimized_data.len() < svg_data.len() {
+        optimized_data
+    } else {
+        svg_data
+    }
+}
+
+/// Collapse the groups in the SVG tree that have no effect on their children
+/// (no id, transform, opacity, clip path, mask or filters), moving the
+/// children to the group's parent.
+fn collapse_groups(root: &usvg::Node) {
+    let redundant_groups: Vec<usvg::Node> = root
+        .descendants()
+        .skip(1)
+        .filter(|node| match &*node.borrow() {
+            NodeKind::Group(group) => {
+                group.id.is_empty() && group.transform.is_identity() && !group.should_isolate()
+            }
+            _ => false,
+        })
+        .collect();
+    for group in redundant_groups {
+        for child in group.children().collect::<Vec<_>>() {
+            child.detach();
+            group.insert_before(child);
+        }
+        group.detach();
+    }
+}
+
+/// Check if the SVG tree node provided (or any of its descendants) contains
+/// raster images.
+fn has_raster_images(root: &usvg::Node) -> bool {
+    for node in root.descendants() {
+        if let NodeKind::Image(image) = &*node.borrow() {
+            match &image.kind {
+                ImageKind::JPEG(_) | ImageKind::PNG(_) | ImageKind::GIF(_) => return true,
+                ImageKind::SVG(tree) => {
+                    if has_raster_images(&tree.root) {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        // Check also clip paths, masks and patterns
+        let mut found = false;
+        node.subroots(|subroot| found = found || has_raster_images(&subroot));
+        if found {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+
+    use super::*;
+
+    const PNG_PIXEL: &str =
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8BQDwAEhQGAhKmMIQAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn prepare_logo_optimize_svg() {
+        let svg = r##"<?xml version="1.0"?>
+<!-- Generator: Editor -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:editor="http://editor.example.com" viewBox="0 0 10 10">
+    <metadata>Some metadata</metadata>
+    <g>
+        <g editor:label="layer">
+            <rect x="1.123456789" y="1" width="5" height="5" fill="#ff0000"/>
+        </g>
+    </g>
+</svg>"##;
+        let logos_viewbox = LogosViewbox {
+            adjust: false,
+            exclude: vec![],
+        };
+        let logo = prepare_logo(svg.as_bytes().to_vec(), &logos_viewbox, true, "logo.svg");
+        let optimized = String::from_utf8(logo.data).unwrap();
+
+        assert!(optimized.len() < svg.len());
+        assert!(!optimized.contains("metadata"));
+        assert!(!optimized.contains("editor"));
+        assert!(!optimized.contains("<g"));
+        assert!(optimized.contains("1.123"));
+        assert!(!optimized.contains("1.1234"));
+        assert!(optimized.contains(r#"viewBox="0 0 10 10""#));
+    }
+
+    #[test]
+    fn prepare_logo_optimize_svg_invalid() {
+        let svg = b"<svg>invalid".to_vec();
+        let logo = prepare_logo(svg.clone(), &LogosViewbox::default(), true, "logo.svg");
+        assert_eq!(logo.data, svg);
+    }
+
+    #[test]
+    fn audit_logo_valid() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect width="10" height="10"/></svg>"#;
+        assert!(audit_logo(svg.as_bytes(), "logo.svg").is_empty());
+    }
+
+    #[test]
+    fn audit_logo_detects_problems() {
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><text x="0" y="5">Logo</text><image width="10" height="10" href="data:image/png;base64,{PNG_PIXEL}"/></svg>"#
+        );
+        assert_eq!(
+            audit_logo(svg.as_bytes(), "logo.svg"),
+            vec![
+                "logo viewBox is missing",
+                "logo embeds raster images",
+                "logo uses text elements (the fonts may not be available)",
+            ]
+        );
+    }
+
+    #[test]
+    fn audit_logo_degenerate_viewbox_and_oversized() {
+        let padding = " ".repeat(LOGO_MAX_SIZE);
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 0 10"><rect width="10" height="10"/>{padding}</svg>"#
+        );
+        assert_eq!(
+            audit_logo(svg.as_bytes(), "logo.svg"),
+            vec![
+                "logo is too big (201 KB, max 200 KB)",
+                "logo viewBox is degenerate (0 0 0 10)"
+            ]
+        );
+    }
+
+    #[test]
+    fn audit_logo_raster_not_decodable() {
+        let problems = audit_logo(b"not a png", "logo.png");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("logo could not be decoded"));
+    }
+
+    #[test]
+    fn prepare_logo_derivatives_svg() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 20 10"><rect width="20" height="10"/></svg>"#;
+        let logo = prepare_logo(
+            svg.as_bytes().to_vec(),
+            &LogosViewbox::default(),
+            false,
+            "logo.svg",
+        );
+
+        let derivatives = prepare_logo_derivatives(&logo, &[32, 64]).unwrap();
+        let summary: Vec<_> = derivatives
+            .iter()
+            .map(|d| {
+                let image = image::load_from_memory(&d.data).unwrap();
+                (d.extension.as_str(), d.size, image.width(), image.height())
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("png", 32, 32, 16),
+                ("webp", 32, 32, 16),
+                ("png", 64, 64, 32),
+                ("webp", 64, 64, 32),
+            ]
+        );
+    }
+
+    #[test]
+    fn prepare_logo_derivatives_raster() {
+        let png = base64::engine::general_purpose::STANDARD.decode(PNG_PIXEL).unwrap();
+        let logo = prepare_logo(png, &LogosViewbox::default(), false, "logo.png");
+
+        let derivatives = prepare_logo_derivatives(&logo, &[16]).unwrap();
+        assert_eq!(derivatives.len(), 2);
+        for derivative in derivatives {
+            let image = image::load_from_memory(&derivative.data).unwrap();
+            assert_eq!((image.width(), image.height()), (16, 16));
+        }
+    }
+
+    #[test]
+    fn prepare_logo_derivatives_unsupported_format() {
+        let logo = prepare_logo(b"data".to_vec(), &LogosViewbox::default(), false, "logo.bmp");
+        assert!(prepare_logo_derivatives(&logo, &[16]).is_err());
+    }
+}
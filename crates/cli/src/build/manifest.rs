@@ -0,0 +1,325 @@
+//! This module defines the build manifest used to support incremental builds.
+//!
+//! The manifest is written to the output directory at the end of each build.
+//! It records a digest of the inputs used to generate each of the build steps
+//! outputs, as well as the logos prepared. On subsequent builds, the steps
+//! whose inputs haven't changed (and whose outputs are still present in the
+//! output directory) are skipped.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::Result;
+use landscape2_core::data::ItemLogoDerivative;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+/// File name of the build manifest in the output directory.
+const MANIFEST_FILE: &str = ".build-manifest.json";
+
+/// Build manifest.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BuildManifest {
+    pub version: String,
+
+    #[serde(default)]
+    pub logos: BTreeMap<LogoFileName, LogoEntry>,
+
+    #[serde(default)]
+    pub steps: BTreeMap<StepName, StepEntry>,
+}
+
+/// Type alias to represent the file name of a logo in the logos source.
+pub(crate) type LogoFileName = String;
+
+/// Type alias to represent the name of a build step.
+pub(crate) type StepName = String;
+
+/// Information about a logo prepared during the build.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LogoEntry {
+    pub path: String,
+    pub source_digest: String,
+
+    /// Size of the logo source (in bytes).
+    #[serde(default)]
+    pub source_size: usize,
+
+    /// Size of the logo written to the output directory (in bytes).
+    #[serde(default)]
+    pub size: usize,
+
+    /// Raster derivatives generated from the logo.
+    #[serde(default)]
+    pub derivatives: Vec<ItemLogoDerivative>,
+}
+
+/// Information about the outputs generated by a build step.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StepEntry {
+    pub digest: String,
+    pub outputs: Vec<String>,
+}
+
+impl BuildManifest {
+    /// Create a new empty build manifest.
+    pub(crate) fn new() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Load the build manifest available in the output directory provided, if
+    /// any. The manifest file is removed after reading it, so that a build that
+    /// fails midway doesn't leave behind a manifest that doesn't match the
+    /// content of the output directory. As a consequence, the build following
+    /// a failed one is always a full build.
+    pub(crate) fn load(output_dir: &Path) -> Self {
+        let path = output_dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Self::new();
+        }
+
+        let manifest = match fs::read(&path).map(|data| serde_json::from_slice::<BuildManifest>(&data)) {
+            Ok(Ok(manifest)) => manifest,
+            Ok(Err(err)) => {
+                warn!(?err, "error parsing build manifest, doing a full build");
+                Self::new()
+            }
+            Err(err) => {
+                warn!(?err, "error reading build manifest, doing a full build");
+                Self::new()
+            }
+        };
+        if let Err(err) = fs::remove_file(&path) {
+            warn!(?err, "error removing previous build manifest");
+        }
+
+        // Manifests generated by a different version of the tool are ignored,
+        // as the outputs may have been generated differently
+        if manifest.version != env!("CARGO_PKG_VERSION") {
+            debug!("build manifest generated by a different version, doing a full build");
+            return Self::new();
+        }
+
+        manifest
+    }
+
+    /// Write the build manifest to the output directory provided.
+    pub(crate) fn save(&self, output_dir: &Path) -> Result<()> {
+        fs::write(output_dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Return the entry of the logo provided if it was prepared in this build
+    /// from the same source and it's still present in the output directory
+    /// (including its derivatives).
+    pub(crate) fn logo(&self, file_name: &str, source_digest: &str, output_dir: &Path) -> Option<&LogoEntry> {
+        let entry = self.logos.get(file_name)?;
+        if entry.source_digest != source_digest
+            || !output_dir.join(&entry.path).exists()
+            || !entry.derivatives.iter().all(|d| output_dir.join(&d.path).exists())
+        {
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Track a logo prepared during the build.
+    pub(crate) fn track_logo(&mut self, file_name: &str, entry: LogoEntry) {
+        self.logos.insert(file_name.to_string(), entry);
+    }
+
+    /// Return the outputs of the step provided if they were generated in this
+    /// build from the same inputs and they are still present in the output
+    /// directory.
+    pub(crate) fn step_outputs(&self, step: &str, digest: &str, output_dir: &Path) -> Option<Vec<String>> {
+        let entry = self.steps.get(step)?;
+        if entry.digest != digest || !entry.outputs.iter().all(|output| output_dir.join(output).exists()) {
+            return None;
+        }
+        debug!(?step, "inputs haven't changed, skipping step");
+        Some(entry.outputs.clone())
+    }
+
+    /// Track the outputs generated by a build step.
+    pub(crate) fn track_step(&mut self, step: &str, digest: String, outputs: Vec<String>) {
+        self.steps.insert(step.to_string(), StepEntry { digest, outputs });
+    }
+}
+
+/// Calculate the digest of the value provided.
+///
+/// The value is converted to a JSON value before computing the digest, so that
+/// maps keys are always sorted and the digest is stable across builds.
+pub(crate) fn digest<T>(value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let data = serde_json::to_vec(&serde_json::to_value(value)?)?;
+    Ok(hex::encode(Sha256::digest(data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    const DIGEST: &str = "digest";
+    const STEP: &str = "step";
+
+    fn manifest_with_step(outputs: &[&str]) -> BuildManifest {
+        let mut manifest = BuildManifest::new();
+        manifest.track_step(
+            STEP,
+            DIGEST.to_string(),
+            outputs.iter().map(ToString::to_string).collect(),
+        );
+        manifest
+    }
+
+    #[test]
+    fn step_outputs_digest_matches() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::write(output_dir.join("file.json"), b"{}").unwrap();
+
+        let manifest = manifest_with_step(&["file.json"]);
+        let outputs = manifest.step_outputs(STEP, DIGEST, output_dir);
+
+        assert_eq!(outputs, Some(vec!["file.json".to_string()]));
+    }
+
+    #[test]
+    fn step_outputs_digest_mismatch() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::write(output_dir.join("file.json"), b"{}").unwrap();
+
+        let manifest = manifest_with_step(&["file.json"]);
+        let outputs = manifest.step_outputs(STEP, "other-digest", output_dir);
+
+        assert_eq!(outputs, None);
+    }
+
+    #[test]
+    fn step_outputs_output_file_missing() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::write(output_dir.join("file.json"), b"{}").unwrap();
+
+        let manifest = manifest_with_step(&["file.json", "missing.json"]);
+        let outputs = manifest.step_outputs(STEP, DIGEST, output_dir);
+
+        assert_eq!(outputs, None);
+    }
+
+    #[test]
+    fn step_outputs_unknown_step() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+
+        let manifest = manifest_with_step(&[]);
+        let outputs = manifest.step_outputs("other-step", DIGEST, output_dir);
+
+        assert_eq!(outputs, None);
+    }
+
+    #[test]
+    fn load_and_save_roundtrip() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::write(output_dir.join("file.json"), b"{}").unwrap();
+
+        // Save manifest and load it back
+        let manifest = manifest_with_step(&["file.json"]);
+        manifest.save(output_dir).unwrap();
+        let loaded_manifest = BuildManifest::load(output_dir);
+
+        // The manifest file is removed once loaded
+        let manifest_file_exists = output_dir.join(MANIFEST_FILE).exists();
+
+        assert_eq!(loaded_manifest, manifest);
+        assert!(!manifest_file_exists);
+    }
+
+    #[test]
+    fn load_version_mismatch() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::write(output_dir.join("file.json"), b"{}").unwrap();
+
+        // Save manifest generated by a different version and load it back
+        let manifest = BuildManifest {
+            version: "0.0.0".to_string(),
+            ..manifest_with_step(&["file.json"])
+        };
+        manifest.save(output_dir).unwrap();
+        let loaded_manifest = BuildManifest::load(output_dir);
+        let outputs = loaded_manifest.step_outputs(STEP, DIGEST, output_dir);
+
+        assert_eq!(loaded_manifest, BuildManifest::new());
+        assert_eq!(outputs, None);
+    }
+
+    #[test]
+    fn load_invalid_manifest() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::write(output_dir.join(MANIFEST_FILE), b"invalid").unwrap();
+
+        let loaded_manifest = BuildManifest::load(output_dir);
+
+        assert_eq!(loaded_manifest, BuildManifest::new());
+    }
+
+    #[test]
+    fn logo_reused_when_source_and_outputs_match() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path();
+        fs::create_dir_all(output_dir.join("logos")).unwrap();
+        fs::write(output_dir.join("logos").join("logo.svg"), b"<svg/>").unwrap();
+        fs::write(output_dir.join("logos").join("logo-64.png"), b"png").unwrap();
+
+        let mut manifest = BuildManifest::new();
+        let entry = LogoEntry {
+            path: "logos/logo.svg".to_string(),
+            source_digest: DIGEST.to_string(),
+            derivatives: vec![ItemLogoDerivative {
+                format: "png".to_string(),
+                path: "logos/logo-64.png".to_string(),
+                size: 64,
+            }],
+            ..Default::default()
+        };
+        manifest.track_logo("logo.svg", entry.clone());
+
+        // Same source and all outputs present
+        assert_eq!(manifest.logo("logo.svg", DIGEST, output_dir), Some(&entry));
+
+        // Unknown logo or different source
+        assert_eq!(manifest.logo("other.svg", DIGEST, output_dir), None);
+        assert_eq!(manifest.logo("logo.svg", "other-digest", output_dir), None);
+
+        // Derivative missing from the output directory
+        fs::remove_file(output_dir.join("logos").join("logo-64.png")).unwrap();
+        assert_eq!(manifest.logo("logo.svg", DIGEST, output_dir), None);
+
+        // Logo missing from the output directory
+        fs::remove_file(output_dir.join("logos").join("logo.svg")).unwrap();
+        manifest.logos.get_mut("logo.svg").unwrap().derivatives.clear();
+        assert_eq!(manifest.logo("logo.svg", DIGEST, output_dir), None);
+    }
+
+    #[test]
+    fn digest_is_stable_regardless_of_keys_order() {
+        let a = BTreeMap::from([("a", 1), ("b", 2)]);
+        let b = HashMap::from([("b", 2), ("a", 1)]);
+        assert_eq!(digest(&a).unwrap(), digest(&b).unwrap());
+        assert_ne!(digest(&a).unwrap(), digest(&BTreeMap::from([("a", 2)])).unwrap());
+    }
+}
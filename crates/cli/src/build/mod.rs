@@ -1,63 +1,74 @@
 //! This module defines the functionality of the build CLI subcommand.
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     ffi::OsStr,
     fs::{self, File},
     io::Write,
-    net::TcpListener,
     path::{Path, PathBuf},
-    sync::Arc,
-    time::{Duration, Instant},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Instant,
 };
 
 use anyhow::{Context, Result, bail};
 use askama::Template;
-use base64::{Engine as _, engine::general_purpose::STANDARD as b64};
 use futures::stream::{self, StreamExt};
-use headless_chrome::{
-    Browser, LaunchOptions, browser,
-    protocol::cdp::Page::{self, CaptureScreenshotFormatOption},
-    types::PrintToPdfOptions,
-};
 use landscape2_core::{
-    data::{self, CrunchbaseData, DataSource, GithubData, Item, LandscapeData},
+    data::{
+        self, CrunchbaseData, DataSource, GithubData, Item, ItemLogoDerivative, ItemsAliases, LandscapeData,
+    },
     datasets::{Datasets, NewDatasetsInput, embed::EmbedView, full::Full},
     games::{GamesSource, LandscapeGames},
     guide::{GuideSource, LandscapeGuide},
-    settings::{self, Analytics, Colors, LandscapeSettings, LogosViewbox, Osano, SettingsSource},
+    search::SearchIndex,
+    settings::{self, Analytics, Colors, LandscapeSettings, Osano, SettingsSource},
 };
 use qrcode::render::svg;
 use reqwest::StatusCode;
 use rust_embed::{EmbeddedFile, RustEmbed};
 use tokio::sync::Mutex;
-use tracing::{debug, error, info, instrument, trace, warn};
+use tracing::{debug, error, info, instrument, warn};
 use url::Url;
 
-use crate::{
-    build::{
-        api::{Api, ApiSources},
-        projects::collect_projects,
-    },
-    serve::{self, serve},
+use crate::build::{
+    api::{Api, ApiSources},
+    projects::collect_projects,
 };
 
 use self::{
+    aliases::{prepare_items_aliases, write_items_aliases},
     cache::Cache,
+    cards::render_items_cards,
+    changes::prepare_changes,
     crunchbase::collect_crunchbase_data,
     export::generate_items_csv,
     github::collect_github_data,
-    logos::{LogosSource, prepare_logo},
+    logos::{
+        LogosSource, audit_logo, get_logo, get_logo_source_digest, prepare_logo, prepare_logo_derivatives,
+    },
+    manifest::{BuildManifest, LogoEntry, digest},
+    pages::render_items_pages,
+    poster::render_posters,
     projects::{ProjectsMd, generate_projects_csv},
 };
 
-mod api;
-mod cache;
+pub(crate) mod aliases;
+pub(crate) mod api;
+pub(crate) mod cache;
+mod cards;
+mod changes;
 mod clomonitor;
-mod crunchbase;
+pub(crate) mod crunchbase;
 mod export;
 mod github;
-mod logos;
+pub(crate) mod logos;
+mod manifest;
+mod openapi;
+mod pages;
+mod poster;
 mod projects;
 
 /// Maximum number of CLOMonitor reports summaries to fetch concurrently.
@@ -81,6 +92,24 @@ const IMAGES_PATH: &str = "images";
 /// Path where the item logos will be written to in the output directory.
 const LOGOS_PATH: &str = "logos";
 
+/// Path where the screenshots configured will be written to (in the docs
+/// directory).
+const SCREENSHOTS_PATH: &str = "screenshots";
+
+/// Names of the build steps tracked in the build manifest.
+const STEP_ALIASES: &str = "aliases";
+const STEP_API: &str = "api";
+const STEP_ASSETS: &str = "assets";
+const STEP_CARDS: &str = "cards";
+const STEP_DATASETS: &str = "datasets";
+const STEP_DOCS: &str = "docs";
+const STEP_GAMES: &str = "games";
+const STEP_GUIDE: &str = "guide";
+const STEP_HTML: &str = "html";
+const STEP_PAGES: &str = "pages";
+const STEP_SCREENSHOT: &str = "screenshot";
+const STEP_SEARCH: &str = "search";
+
 /// Path where the data sources files will be written to in the output dir.
 const SOURCES_PATH: &str = "sources";
 
@@ -112,10 +141,23 @@ pub struct BuildArgs {
     #[arg(long)]
     pub cache_dir: Option<PathBuf>,
 
+    /// Full dataset of a previous build (data/full.json), usually the one of
+    /// the landscape currently deployed, used as the baseline to compute the
+    /// landscape changes and the items ids aliases (local path or url). When
+    /// not provided, the full dataset left in the output directory by the
+    /// previous build (if any) is used instead.
+    #[arg(long)]
+    pub baseline: Option<String>,
+
     /// Data source.
     #[command(flatten)]
     pub data_source: DataSource,
 
+    /// Regenerate all output files, ignoring the manifest of the previous
+    /// build available in the output directory.
+    #[arg(long, default_value_t = false)]
+    pub full_rebuild: bool,
+
     /// Games source.
     #[command(flatten)]
     pub games_source: GamesSource,
@@ -135,6 +177,14 @@ pub struct BuildArgs {
     /// Settings source.
     #[command(flatten)]
     pub settings_source: SettingsSource,
+
+    /// Fail the build if any problems are found when auditing the logos.
+    #[arg(long, default_value_t = false)]
+    pub strict_logos: bool,
+
+    /// Optimize SVG logos (removing unnecessary data and rounding coordinates).
+    #[arg(long, default_value_t = false)]
+    pub optimize_logos: bool,
 }
 
 /// Build landscape website.
@@ -142,6 +192,7 @@ pub struct BuildArgs {
 /// # Errors
 /// Returns an error if required assets are missing, inputs are invalid, or any
 /// data processing step fails.
+#[allow(clippy::too_many_lines)]
 #[instrument(skip_all)]
 pub async fn build(args: &BuildArgs) -> Result<()> {
     info!("building landscape website..");
@@ -153,6 +204,14 @@ pub async fn build(args: &BuildArgs) -> Result<()> {
     // Setup output directory, creating it when needed
     setup_output_dir(&args.output_dir)?;
 
+    // Load previous build manifest (if available) and setup the new one
+    let prev_manifest = if args.full_rebuild {
+        BuildManifest::new()
+    } else {
+        BuildManifest::load(&args.output_dir)
+    };
+    let mut manifest = BuildManifest::new();
+
     // Setup cache
     let cache = Cache::new(args.cache_dir.as_ref())?;
 
@@ -163,17 +222,30 @@ pub async fn build(args: &BuildArgs) -> Result<()> {
     let mut settings = LandscapeSettings::new(&args.settings_source).await?;
 
     // Prepare games data and copy it to the output directory
-    let games = prepare_games_data(&args.games_source, &args.output_dir).await?;
+    let games = prepare_games_data(
+        &args.games_source,
+        &prev_manifest,
+        &mut manifest,
+        &args.output_dir,
+    )
+    .await?;
 
     // Prepare guide and copy it to the output directory
-    let guide = prepare_guide(&args.guide_source, &args.output_dir).await?;
+    let guide = prepare_guide(
+        &args.guide_source,
+        &prev_manifest,
+        &mut manifest,
+        &args.output_dir,
+    )
+    .await?;
 
     // Prepare items logos and copy them to the output directory
-    prepare_items_logos(
-        &args.logos_source,
-        &settings.logos_viewbox,
+    let logos_sizes = prepare_items_logos(
+        args,
+        &settings,
         &mut landscape_data,
-        &args.output_dir,
+        &prev_manifest,
+        &mut manifest,
     )
     .await?;
 
@@ -198,69 +270,221 @@ pub async fn build(args: &BuildArgs) -> Result<()> {
     // Collect CLOMonitor reports summaries and copy them to the output directory
     collect_clomonitor_reports(&cache, &mut landscape_data, &settings, &args.output_dir).await?;
 
-    // Generate API data files
-    generate_api(
-        &ApiSources {
-            landscape_data: &landscape_data,
-            settings: &settings,
-        },
+    // Baseline used to prepare the items ids aliases and the changelog: the
+    // one provided, or the full dataset of the previous build available in
+    // the output directory
+    let prev_full = args.output_dir.join(DATASETS_PATH).join("full.json");
+    let baseline = args.baseline.clone().unwrap_or_else(|| prev_full.to_string_lossy().into_owned());
+
+    // Prepare items ids aliases from the baseline (if available)
+    let items_aliases = prepare_items_aliases(&baseline, &landscape_data).await.unwrap_or_else(|err| {
+        warn!(
+            ?err,
+            "error preparing items aliases, no aliases will be available"
+        );
+        ItemsAliases::default()
+    });
+    let aliases_output = write_items_aliases(&items_aliases, &args.output_dir)?;
+    manifest.track_step(STEP_ALIASES, digest(&items_aliases)?, vec![aliases_output]);
+
+    // Prepare changelog and changes feed from the baseline (an empty
+    // changelog is started when it's not available)
+    let changelog = match prepare_changes(
+        &baseline,
+        &landscape_data,
+        &settings.foundation,
+        &settings.url,
         &args.output_dir,
-    )?;
+    )
+    .await
+    {
+        Ok(changelog) => Some(changelog),
+        Err(err) => {
+            warn!(?err, "error preparing changes, no changelog will be generated");
+            None
+        }
+    };
+
+    // Generate API data files
+    let api_digest = digest(&(
+        &landscape_data,
+        &settings,
+        &crunchbase_data,
+        &github_data,
+        &changelog,
+        &items_aliases,
+    ))?;
+    let api_outputs = match prev_manifest.step_outputs(STEP_API, &api_digest, &args.output_dir) {
+        Some(outputs) => outputs,
+        None => generate_api(
+            &ApiSources {
+                changelog: changelog.as_ref(),
+                items_aliases: &items_aliases,
+                landscape_data: &landscape_data,
+                settings: &settings,
+            },
+            &args.output_dir,
+        )?,
+    };
+    manifest.track_step(STEP_API, api_digest, api_outputs);
 
     // Generate QR code
     let qr_code = generate_qr_code(&settings.url, &args.output_dir)?;
 
     // Generate datasets for web application
-    let datasets = generate_datasets(
-        &NewDatasetsInput {
-            crunchbase_data: &crunchbase_data,
-            games: &games,
-            github_data: &github_data,
-            guide: &guide,
-            landscape_data: &landscape_data,
-            qr_code: &qr_code,
-            settings: &settings,
-        },
-        &args.output_dir,
-    )?;
+    let datasets = Datasets::new(&NewDatasetsInput {
+        crunchbase_data: &crunchbase_data,
+        games: &games,
+        github_data: &github_data,
+        guide: &guide,
+        items_aliases: &items_aliases,
+        landscape_data: &landscape_data,
+        qr_code: &qr_code,
+        settings: &settings,
+    });
+    let datasets_digest = digest(&(&datasets.base, &datasets.embed, &datasets.full, &datasets.stats))?;
+    let datasets_outputs = match prev_manifest.step_outputs(STEP_DATASETS, &datasets_digest, &args.output_dir)
+    {
+        Some(outputs) => outputs,
+        None => write_datasets(&datasets, &args.output_dir)?,
+    };
+    manifest.track_step(STEP_DATASETS, datasets_digest.clone(), datasets_outputs);
+
+    // Generate full-text search index
+    let search_digest = digest(&(&landscape_data, &guide))?;
+    let search_outputs = match prev_manifest.step_outputs(STEP_SEARCH, &search_digest, &args.output_dir) {
+        Some(outputs) => outputs,
+        None => vec![generate_search_index(
+            &landscape_data,
+            guide.as_ref(),
+            &args.output_dir,
+        )?],
+    };
+    manifest.track_step(STEP_SEARCH, search_digest, search_outputs);
+
+    // Copy embed and web application assets files to the output directory
+    let assets_digest = get_web_assets_digest()?;
+    let assets_outputs =
+        if let Some(outputs) = prev_manifest.step_outputs(STEP_ASSETS, &assets_digest, &args.output_dir) {
+            outputs
+        } else {
+            let mut outputs = copy_embed_assets(&args.output_dir)?;
+            outputs.extend(copy_webapp_assets(&args.output_dir)?);
+            outputs
+        };
+    manifest.track_step(STEP_ASSETS, assets_digest.clone(), assets_outputs);
 
     // Render index, embed-item, and kiosk html files and write them to the
     // output dir
-    render_index_html(
-        settings.analytics.as_ref(),
-        &datasets,
-        settings.osano.as_ref(),
-        &args.output_dir,
-    )?;
-    render_embed_item_html(settings.colors.as_ref(), &args.output_dir)?;
-    render_kiosk_html(
+    let html_digest = digest(&(
+        &datasets_digest,
+        &assets_digest,
+        &settings.analytics,
+        &settings.colors,
         &settings.foundation,
+        &settings.osano,
         &settings.url,
-        settings.colors.as_ref(),
-        &args.output_dir,
-    )?;
-
-    // Copy embed and web application assets files to the output directory
-    copy_embed_assets(&args.output_dir)?;
-    copy_webapp_assets(&args.output_dir)?;
-
-    // Generate items.csv file
-    generate_items_csv_file(&landscape_data, &args.output_dir)?;
+    ))?;
+    let html_outputs = match prev_manifest.step_outputs(STEP_HTML, &html_digest, &args.output_dir) {
+        Some(outputs) => outputs,
+        None => vec![
+            render_index_html(
+                settings.analytics.as_ref(),
+                &datasets,
+                settings.osano.as_ref(),
+                &args.output_dir,
+            )?,
+            render_embed_item_html(settings.colors.as_ref(), &args.output_dir)?,
+            render_kiosk_html(
+                &settings.foundation,
+                &settings.url,
+                settings.colors.as_ref(),
+                &args.output_dir,
+            )?,
+        ],
+    };
+    manifest.track_step(STEP_HTML, html_digest.clone(), html_outputs);
 
-    // Generate projects.* files
-    generate_projects_files(&landscape_data, &args.output_dir)?;
+    // Generate items.csv and projects.* files
+    let docs_digest = digest(&(&landscape_data, &crunchbase_data, &github_data))?;
+    let docs_outputs =
+        if let Some(outputs) = prev_manifest.step_outputs(STEP_DOCS, &docs_digest, &args.output_dir) {
+            outputs
+        } else {
+            let mut outputs = vec![generate_items_csv_file(&landscape_data, &args.output_dir)?];
+            outputs.extend(generate_projects_files(&landscape_data, &args.output_dir)?);
+            outputs
+        };
+    manifest.track_step(STEP_DOCS, docs_digest, docs_outputs);
 
-    // Prepare landscape screenshot (in PNG and PDF formats)
-    if let Some(width) = &settings.screenshot_width {
-        prepare_screenshot(*width, &args.output_dir).await?;
+    // Render items social cards
+    let cards_digest = digest(&(&landscape_data, &settings.colors, &settings.foundation))?;
+    let cards_outputs = match prev_manifest.step_outputs(STEP_CARDS, &cards_digest, &args.output_dir) {
+        Some(outputs) => outputs,
+        None => render_items_cards(&landscape_data, &settings, &args.output_dir).await?,
+    };
+    manifest.track_step(STEP_CARDS, cards_digest, cards_outputs.clone());
+
+    // Render items pages, sitemap and robots.txt files
+    let pages_digest = digest(&(
+        &landscape_data,
+        &cards_outputs,
+        &settings.base_path,
+        &settings.colors,
+        &settings.foundation,
+        &settings.images,
+        &settings.url,
+    ))?;
+    let pages_outputs = match prev_manifest.step_outputs(STEP_PAGES, &pages_digest, &args.output_dir) {
+        Some(outputs) => outputs,
+        None => render_items_pages(&landscape_data, &settings, &cards_outputs, &args.output_dir)?,
+    };
+    manifest.track_step(STEP_PAGES, pages_digest, pages_outputs);
+
+    // Render landscape screenshots (the full landscape one, available for
+    // download from the web application, and the ones configured)
+    let mut screenshots = vec![];
+    if let Some(width) = settings.screenshot_width {
+        let screenshot = settings::Screenshot {
+            width,
+            ..Default::default()
+        };
+        screenshots.push((screenshot, Path::new(DOCS_PATH).join("landscape")));
+    }
+    for screenshot in settings.screenshots.iter().flatten() {
+        let path = Path::new(DOCS_PATH).join(SCREENSHOTS_PATH).join(screenshot.name());
+        screenshots.push((screenshot.clone(), path));
+    }
+    if !screenshots.is_empty() {
+        let screenshots_digest = digest(&(
+            &landscape_data,
+            &settings.categories,
+            &settings.colors,
+            &settings.featured_items,
+            &settings.grid_items_size,
+            &settings.groups,
+            &screenshots,
+        ))?;
+        let screenshots_outputs =
+            match prev_manifest.step_outputs(STEP_SCREENSHOT, &screenshots_digest, &args.output_dir) {
+                Some(outputs) => outputs,
+                None => render_posters(&landscape_data, &settings, &screenshots, &args.output_dir).await?,
+            };
+        manifest.track_step(STEP_SCREENSHOT, screenshots_digest, screenshots_outputs);
     }
 
     // Copy data sources files to the output directory
     copy_data_sources_files(args, &args.output_dir).await?;
 
+    // Write build manifest to the output directory
+    manifest.save(&args.output_dir)?;
+
     let duration = start.elapsed().as_secs_f64();
     info!("landscape website built! (took: {:.3}s)", duration);
-    display_success_msg(&args.output_dir.to_string_lossy());
+    display_success_msg(
+        &args.output_dir.to_string_lossy(),
+        args.optimize_logos.then_some(logos_sizes),
+    );
 
     Ok(())
 }
@@ -402,24 +626,26 @@ async fn copy_data_sources_files(args: &BuildArgs, output_dir: &Path) -> Result<
     Ok(())
 }
 
-/// Copy embed assets files to the output directory.
+/// Copy embed assets files to the output directory, returning the paths of the
+/// files copied.
 #[instrument(skip_all, err)]
-fn copy_embed_assets(output_dir: &Path) -> Result<()> {
+fn copy_embed_assets(output_dir: &Path) -> Result<Vec<String>> {
     debug!("copying embed assets to output directory");
 
-    let copy_embed_asset = |path: &str, embedded_file: EmbeddedFile| -> Result<()> {
+    let copy_embed_asset = |path: &str, embedded_file: EmbeddedFile| -> Result<String> {
         let path = Path::new(EMBED_PATH).join(path);
         if let Some(parent_path) = path.parent() {
             fs::create_dir_all(output_dir.join(parent_path))?;
         }
-        let mut file = File::create(output_dir.join(path))?;
+        let mut file = File::create(output_dir.join(&path))?;
         file.write_all(&embedded_file.data)?;
-        Ok(())
+        Ok(path.to_string_lossy().into_owned())
     };
 
+    let mut outputs = vec![];
     for path in EmbedAssets::iter() {
         if let Some(embedded_file) = EmbedAssets::get(&path) {
-            copy_embed_asset(&path, embedded_file)?;
+            outputs.push(copy_embed_asset(&path, embedded_file)?);
         }
     }
     for path in EmbedItemAssets::iter() {
@@ -428,18 +654,20 @@ fn copy_embed_assets(output_dir: &Path) -> Result<()> {
             continue;
         }
         if let Some(embedded_file) = EmbedItemAssets::get(&path) {
-            copy_embed_asset(&path, embedded_file)?;
+            outputs.push(copy_embed_asset(&path, embedded_file)?);
         }
     }
 
-    Ok(())
+    Ok(outputs)
 }
 
-/// Copy web application assets files to the output directory.
+/// Copy web application assets files to the output directory, returning the
+/// paths of the files copied.
 #[instrument(skip_all, err)]
-fn copy_webapp_assets(output_dir: &Path) -> Result<()> {
+fn copy_webapp_assets(output_dir: &Path) -> Result<Vec<String>> {
     debug!("copying web application assets to output directory");
 
+    let mut outputs = vec![];
     for path in WebappAssets::iter() {
         if path == "index.html" || path == ".keep" {
             // This file is a template that will be rendered later on
@@ -452,14 +680,24 @@ fn copy_webapp_assets(output_dir: &Path) -> Result<()> {
             }
             let mut file = File::create(output_dir.join(path.as_ref()))?;
             file.write_all(&embedded_file.data)?;
+            outputs.push(path.into_owned());
         }
     }
 
-    Ok(())
+    Ok(outputs)
 }
 
 /// Display build success message.
-fn display_success_msg(output_dir: &str) {
+#[allow(clippy::cast_precision_loss)]
+fn display_success_msg(output_dir: &str, logos_sizes: Option<LogosSizes>) {
+    if let Some(sizes) = logos_sizes {
+        let saved = sizes.source.saturating_sub(sizes.output) as f64 / sizes.source.max(1) as f64 * 100.0;
+        println!(
+            "\n🗜️  Logos optimized: {:.1} KB -> {:.1} KB ({saved:.1}% smaller)",
+            sizes.source as f64 / 1024.0,
+            sizes.output as f64 / 1024.0,
+        );
+    }
     println!(
         "\n✅ Landscape built successfully!
 
@@ -470,17 +708,49 @@ You can see it in action by running the following command:
     );
 }
 
-/// Generate API data files and write them to API_PATH in the output directory.
+/// Calculate the digest of the embed and web application assets.
+fn get_web_assets_digest() -> Result<String> {
+    let mut assets = vec![];
+    for path in EmbedAssets::iter() {
+        if let Some(embedded_file) = EmbedAssets::get(&path) {
+            assets.push((
+                format!("embed/{path}"),
+                hex::encode(embedded_file.metadata.sha256_hash()),
+            ));
+        }
+    }
+    for path in EmbedItemAssets::iter() {
+        if let Some(embedded_file) = EmbedItemAssets::get(&path) {
+            assets.push((
+                format!("embed-item/{path}"),
+                hex::encode(embedded_file.metadata.sha256_hash()),
+            ));
+        }
+    }
+    for path in WebappAssets::iter() {
+        if let Some(embedded_file) = WebappAssets::get(&path) {
+            assets.push((
+                format!("webapp/{path}"),
+                hex::encode(embedded_file.metadata.sha256_hash()),
+            ));
+        }
+    }
+    digest(&assets)
+}
+
+/// Generate API data files and write them to API_PATH in the output directory,
+/// returning the paths of the files written.
 #[instrument(skip_all, err)]
-fn generate_api(input: &ApiSources, output_dir: &Path) -> Result<()> {
+fn generate_api(input: &ApiSources, output_dir: &Path) -> Result<Vec<String>> {
     debug!("generating api");
 
     let api = Api::new(input);
-    let api_path = output_dir.join(API_PATH);
 
     // Write data files to output dir
+    let mut outputs = vec![];
     for (endpoint, data) in &api.endpoints {
-        let endpoint_full_path = api_path.join(endpoint.strip_prefix('/').unwrap_or(endpoint));
+        let endpoint_path = Path::new(API_PATH).join(endpoint.strip_prefix('/').unwrap_or(endpoint));
+        let endpoint_full_path = output_dir.join(&endpoint_path);
 
         // Create endpoint parent directory if needed
         let Some(parent_path) = endpoint_full_path.parent() else {
@@ -493,77 +763,104 @@ fn generate_api(input: &ApiSources, output_dir: &Path) -> Result<()> {
         // Write data file
         let mut file = File::create(endpoint_full_path)?;
         file.write_all(data.as_bytes())?;
+        outputs.push(endpoint_path.to_string_lossy().into_owned());
     }
 
-    Ok(())
+    Ok(outputs)
 }
 
-/// Generate datasets from the landscape data and settings, as well as from the
-/// data collected from external services (GitHub, Crunchbase, etc). Some of
-/// the datasets will be embedded in the index document, and the rest will be
-/// written to the DATASETS_PATH in the output directory.
+/// Write the datasets generated from the landscape data and settings, as well
+/// as from the data collected from external services (GitHub, Crunchbase,
+/// etc), to the DATASETS_PATH in the output directory. Some of the datasets
+/// will also be embedded in the index document. Returns the paths of the files
+/// written.
 #[instrument(skip_all, err)]
-fn generate_datasets(input: &NewDatasetsInput, output_dir: &Path) -> Result<Datasets> {
-    debug!("generating datasets");
-
-    let datasets = Datasets::new(input);
-    let datasets_path = output_dir.join(DATASETS_PATH);
+fn write_datasets(datasets: &Datasets, output_dir: &Path) -> Result<Vec<String>> {
+    debug!("writing datasets");
+
+    let mut outputs = vec![];
+    let mut write = |file_name: String, data: Vec<u8>| -> Result<()> {
+        let path = Path::new(DATASETS_PATH).join(file_name);
+        File::create(output_dir.join(&path))?.write_all(&data)?;
+        outputs.push(path.to_string_lossy().into_owned());
+        Ok(())
+    };
 
     // Base
-    let mut base_file = File::create(datasets_path.join("base.json"))?;
-    base_file.write_all(&serde_json::to_vec(&datasets.base)?)?;
+    write("base.json".to_string(), serde_json::to_vec(&datasets.base)?)?;
 
     // Embed
     for (key, view) in &datasets.embed.views {
-        let mut embed_file = File::create(datasets_path.join(format!("embed_{key}.json")))?;
-        embed_file.write_all(&serde_json::to_vec(&view)?)?;
+        write(format!("embed_{key}.json"), serde_json::to_vec(&view)?)?;
 
         let view_full_dataset = prepare_view_full_dataset(&datasets.full, view);
-        let mut embed_full_file = File::create(datasets_path.join(format!("embed_full_{key}.json")))?;
-        embed_full_file.write_all(&serde_json::to_vec(&view_full_dataset)?)?;
+        write(
+            format!("embed_full_{key}.json"),
+            serde_json::to_vec(&view_full_dataset)?,
+        )?;
     }
 
     // Full
-    let mut full_file = File::create(datasets_path.join("full.json"))?;
-    full_file.write_all(&serde_json::to_vec(&datasets.full)?)?;
+    write("full.json".to_string(), serde_json::to_vec(&datasets.full)?)?;
 
     // Stats
-    let mut stats_file = File::create(datasets_path.join("stats.json"))?;
-    stats_file.write_all(&serde_json::to_vec(&datasets.stats)?)?;
+    write("stats.json".to_string(), serde_json::to_vec(&datasets.stats)?)?;
+
+    Ok(outputs)
+}
+
+/// Generate the full-text search index of the landscape items and guide,
+/// returning its path.
+#[instrument(skip_all, err)]
+fn generate_search_index(
+    landscape_data: &LandscapeData,
+    guide: Option<&LandscapeGuide>,
+    output_dir: &Path,
+) -> Result<String> {
+    debug!("generating search index");
+
+    let search_index = SearchIndex::new(landscape_data, guide);
+    let path = Path::new(DATASETS_PATH).join("search.json");
+    File::create(output_dir.join(&path))?.write_all(&serde_json::to_vec(&search_index)?)?;
 
-    Ok(datasets)
+    Ok(path.to_string_lossy().into_owned())
 }
 
-/// Generate the items.csv file from the landscape data.
+/// Generate the items.csv file from the landscape data, returning its path.
 #[instrument(skip_all, err)]
-fn generate_items_csv_file(landscape_data: &LandscapeData, output_dir: &Path) -> Result<()> {
+fn generate_items_csv_file(landscape_data: &LandscapeData, output_dir: &Path) -> Result<String> {
     debug!("generating items csv file");
 
-    let docs_path = output_dir.join(DOCS_PATH);
-    let w = csv::Writer::from_path(docs_path.join("items.csv"))?;
+    let path = Path::new(DOCS_PATH).join("items.csv");
+    let w = csv::Writer::from_path(output_dir.join(&path))?;
     generate_items_csv(w, landscape_data)?;
 
-    Ok(())
+    Ok(path.to_string_lossy().into_owned())
 }
 
-/// Generate the projects.md and projects.csv files from the landscape data.
+/// Generate the projects.md and projects.csv files from the landscape data,
+/// returning their paths.
 #[instrument(skip_all, err)]
-fn generate_projects_files(landscape_data: &LandscapeData, output_dir: &Path) -> Result<()> {
+fn generate_projects_files(landscape_data: &LandscapeData, output_dir: &Path) -> Result<Vec<String>> {
     debug!("generating projects files");
 
     let projects = collect_projects(landscape_data);
 
     // projects.md
     let projects_md = ProjectsMd { projects: &projects }.render()?;
-    let docs_path = output_dir.join(DOCS_PATH);
-    let mut file = File::create(docs_path.join("projects.md"))?;
+    let md_path = Path::new(DOCS_PATH).join("projects.md");
+    let mut file = File::create(output_dir.join(&md_path))?;
     file.write_all(projects_md.as_bytes())?;
 
     // projects.csv
-    let w = csv::Writer::from_path(docs_path.join("projects.csv"))?;
+    let csv_path = Path::new(DOCS_PATH).join("projects.csv");
+    let w = csv::Writer::from_path(output_dir.join(&csv_path))?;
     generate_projects_csv(w, &projects)?;
 
-    Ok(())
+    Ok(vec![
+        md_path.to_string_lossy().into_owned(),
+        csv_path.to_string_lossy().into_owned(),
+    ])
 }
 
 /// Generate QR code and copy it to output directory.
@@ -589,7 +886,12 @@ fn generate_qr_code(url: &String, output_dir: &Path) -> Result<String> {
 
 /// Prepare games data and copy it to the output directory.
 #[instrument(skip_all, err)]
-async fn prepare_games_data(games_source: &GamesSource, output_dir: &Path) -> Result<Option<LandscapeGames>> {
+async fn prepare_games_data(
+    games_source: &GamesSource,
+    prev_manifest: &BuildManifest,
+    manifest: &mut BuildManifest,
+    output_dir: &Path,
+) -> Result<Option<LandscapeGames>> {
     debug!("preparing games data");
 
     let Some(games) = LandscapeGames::new(games_source).await? else {
@@ -598,8 +900,16 @@ async fn prepare_games_data(games_source: &GamesSource, output_dir: &Path) -> Re
 
     // Quiz game data
     if let Some(quiz) = &games.quiz {
-        let path = output_dir.join(DATASETS_PATH).join("quiz.json");
-        File::create(path)?.write_all(&serde_json::to_vec(&quiz.questions)?)?;
+        let games_digest = digest(&quiz.questions)?;
+        let outputs = if let Some(outputs) = prev_manifest.step_outputs(STEP_GAMES, &games_digest, output_dir)
+        {
+            outputs
+        } else {
+            let path = Path::new(DATASETS_PATH).join("quiz.json");
+            File::create(output_dir.join(&path))?.write_all(&serde_json::to_vec(&quiz.questions)?)?;
+            vec![path.to_string_lossy().into_owned()]
+        };
+        manifest.track_step(STEP_GAMES, games_digest, outputs);
     }
 
     Ok(Some(games))
@@ -607,28 +917,45 @@ async fn prepare_games_data(games_source: &GamesSource, output_dir: &Path) -> Re
 
 /// Prepare guide and copy it to the output directory.
 #[instrument(skip_all, err)]
-async fn prepare_guide(guide_source: &GuideSource, output_dir: &Path) -> Result<Option<LandscapeGuide>> {
+async fn prepare_guide(
+    guide_source: &GuideSource,
+    prev_manifest: &BuildManifest,
+    manifest: &mut BuildManifest,
+    output_dir: &Path,
+) -> Result<Option<LandscapeGuide>> {
     debug!("preparing guide");
 
     let Some(guide) = LandscapeGuide::new(guide_source).await? else {
         return Ok(None);
     };
-    let path = output_dir.join(DATASETS_PATH).join("guide.json");
-    File::create(path)?.write_all(&serde_json::to_vec(&guide)?)?;
+    let guide_digest = digest(&guide)?;
+    let outputs = if let Some(outputs) = prev_manifest.step_outputs(STEP_GUIDE, &guide_digest, output_dir) {
+        outputs
+    } else {
+        let path = Path::new(DATASETS_PATH).join("guide.json");
+        File::create(output_dir.join(&path))?.write_all(&serde_json::to_vec(&guide)?)?;
+        vec![path.to_string_lossy().into_owned()]
+    };
+    manifest.track_step(STEP_GUIDE, guide_digest, outputs);
 
     Ok(Some(guide))
 }
 
 /// Prepare items logos and copy them to the output directory, updating the
-/// logo reference on each landscape item.
+/// logo reference on each landscape item. Raster derivatives of the logos are
+/// generated as well when configured in the settings. Logos are also audited,
+/// and the problems found are reported as warnings (or errors in strict mode).
+#[allow(clippy::too_many_lines)]
 #[instrument(skip_all, err)]
 async fn prepare_items_logos(
-    logos_source: &LogosSource,
-    logos_viewbox: &LogosViewbox,
+    args: &BuildArgs,
+    settings: &LandscapeSettings,
     landscape_data: &mut LandscapeData,
-    output_dir: &Path,
-) -> Result<()> {
+    prev_manifest: &BuildManifest,
+    manifest: &mut BuildManifest,
+) -> Result<LogosSizes> {
     debug!("preparing logos");
+    let (logos_source, output_dir) = (&args.logos_source, args.output_dir.as_path());
 
     // Get logos from the source and copy them to the output directory
     let mut concurrency = num_cpus::get();
@@ -636,24 +963,74 @@ async fn prepare_items_logos(
         concurrency = PREPARE_LOGOS_MAX_CONCURRENCY;
     }
     let http_client = reqwest::Client::new();
-    let logos_source = Arc::new(logos_source.clone());
-    let logos_viewbox = Arc::new(logos_viewbox.clone());
-    let logos: HashMap<String, Option<String>> = stream::iter(landscape_data.items.iter())
+    let logos_viewbox = Arc::new(settings.logos_viewbox.clone());
+    let raster_sizes = Arc::new(settings.logos_raster.as_ref().map(|r| r.sizes.clone()).unwrap_or_default());
+    let logos_problems = AtomicUsize::new(0);
+    let logos: HashMap<String, Option<LogoEntry>> = stream::iter(landscape_data.items.iter())
         .map(|item| async {
-            // Prepare logo
-            let http_client = http_client.clone();
-            let logos_source = logos_source.clone();
+            // Get logo from the source provided
+            let logo_data = match get_logo(http_client.clone(), logos_source, &item.logo).await {
+                Ok(logo_data) => logo_data,
+                Err(err) => {
+                    error!(?err, ?item.logo, "error preparing logo");
+                    return (item.id.clone(), None);
+                }
+            };
+
+            // Audit logo, reporting the problems found
+            let file_name = item.logo.clone();
+            let audit = tokio::task::spawn_blocking(move || {
+                let problems = audit_logo(&logo_data, &file_name);
+                (logo_data, problems)
+            });
+            let logo_data = match audit.await {
+                Ok((logo_data, problems)) => {
+                    for problem in &problems {
+                        warn!(?item.name, ?item.logo, "{problem}");
+                    }
+                    logos_problems.fetch_add(problems.len(), Ordering::Relaxed);
+                    logo_data
+                }
+                Err(err) => {
+                    error!(?err, ?item.logo, "error executing audit_logo task");
+                    return (item.id.clone(), None);
+                }
+            };
+
+            // Reuse the logo prepared in the previous build if the source
+            // hasn't changed
+            let source_digest = get_logo_source_digest(
+                &logo_data,
+                &logos_viewbox,
+                args.optimize_logos,
+                &raster_sizes,
+                &item.logo,
+            );
+            if let Some(entry) = prev_manifest.logo(&item.logo, &source_digest, output_dir) {
+                return (item.id.clone(), Some(entry.clone()));
+            }
+
+            // Prepare logo and its raster derivatives (if requested)
+            let source_size = logo_data.len();
+            let optimize = args.optimize_logos;
             let logos_viewbox = logos_viewbox.clone();
+            let raster_sizes = raster_sizes.clone();
             let file_name = item.logo.clone();
-            let logo = match tokio::spawn(async move {
-                prepare_logo(http_client, &logos_source, &logos_viewbox, &file_name).await
+            let (logo, derivatives) = match tokio::task::spawn_blocking(move || {
+                let logo = prepare_logo(logo_data, &logos_viewbox, optimize, &file_name);
+                let derivatives = if raster_sizes.is_empty() {
+                    Ok(vec![])
+                } else {
+                    prepare_logo_derivatives(&logo, &raster_sizes)
+                };
+                (logo, derivatives)
             })
             .await
             {
-                Ok(Ok(logo)) => logo,
-                Ok(Err(err)) => {
-                    error!(?err, ?item.logo, "error preparing logo");
-                    return (item.id.clone(), None);
+                Ok((logo, Ok(derivatives))) => (logo, derivatives),
+                Ok((logo, Err(err))) => {
+                    warn!(?err, ?item.logo, "error preparing logo derivatives");
+                    (logo, vec![])
                 }
                 Err(err) => {
                     error!(?err, ?item.logo, "error executing prepare_logo task");
@@ -674,117 +1051,82 @@ async fn prepare_items_logos(
                 error!(?err, ?file_name, "error writing logo to file in output dir");
             }
 
-            (item.id.clone(), Some(format!("{LOGOS_PATH}/{file_name}")))
+            // Copy logo derivatives to output dir next to the logo
+            let mut derivatives_entries = vec![];
+            for derivative in derivatives {
+                let file_name = format!("{}-{}.{}", logo.digest, derivative.size, derivative.extension);
+                let path = format!("{LOGOS_PATH}/{file_name}");
+                if let Err(err) = fs::write(output_dir.join(&path), &derivative.data) {
+                    error!(
+                        ?err,
+                        ?file_name,
+                        "error writing logo derivative to file in output dir"
+                    );
+                    continue;
+                }
+                derivatives_entries.push(ItemLogoDerivative {
+                    format: derivative.extension,
+                    path,
+                    size: derivative.size,
+                });
+            }
+
+            let entry = LogoEntry {
+                path: format!("{LOGOS_PATH}/{file_name}"),
+                source_digest,
+                source_size,
+                size: logo.data.len(),
+                derivatives: derivatives_entries,
+            };
+            (item.id.clone(), Some(entry))
         })
         .buffer_unordered(concurrency)
         .collect()
         .await;
 
-    // Update logo field in landscape items to logo digest path
+    // Update logo fields in landscape items to logo digest paths
     for item in &mut landscape_data.items {
         item.logo = if let Some(Some(logo)) = logos.get(&item.id) {
-            logo.clone()
+            manifest.track_logo(&item.logo, logo.clone());
+            if !logo.derivatives.is_empty() {
+                item.logo_derivatives = Some(logo.derivatives.clone());
+            }
+            logo.path.clone()
         } else {
             String::new()
         }
     }
 
-    debug!("done!");
-    Ok(())
-}
-
-/// Prepare landscape screenshot (in PNG and PDF formats).
-#[allow(clippy::cast_precision_loss, clippy::items_after_statements)]
-#[instrument(skip(output_dir), err)]
-async fn prepare_screenshot(width: u32, output_dir: &Path) -> Result<()> {
-    debug!("preparing screenshot");
-
-    // Check if Chrome/Chromium is available
-    if browser::default_executable().is_err() {
-        warn!("chrome/chromium not found, no screenshot will be taken");
-        return Ok(());
+    // Fail if any problems were found in the logos in strict mode
+    let logos_problems = logos_problems.into_inner();
+    if args.strict_logos && logos_problems > 0 {
+        bail!("{logos_problems} problems found when auditing logos (strict mode enabled)");
     }
 
-    // Launch server to serve landscape just built
-    let Some(port) = find_available_port() else {
-        bail!("could not find an available port to listen on");
-    };
-    let svr_addr = format!("127.0.0.1:{port}");
-    let svr_addr_copy = svr_addr.clone();
-    let landscape_dir = Some(PathBuf::from(&output_dir));
-    let server = tokio::spawn(async {
-        let args = serve::ServeArgs {
-            addr: svr_addr_copy,
-            graceful_shutdown: false,
-            landscape_dir,
-            silent: true,
-        };
-        serve(&args).await
-    });
-
-    // Setup headless browser and navigate to screenshot url
-    const TIMEOUT: Duration = Duration::from_mins(10);
-    let options = LaunchOptions {
-        args: vec![
-            OsStr::new("--force-device-scale-factor=2"),
-            OsStr::new("--headless=new"),
-            OsStr::new("--hide-scrollbars"),
-        ],
-        idle_browser_timeout: TIMEOUT,
-        sandbox: false,
-        window_size: Some((width, 500)),
-        ..Default::default()
-    };
-    let browser = Browser::new(options)?;
-    let tab = browser.new_tab()?;
-    tab.set_default_timeout(TIMEOUT);
-    let screenshot_url = format!("http://{svr_addr}/screenshot");
-    tab.navigate_to(&screenshot_url)?.wait_until_navigated()?;
-    trace!("navigated to screenshot url");
-
-    // Take screenshot in PNG format and save it to a file
-    let png_b64_data = tab
-        .call_method(Page::CaptureScreenshot {
-            format: Some(CaptureScreenshotFormatOption::Png),
-            quality: None,
-            clip: None,
-            from_surface: None,
-            capture_beyond_viewport: Some(true),
-            optimize_for_speed: None,
-        })
-        .context("error generating screenshot in png format")?
-        .data;
-    let png_data = b64.decode(png_b64_data)?;
-    let png_path = output_dir.join(DOCS_PATH).join("landscape.png");
-    File::create(&png_path)?.write_all(&png_data)?;
-    trace!("screenshot in png format ready");
-
-    // Take screenshot in PDF format and save it to a file
-    // We use the dimensions of the screenshot in PNG format to calculate the
-    // dimensions of the PDF paper, converting from pixels to inches.
-    let png_size = imagesize::size(&png_path).context("error getting screenshot in png format dimensions")?;
-    let pdf_data = tab
-        .print_to_pdf(Some(PrintToPdfOptions {
-            margin_bottom: Some(0.0),
-            margin_left: Some(0.0),
-            margin_right: Some(0.0),
-            margin_top: Some(0.0),
-            page_ranges: Some("1".to_string()),
-            paper_height: Some((png_size.height + 10) as f64 / 2.0 * 0.010_416_666_7),
-            paper_width: Some(png_size.width as f64 / 2.0 * 0.010_416_666_7),
-            print_background: Some(true),
-            ..Default::default()
-        }))
-        .context("error generating screenshot in pdf format")?;
-    let pdf_path = output_dir.join(DOCS_PATH).join("landscape.pdf");
-    File::create(pdf_path)?.write_all(&pdf_data)?;
-    trace!("screenshot in pdf format ready");
-
-    // Stop server
-    server.abort();
+    // Calculate the total size of the logos (identical logos share the same
+    // output file, so they are only counted once)
+    let mut sizes = LogosSizes::default();
+    let mut paths_seen = HashSet::new();
+    for logo in logos.values().flatten() {
+        if paths_seen.insert(&logo.path) {
+            sizes.source += logo.source_size;
+            sizes.output += logo.size;
+        }
+    }
+    if args.optimize_logos {
+        info!(source = sizes.source, output = sizes.output, "logos optimized");
+    }
 
     debug!("done!");
-    Ok(())
+    Ok(sizes)
+}
+
+/// Total size of the logos prepared, before and after being processed (in
+/// bytes).
+#[derive(Debug, Clone, Copy, Default)]
+struct LogosSizes {
+    source: usize,
+    output: usize,
 }
 
 /// Fetch some settings images, copy them to the output directory and update
@@ -904,14 +1246,15 @@ struct IndexHtml<'a> {
     osano: Option<&'a Osano>,
 }
 
-/// Render index html file and write it to the output directory.
+/// Render index html file and write it to the output directory, returning its
+/// path.
 #[instrument(skip_all, err)]
 fn render_index_html(
     analytics: Option<&Analytics>,
     datasets: &Datasets,
     osano: Option<&Osano>,
     output_dir: &Path,
-) -> Result<()> {
+) -> Result<String> {
     debug!("rendering index.html file");
 
     let html = IndexHtml {
@@ -922,7 +1265,7 @@ fn render_index_html(
     .render()?;
     File::create(output_dir.join("index.html"))?.write_all(html.as_bytes())?;
 
-    Ok(())
+    Ok("index.html".to_string())
 }
 
 /// Template for the embed item html document.
@@ -932,16 +1275,17 @@ struct EmbedItemHtml<'a> {
     colors: Option<&'a Colors>,
 }
 
-/// Render embed item html file and write it to the output directory.
+/// Render embed item html file and write it to the output directory,
+/// returning its path.
 #[instrument(skip_all, err)]
-fn render_embed_item_html(colors: Option<&Colors>, output_dir: &Path) -> Result<()> {
+fn render_embed_item_html(colors: Option<&Colors>, output_dir: &Path) -> Result<String> {
     debug!("rendering embed-item.html file");
 
-    let path = output_dir.join(EMBED_PATH).join("embed-item.html");
+    let path = Path::new(EMBED_PATH).join("embed-item.html");
     let html = EmbedItemHtml { colors }.render()?;
-    File::create(path)?.write_all(html.as_bytes())?;
+    File::create(output_dir.join(&path))?.write_all(html.as_bytes())?;
 
-    Ok(())
+    Ok(path.to_string_lossy().into_owned())
 }
 
 /// Template for the kiosk html document.
@@ -954,9 +1298,15 @@ struct KioskHtml<'a> {
     colors: Option<&'a Colors>,
 }
 
-/// Render kiosk html file and write it to the output directory.
+/// Render kiosk html file and write it to the output directory, returning its
+/// path.
 #[instrument(skip_all, err)]
-fn render_kiosk_html(foundation: &str, url: &str, colors: Option<&Colors>, output_dir: &Path) -> Result<()> {
+fn render_kiosk_html(
+    foundation: &str,
+    url: &str,
+    colors: Option<&Colors>,
+    output_dir: &Path,
+) -> Result<String> {
     debug!("rendering kiosk.html file");
 
     let html = KioskHtml {
@@ -967,7 +1317,7 @@ fn render_kiosk_html(foundation: &str, url: &str, colors: Option<&Colors>, outpu
     .render()?;
     File::create(output_dir.join("kiosk.html"))?.write_all(html.as_bytes())?;
 
-    Ok(())
+    Ok("kiosk.html".to_string())
 }
 
 /// Setup output directory, creating it as well as any of the other required
@@ -999,11 +1349,6 @@ fn setup_output_dir(output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Find an available port to listen on.
-fn find_available_port() -> Option<u16> {
-    (9000..10000).find(|port| TcpListener::bind(("127.0.0.1", *port)).is_ok())
-}
-
 #[allow(
     clippy::inline_always,
     clippy::ref_option_ref,
@@ -0,0 +1,289 @@
+//! This module provides the functionality used to generate the OpenAPI
+//! description of the landscape API, as well as the JSON Schemas of the main
+//! data types exposed from it.
+//!
+//! These documents describe the contract of the API, so that consumers can
+//! generate clients from them or detect breaking changes between releases.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use schemars::{
+    JsonSchema, Schema,
+    generate::{Contract, SchemaGenerator, SchemaSettings},
+};
+use serde_json::{Value, json};
+
+use super::{
+    LandscapeSettings,
+    api::{Count, Endpoint, Item, Repository, absolute_url},
+    changes::Changelog,
+    data::ItemsAliases,
+};
+
+/// Endpoint of the OpenAPI description of the API.
+pub(crate) const OPENAPI_ENDPOINT: &str = "openapi.json";
+
+/// Path where the JSON Schemas will be written to in the API directory.
+const SCHEMAS_PATH: &str = "schemas";
+
+/// Version of the OpenAPI specification used.
+const OPENAPI_VERSION: &str = "3.1.0";
+
+/// Kind of data returned by an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseKind {
+    Aliases,
+    Changelog,
+    Count,
+    Index,
+    Item,
+    Items,
+}
+
+impl ResponseKind {
+    /// Return the description of the data returned.
+    fn description(self) -> &'static str {
+        match self {
+            Self::Aliases => "Aliases of the items ids (previous id -> current id)",
+            Self::Changelog => "Landscape changelog",
+            Self::Count => "Number of items",
+            Self::Index => "List of all the endpoints available",
+            Self::Item => "Project details",
+            Self::Items => "List of items",
+        }
+    }
+
+    /// Return the schema of the data returned, registering the definitions
+    /// it depends on in the generator provided.
+    fn schema(self, generator: &mut SchemaGenerator) -> Schema {
+        match self {
+            Self::Aliases => generator.subschema_for::<ItemsAliases>(),
+            Self::Changelog => generator.subschema_for::<Changelog>(),
+            Self::Count => generator.subschema_for::<Count>(),
+            Self::Index => generator.subschema_for::<Vec<Endpoint>>(),
+            Self::Item => generator.subschema_for::<Item>(),
+            Self::Items => generator.subschema_for::<Vec<Item>>(),
+        }
+    }
+}
+
+/// Endpoint path template, along with the values of its parameters.
+#[derive(Debug, Clone, PartialEq)]
+struct PathTemplate {
+    path: String,
+    params: Vec<(&'static str, String)>,
+    kind: ResponseKind,
+}
+
+impl PathTemplate {
+    /// Create a new path template from the endpoint provided. Returns `None`
+    /// for endpoints that are not described in the OpenAPI document.
+    fn from_endpoint(endpoint: &str) -> Option<Self> {
+        let file_kind = |file: &str| match file {
+            "all.json" | "end-users.json" => Some(ResponseKind::Items),
+            "count.json" => Some(ResponseKind::Count),
+            _ => None,
+        };
+        let fixed = |kind| {
+            Some(Self {
+                path: endpoint.to_string(),
+                params: vec![],
+                kind,
+            })
+        };
+
+        let segments: Vec<&str> = endpoint.split('/').collect();
+        match segments.as_slice() {
+            ["aliases.json"] => fixed(ResponseKind::Aliases),
+            ["changes.json"] => fixed(ResponseKind::Changelog),
+            ["index.json"] => fixed(ResponseKind::Index),
+            ["members" | "projects", file] if file_kind(file).is_some() => fixed(file_kind(file)?),
+            ["projects", file] => Some(Self {
+                path: "projects/{project_id}.json".to_string(),
+                params: vec![("project_id", file.strip_suffix(".json")?.to_string())],
+                kind: ResponseKind::Item,
+            }),
+            ["categories", category, subcategory, file] => Some(Self {
+                path: format!("categories/{{category}}/{{subcategory}}/{file}"),
+                params: vec![
+                    ("category", (*category).to_string()),
+                    ("subcategory", (*subcategory).to_string()),
+                ],
+                kind: file_kind(file)?,
+            }),
+            [group, value, file] => {
+                let param = match *group {
+                    "categories" => "category",
+                    "maturity" => "maturity",
+                    "members" => "member_subcategory",
+                    "organizations" => "organization",
+                    "tags" => "tag",
+                    _ => return None,
+                };
+                Some(Self {
+                    path: format!("{group}/{{{param}}}/{file}"),
+                    params: vec![(param, (*value).to_string())],
+                    kind: file_kind(file)?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Generate the OpenAPI description of the endpoints provided.
+pub(crate) fn generate_openapi<'a>(
+    endpoints: impl IntoIterator<Item = &'a Endpoint>,
+    settings: &LandscapeSettings,
+) -> Value {
+    let mut generator = SchemaGenerator::new(SchemaSettings::draft2020_12().with(|s| {
+        s.contract = Contract::Serialize;
+        s.definitions_path = "/components/schemas".into();
+        s.meta_schema = None;
+    }));
+
+    // Group endpoints by their path template, collecting the values available
+    // for each of the parameters
+    let mut templates: BTreeMap<String, (ResponseKind, BTreeMap<&'static str, BTreeSet<String>>)> =
+        BTreeMap::new();
+    for endpoint in endpoints {
+        let Some(template) = PathTemplate::from_endpoint(endpoint) else {
+            continue;
+        };
+        let (_, params) = templates.entry(template.path).or_insert_with(|| (template.kind, BTreeMap::new()));
+        for (name, value) in template.params {
+            params.entry(name).or_default().insert(value);
+        }
+    }
+
+    // Prepare paths
+    let mut paths = serde_json::Map::new();
+    for (path, (kind, params)) in templates {
+        let operation_id = path
+            .trim_end_matches(".json")
+            .split(['/', '-'])
+            .map(|s| s.trim_matches(['{', '}']))
+            .collect::<Vec<_>>()
+            .join("_");
+        let parameters: Vec<Value> = params
+            .into_iter()
+            .map(|(name, values)| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": {"type": "string", "enum": values},
+                })
+            })
+            .collect();
+        let mut operation = json!({
+            "operationId": operation_id,
+            "summary": kind.description(),
+            "responses": {
+                "200": {
+                    "description": kind.description(),
+                    "content": {
+                        "application/json": {"schema": kind.schema(&mut generator)},
+                    },
+                },
+            },
+        });
+        if !parameters.is_empty() {
+            operation["parameters"] = Value::Array(parameters);
+        }
+        paths.insert(format!("/{path}"), json!({ "get": operation }));
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": format!("{} landscape API", settings.foundation),
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "servers": [{"url": absolute_url(&settings.url, "api")}],
+        "paths": paths,
+        "components": {"schemas": generator.take_definitions(true)},
+    })
+}
+
+/// Generate the JSON Schemas of the main data types exposed from the API,
+/// returning the endpoint where each of them should be served from.
+pub(crate) fn generate_json_schemas() -> Vec<(Endpoint, Value)> {
+    vec![
+        (format!("{SCHEMAS_PATH}/item.json"), root_schema_for::<Item>()),
+        (
+            format!("{SCHEMAS_PATH}/repository.json"),
+            root_schema_for::<Repository>(),
+        ),
+    ]
+}
+
+/// Generate the root JSON Schema of the type provided.
+fn root_schema_for<T: JsonSchema>() -> Value {
+    let settings = SchemaSettings::draft2020_12().with(|s| s.contract = Contract::Serialize);
+    SchemaGenerator::new(settings).into_root_schema_for::<T>().to_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_template_from_endpoint() {
+        let template = |endpoint: &str| PathTemplate::from_endpoint(endpoint);
+
+        assert_eq!(
+            template("categories/category/subcategory/all.json"),
+            Some(PathTemplate {
+                path: "categories/{category}/{subcategory}/all.json".to_string(),
+                params: vec![
+                    ("category", "category".to_string()),
+                    ("subcategory", "subcategory".to_string())
+                ],
+                kind: ResponseKind::Items,
+            })
+        );
+        assert_eq!(
+            template("projects/item.json"),
+            Some(PathTemplate {
+                path: "projects/{project_id}.json".to_string(),
+                params: vec![("project_id", "item".to_string())],
+                kind: ResponseKind::Item,
+            })
+        );
+        assert_eq!(
+            template("members/count.json").map(|t| (t.path, t.kind)),
+            Some(("members/count.json".to_string(), ResponseKind::Count))
+        );
+        assert_eq!(template(OPENAPI_ENDPOINT), None);
+        assert_eq!(template("schemas/item.json"), None);
+    }
+
+    #[test]
+    fn generate_openapi_succeeds() {
+        let endpoints = [
+            "tags/tag1/count.json",
+            "tags/tag2/count.json",
+            "projects/all.json",
+        ]
+        .map(String::from);
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            url: "https://landscape.example.com".to_string(),
+            ..Default::default()
+        };
+
+        let openapi = generate_openapi(&endpoints, &settings);
+        assert_eq!(openapi["servers"][0]["url"], "https://landscape.example.com/api");
+        assert_eq!(
+            openapi["paths"]["/tags/{tag}/count.json"]["get"]["parameters"][0]["schema"]["enum"],
+            json!(["tag1", "tag2"])
+        );
+        assert_eq!(
+            openapi["paths"]["/projects/all.json"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"]["items"]["$ref"],
+            "#/components/schemas/Item"
+        );
+        assert!(openapi["components"]["schemas"]["Repository"].is_object());
+    }
+}
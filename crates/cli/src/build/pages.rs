@@ -0,0 +1,284 @@
+//! This module provides the functionality used to render a static html page
+//! per item, as well as the sitemap and robots.txt files.
+//!
+//! The web application is a single-page application, so search engines can
+//! barely index anything about the items in the landscape. These crawlable
+//! pages contain the most relevant information about each of them, and link
+//! back to the item in the web application.
+
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+use anyhow::Result;
+use askama::Template;
+use landscape2_core::{
+    data::{Item, LandscapeData},
+    settings::{Colors, LandscapeSettings},
+};
+use tracing::{debug, instrument};
+
+use super::{
+    api::absolute_url,
+    cards::{CARD_HEIGHT, CARD_WIDTH, card_path},
+};
+
+/// Path where the items pages will be written to in the output directory.
+pub(crate) const ITEMS_PAGES_PATH: &str = "items";
+
+/// Format used to display dates in the items pages.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Template for the item html page.
+#[derive(Debug, Clone, Template)]
+#[template(path = "item-page.html")]
+struct ItemPageHtml<'a> {
+    category: &'a str,
+    foundation: &'a str,
+    landscape_item_url: String,
+    links: Vec<(&'a str, &'a str)>,
+    logo_url: String,
+    name: &'a str,
+    stats: Vec<(&'static str, String)>,
+    subcategory: &'a str,
+    url: String,
+
+    colors: Option<&'a Colors>,
+    description: Option<&'a str>,
+    favicon: Option<String>,
+    image: Option<String>,
+    image_size: Option<(u32, u32)>,
+    maturity: Option<&'a str>,
+}
+
+impl<'a> ItemPageHtml<'a> {
+    /// Create a new item page from the item provided. The item social card
+    /// is used as the page image when available, falling back to the
+    /// landscape open graph image.
+    fn new(item: &'a Item, settings: &'a LandscapeSettings, site_url: &str, card: Option<&str>) -> Self {
+        let images = settings.images.as_ref();
+        let (image, image_size) = if let Some(card) = card {
+            (
+                Some(absolute_url(site_url, card)),
+                Some((CARD_WIDTH, CARD_HEIGHT)),
+            )
+        } else {
+            let open_graph = images.and_then(|i| i.open_graph.as_ref()).map(|image| {
+                if image.starts_with("http://") || image.starts_with("https://") {
+                    image.clone()
+                } else {
+                    absolute_url(site_url, image)
+                }
+            });
+            (open_graph, None)
+        };
+        Self {
+            category: &item.category,
+            foundation: &settings.foundation,
+            landscape_item_url: format!("{site_url}/?item={}", item.id),
+            links: item_links(item),
+            logo_url: absolute_url(site_url, &item.logo),
+            name: &item.name,
+            stats: item_stats(item),
+            subcategory: &item.subcategory,
+            url: item_page_url(site_url, &item.id),
+            colors: settings.colors.as_ref(),
+            description: item.description().map(String::as_str),
+            favicon: images.and_then(|i| i.favicon.as_ref()).map(|favicon| absolute_url(site_url, favicon)),
+            image,
+            image_size,
+            maturity: item.maturity.as_deref(),
+        }
+    }
+}
+
+/// Template for the sitemap.
+#[derive(Debug, Clone, Template)]
+#[template(path = "sitemap.xml")]
+struct SitemapXml<'a> {
+    urls: &'a [String],
+}
+
+/// Render the items pages, the sitemap and the robots.txt file, writing them
+/// to the output directory and returning their paths. The items cards paths
+/// provided are referenced from the corresponding pages.
+#[instrument(skip_all, err)]
+pub(crate) fn render_items_pages(
+    landscape_data: &LandscapeData,
+    settings: &LandscapeSettings,
+    cards: &[String],
+    output_dir: &Path,
+) -> Result<Vec<String>> {
+    debug!("rendering items pages");
+
+    let site_url = site_url(settings);
+    let mut outputs = vec![];
+    let mut urls = vec![format!("{site_url}/")];
+    let cards: HashSet<&str> = cards.iter().map(String::as_str).collect();
+
+    // Items pages
+    for item in &landscape_data.items {
+        let page_dir = Path::new(ITEMS_PAGES_PATH).join(&item.id);
+        fs::create_dir_all(output_dir.join(&page_dir))?;
+        let path = page_dir.join("index.html");
+        let card = card_path(&item.id);
+        let card = cards.contains(card.as_str()).then_some(card.as_str());
+        let html = ItemPageHtml::new(item, settings, &site_url, card).render()?;
+        File::create(output_dir.join(&path))?.write_all(html.as_bytes())?;
+        outputs.push(path.to_string_lossy().into_owned());
+        urls.push(item_page_url(&site_url, &item.id));
+    }
+
+    // Sitemap
+    let sitemap = SitemapXml { urls: &urls }.render()?;
+    File::create(output_dir.join("sitemap.xml"))?.write_all(sitemap.as_bytes())?;
+    outputs.push("sitemap.xml".to_string());
+
+    // Robots
+    let robots = format!("User-agent: *\nAllow: /\n\nSitemap: {site_url}/sitemap.xml\n");
+    File::create(output_dir.join("robots.txt"))?.write_all(robots.as_bytes())?;
+    outputs.push("robots.txt".to_string());
+
+    Ok(outputs)
+}
+
+/// Return the url of the site root, including the base path (if any).
+fn site_url(settings: &LandscapeSettings) -> String {
+    let url = settings.url.trim_end_matches('/');
+    match settings.base_path.as_deref() {
+        Some(base_path) if !url.ends_with(base_path) => format!("{url}{base_path}"),
+        _ => url.to_string(),
+    }
+}
+
+/// Return the url of the page of the item provided.
+fn item_page_url(site_url: &str, item_id: &str) -> String {
+    format!("{site_url}/{ITEMS_PAGES_PATH}/{item_id}/")
+}
+
+/// Return the links of the item provided (name and url).
+fn item_links(item: &Item) -> Vec<(&str, &str)> {
+    let mut links = vec![("Homepage", item.homepage_url.as_str())];
+    for repo in item.repositories.iter().flatten() {
+        links.push(("Repository", &repo.url));
+    }
+    let optional_links = [
+        ("Documentation", &item.documentation_url),
+        ("Blog", &item.blog_url),
+        ("Artwork", &item.artwork_url),
+        ("Package manager", &item.package_manager_url),
+        ("DevStats", &item.devstats_url),
+        ("Crunchbase", &item.crunchbase_url),
+        ("Twitter", &item.twitter_url),
+        ("Bluesky", &item.bluesky_url),
+        ("LinkedIn", &item.linkedin_url),
+        ("YouTube", &item.youtube_url),
+        ("Slack", &item.slack_url),
+        ("Discord", &item.discord_url),
+    ];
+    for (name, url) in optional_links {
+        if let Some(url) = url {
+            links.push((name, url));
+        }
+    }
+    for link in item.other_links.iter().flatten() {
+        links.push((&link.name, &link.url));
+    }
+    links
+}
+
+/// Return the key stats of the item provided (name and value).
+fn item_stats(item: &Item) -> Vec<(&'static str, String)> {
+    let mut stats = vec![];
+    if let Some(organization) = item.crunchbase_data.as_ref().and_then(|org| org.name.as_ref()) {
+        stats.push(("Organization", organization.clone()));
+    }
+    if let Some(accepted_at) = item.accepted_at {
+        stats.push(("Accepted", accepted_at.format(DATE_FORMAT).to_string()));
+    }
+    if let Some(repo) = item.primary_repository() {
+        let gh_data = repo.github_data.as_ref();
+        if let Some(license) = repo.license.as_ref().or(gh_data.and_then(|gh| gh.license.as_ref())) {
+            stats.push(("License", license.clone()));
+        }
+        if let Some(gh_data) = gh_data {
+            stats.push(("GitHub stars", gh_data.stars.to_string()));
+            stats.push(("Contributors", gh_data.contributors.count.to_string()));
+            if let Some(ts) = gh_data.first_commit.as_ref().and_then(|c| c.ts) {
+                stats.push(("First commit", ts.format(DATE_FORMAT).to_string()));
+            }
+            if let Some(ts) = gh_data.latest_commit.ts {
+                stats.push(("Latest commit", ts.format(DATE_FORMAT).to_string()));
+            }
+            if let Some(ts) = gh_data.latest_release.as_ref().and_then(|r| r.ts) {
+                stats.push(("Latest release", ts.format(DATE_FORMAT).to_string()));
+            }
+        }
+    }
+    if let Some(funding) = item.crunchbase_data.as_ref().and_then(|org| org.funding) {
+        stats.push(("Funding", format!("${funding}")));
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn site_url_includes_base_path() {
+        let settings = |url: &str, base_path: Option<&str>| LandscapeSettings {
+            url: url.to_string(),
+            base_path: base_path.map(ToString::to_string),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            site_url(&settings("https://l.example.com/", None)),
+            "https://l.example.com"
+        );
+        assert_eq!(
+            site_url(&settings("https://l.example.com", Some("/landscape"))),
+            "https://l.example.com/landscape"
+        );
+        assert_eq!(
+            site_url(&settings("https://l.example.com/landscape", Some("/landscape"))),
+            "https://l.example.com/landscape"
+        );
+    }
+
+    #[test]
+    fn item_page_html_render_succeeds() {
+        let item = Item {
+            id: "category--subcategory--item".to_string(),
+            category: "Category".to_string(),
+            subcategory: "Subcategory".to_string(),
+            name: "Item <1>".to_string(),
+            homepage_url: "https://item.example.com".to_string(),
+            logo: "logos/item.svg".to_string(),
+            description: Some("Item description".to_string()),
+            ..Default::default()
+        };
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            url: "https://landscape.example.com".to_string(),
+            ..Default::default()
+        };
+
+        let card = card_path(&item.id);
+        let html = ItemPageHtml::new(&item, &settings, &site_url(&settings), Some(&card)).render().unwrap();
+        assert!(html.contains("<title>Item &#60;1&#62; - Foundation Landscape</title>"));
+        assert!(html.contains(
+            r#"<link rel="canonical" href="https://landscape.example.com/items/category--subcategory--item/" />"#
+        ));
+        assert!(html.contains(r#"<meta property="og:description" content="Item description" />"#));
+        assert!(html.contains(r#"href="https://landscape.example.com/?item=category--subcategory--item""#));
+        assert!(html.contains(
+            r#"<meta property="og:image" content="https://landscape.example.com/images/cards/category--subcategory--item.png" />"#
+        ));
+        assert!(html.contains(r#"<meta property="og:image:width" content="1200" />"#));
+    }
+}
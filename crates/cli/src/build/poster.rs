@@ -0,0 +1,1085 @@
+//! This module provides the functionality used to render the landscape poster
+//! (in SVG, PNG and PDF formats).
+//!
+//! The poster is laid out natively the same way the grid view in the web
+//! application displays the landscape (groups, categories, subcategories and
+//! items), so no headless browser is required to generate it.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use askama::Template;
+use base64::{Engine as _, engine::general_purpose::STANDARD as b64};
+use flate2::{Compression, write::ZlibEncoder};
+use futures::stream::{self, StreamExt};
+use image::{DynamicImage, ImageFormat, RgbImage, RgbaImage, imageops};
+use landscape2_core::{
+    data::{Item, LandscapeData},
+    normalize_name,
+    settings::{FeaturedItemRule, GridItemsSize, LandscapeSettings, PaperSize, Screenshot, ScreenshotFormat},
+};
+use resvg::tiny_skia::{Pixmap, Transform};
+use tracing::{debug, instrument, warn};
+use usvg::{TreeParsing, TreeTextToPath, fontdb};
+
+use super::{
+    cards::{FONT_FAMILY, load_system_fonts, truncate},
+    logos::{pixmap_to_image, render_logo},
+};
+
+/// Scale factor used when rendering the poster in PNG format.
+const PNG_SCALE: u32 = 2;
+
+/// Maximum size of a PDF page (in points) supported by most PDF readers.
+const PDF_MAX_PAGE_SIZE: f64 = 14_400.0;
+
+/// Margin used when fitting the poster into a paper size (in points).
+const PDF_PAPER_MARGIN: f64 = 18.0;
+
+/// Maximum number of logos to render concurrently.
+const RENDER_LOGOS_MAX_CONCURRENCY: usize = 20;
+
+/// Default colors used for the categories (they alternate).
+const DEFAULT_CATEGORIES_COLORS: [&str; 2] = ["rgba(19, 17, 24, 0.7)", "rgba(80, 142, 191, 0.7)"];
+
+/// Padding around the poster content (in px).
+const PAGE_PADDING: u32 = 24;
+
+/// Space above and below each group (in px).
+const GROUP_MARGIN: u32 = 24;
+
+/// Height reserved for the group title (in px).
+const GROUP_TITLE_HEIGHT: u32 = 54;
+
+/// Width of the category title (in px).
+const CATEGORY_TITLE_WIDTH: u32 = 48;
+
+/// Maximum number of characters of the category title displayed.
+const CATEGORY_TITLE_MAX_CHARS: usize = 33;
+
+/// Maximum length of each line of the category title (in px).
+const CATEGORY_TITLE_MAX_LENGTH: u32 = 130;
+
+/// Height of the subcategory title (in px).
+const SUBCATEGORY_TITLE_HEIGHT: u32 = 28;
+
+/// Font size used in the categories and subcategories titles (in px).
+const TITLE_FONT_SIZE: u32 = 11;
+
+/// Font size used in the featured items labels (in px).
+const LABEL_FONT_SIZE: u32 = 8;
+
+/// Height of the featured items labels (in px).
+const LABEL_HEIGHT: u32 = 16;
+
+/// Ratio used to estimate the average width of a character from the font
+/// size.
+const CHAR_WIDTH_RATIO: f64 = 0.6;
+
+/// Width of the white border that separates categories and subcategories.
+const BORDER: u32 = 3;
+
+/// Padding of the container where items are displayed (in px).
+const ITEMS_PADDING: u32 = 12;
+
+/// Space between items (in px).
+const ITEMS_GAP: u32 = 5;
+
+/// Padding between the item border and its logo (in px).
+const ITEM_PADDING: u32 = 3;
+
+/// Padding between the featured item border and its logo (in px).
+const FEATURED_ITEM_PADDING: u32 = 8;
+
+/// Width reserved for each column in a row (in px). This value is used to
+/// calculate how many columns we'll have in a row.
+const COLUMN_RESERVED_WIDTH: u32 = 500;
+
+/// Minimum number of items (non featured) that must fit in a column.
+const MIN_COLUMN_ITEMS: u32 = 4;
+
+/// Lateral padding used in the layout of the columns (in px).
+const COLUMN_PADDING: u32 = 14;
+
+/// Space between items used in the layout of the columns (in px).
+const COLUMN_ITEMS_SPACING: u32 = 6;
+
+/// Template for the landscape poster.
+#[derive(Debug, Clone, Default, Template)]
+#[template(path = "poster.svg")]
+struct PosterSvg {
+    font_family: &'static str,
+    height: u32,
+    logos: Vec<PosterLogo>,
+    rects: Vec<PosterRect>,
+    texts: Vec<PosterText>,
+    width: u32,
+}
+
+/// Logo drawn in the poster.
+#[derive(Debug, Clone, Default)]
+struct PosterLogo {
+    height: u32,
+    href: String,
+    path: String,
+    width: u32,
+    x: u32,
+    y: u32,
+}
+
+/// Rectangle drawn in the poster.
+#[derive(Debug, Clone, Default)]
+struct PosterRect {
+    fill: String,
+    height: u32,
+    width: u32,
+    x: u32,
+    y: u32,
+
+    stroke: Option<String>,
+}
+
+/// Text drawn in the poster.
+#[derive(Debug, Clone, Default)]
+struct PosterText {
+    anchor: &'static str,
+    bold: bool,
+    content: String,
+    fill: String,
+    letter_spacing: u32,
+    size: u32,
+    vertical: bool,
+    x: u32,
+    y: u32,
+}
+
+/// Subcategory displayed in a category of the poster.
+struct Subcategory<'a> {
+    name: &'a str,
+    items: Vec<(&'a Item, bool)>,
+}
+
+/// Render the landscape screenshots provided (each of them along with the
+/// path of its files in the output directory, without extension), returning
+/// the paths of the files written.
+#[instrument(skip_all, err)]
+pub(crate) async fn render_posters(
+    landscape_data: &LandscapeData,
+    settings: &LandscapeSettings,
+    screenshots: &[(Screenshot, PathBuf)],
+    output_dir: &Path,
+) -> Result<Vec<String>> {
+    debug!("rendering posters");
+
+    // Load the system fonts (only once for all posters)
+    let fontdb = load_system_fonts().await?;
+
+    let mut outputs = vec![];
+    for (screenshot, path) in screenshots {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(output_dir.join(parent))?;
+        }
+        let formats = screenshot.formats();
+        let mut poster = layout_poster(landscape_data, settings, screenshot);
+        if poster.rects.is_empty() {
+            warn!(
+                name = screenshot.name(),
+                "no items found for screenshot, skipping"
+            );
+            continue;
+        }
+
+        // SVG (logos are embedded, so the file is self-contained)
+        if formats.contains(&ScreenshotFormat::Svg) {
+            embed_logos(&mut poster, output_dir);
+            let svg_path = path.with_extension("svg");
+            fs::write(output_dir.join(&svg_path), poster.render()?)?;
+            outputs.push(svg_path.to_string_lossy().into_owned());
+        }
+
+        // PNG
+        if !formats.contains(&ScreenshotFormat::Png) && !formats.contains(&ScreenshotFormat::Pdf) {
+            continue;
+        }
+        let image = render_png(&poster, fontdb.clone(), output_dir).await?;
+        if formats.contains(&ScreenshotFormat::Png) {
+            let png_path = path.with_extension("png");
+            let mut png_data = vec![];
+            image.write_to(&mut Cursor::new(&mut png_data), ImageFormat::Png)?;
+            fs::write(output_dir.join(&png_path), png_data)?;
+            outputs.push(png_path.to_string_lossy().into_owned());
+        }
+
+        // PDF (poster dimensions are converted from pixels to points)
+        if formats.contains(&ScreenshotFormat::Pdf) {
+            let pdf_path = path.with_extension("pdf");
+            let image = DynamicImage::ImageRgba8(image).to_rgb8();
+            let size = (f64::from(poster.width) * 0.75, f64::from(poster.height) * 0.75);
+            let pdf_data = render_pdf(&image, size, screenshot.paper_size.as_ref())?;
+            fs::write(output_dir.join(&pdf_path), pdf_data)?;
+            outputs.push(pdf_path.to_string_lossy().into_owned());
+        }
+    }
+
+    Ok(outputs)
+}
+
+/// Embed the logos in the poster provided, so that the poster SVG file is
+/// self-contained.
+fn embed_logos(poster: &mut PosterSvg, output_dir: &Path) {
+    for logo in &mut poster.logos {
+        let extension = Path::new(&logo.path).extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        let mime_type = match extension {
+            "svg" => "image/svg+xml".to_string(),
+            "jpg" => "image/jpeg".to_string(),
+            extension => format!("image/{extension}"),
+        };
+        match fs::read(output_dir.join(&logo.path)) {
+            Ok(data) => logo.href = format!("data:{mime_type};base64,{}", b64.encode(data)),
+            Err(err) => warn!(?err, ?logo.path, "error reading logo"),
+        }
+    }
+}
+
+/// Lay out the landscape poster for the screenshot provided. Only the group
+/// or category selected in the screenshot are included (if any).
+fn layout_poster(
+    landscape_data: &LandscapeData,
+    settings: &LandscapeSettings,
+    screenshot: &Screenshot,
+) -> PosterSvg {
+    let mut poster = PosterSvg {
+        font_family: FONT_FAMILY,
+        width: screenshot.width,
+        ..Default::default()
+    };
+    let colors = settings.colors.as_ref().map_or(DEFAULT_CATEGORIES_COLORS, |c| {
+        [c.color5.as_str(), c.color6.as_str()]
+    });
+
+    // Groups to display (all categories are displayed in a single group when
+    // no groups have been defined or a category has been selected)
+    let groups: Vec<(Option<&str>, Option<String>, Vec<&str>)> =
+        match (&settings.groups, &screenshot.category) {
+            (_, Some(category)) => vec![(None, None, vec![category.as_str()])],
+            (Some(groups), None) => groups
+                .iter()
+                .filter_map(|group| {
+                    let normalized_name =
+                        group.normalized_name.clone().unwrap_or(normalize_name(&group.name));
+                    if let Some(selected_group) = &screenshot.group
+                        && *selected_group != group.name
+                        && *selected_group != normalized_name
+                    {
+                        return None;
+                    }
+                    let categories = group.categories.iter().map(String::as_str).collect();
+                    Some((Some(group.name.as_str()), Some(normalized_name), categories))
+                })
+                .collect(),
+            (None, None) => vec![(
+                None,
+                None,
+                landscape_data.categories.iter().map(|c| c.name.as_str()).collect(),
+            )],
+        };
+
+    let mut y = PAGE_PADDING;
+    for (group_name, group_normalized_name, categories) in groups {
+        y += GROUP_MARGIN;
+
+        // Group title
+        if let Some(group_name) = group_name {
+            poster.texts.push(PosterText {
+                anchor: "start",
+                bold: true,
+                content: group_name.to_uppercase(),
+                fill: "#212529".to_string(),
+                letter_spacing: 2,
+                size: 20,
+                x: PAGE_PADDING,
+                y: y + 20,
+                ..Default::default()
+            });
+            y += GROUP_TITLE_HEIGHT;
+        }
+
+        // Categories
+        let mut categories_with_items = 0;
+        for category in categories {
+            let (subcategories, overridden) = category_subcategories(
+                landscape_data,
+                settings,
+                category,
+                group_normalized_name.as_deref(),
+            );
+            if subcategories.is_empty() {
+                continue;
+            }
+            let color = colors[categories_with_items % colors.len()];
+            y += layout_category(
+                &mut poster,
+                settings,
+                category,
+                &subcategories,
+                overridden,
+                color,
+                y,
+            );
+            categories_with_items += 1;
+        }
+
+        y += GROUP_MARGIN;
+    }
+    poster.height = y + PAGE_PADDING;
+
+    poster
+}
+
+/// Lay out the category provided in the poster at the vertical position
+/// given, returning the height it takes.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss,
+    clippy::too_many_arguments,
+    clippy::too_many_lines
+)]
+fn layout_category(
+    poster: &mut PosterSvg,
+    settings: &LandscapeSettings,
+    category: &str,
+    subcategories: &[Subcategory],
+    overridden: bool,
+    color: &str,
+    y: u32,
+) -> u32 {
+    let (item_width, item_height) = grid_item_size(settings.grid_items_size.as_ref());
+    let x = PAGE_PADDING + CATEGORY_TITLE_WIDTH;
+    let container_width = poster.width.saturating_sub(2 * PAGE_PADDING + CATEGORY_TITLE_WIDTH);
+
+    // Distribute subcategories in rows and calculate the columns widths
+    let subcategories_details: Vec<(usize, usize)> = subcategories
+        .iter()
+        .map(|s| {
+            (
+                s.items.len(),
+                s.items.iter().filter(|(_, featured)| *featured).count(),
+            )
+        })
+        .collect();
+    let layout = grid_category_layout(&subcategories_details, overridden, container_width, item_width);
+    let mut rows = vec![];
+    for row in layout {
+        let mut columns = vec![];
+        let mut remaining_width = container_width;
+        for (i, (subcategory_index, percentage)) in row.iter().enumerate() {
+            let column_width = if i == row.len() - 1 {
+                remaining_width
+            } else {
+                (f64::from(container_width) * percentage / 100.0).floor() as u32
+            };
+            remaining_width = remaining_width.saturating_sub(column_width);
+
+            // Place items in the column grid
+            let items_width = column_width.saturating_sub(BORDER + 2 * ITEMS_PADDING);
+            let items_per_row = ((items_width + ITEMS_GAP) / (item_width + ITEMS_GAP)).max(1);
+            let featured: Vec<bool> =
+                subcategories[*subcategory_index].items.iter().map(|(_, f)| *f).collect();
+            let (positions, items_rows) = pack_items(&featured, items_per_row as usize);
+            let items_rows = items_rows as u32;
+            let column_height = SUBCATEGORY_TITLE_HEIGHT
+                + 2 * ITEMS_PADDING
+                + items_rows * item_height
+                + items_rows.saturating_sub(1) * ITEMS_GAP
+                + BORDER;
+
+            columns.push((*subcategory_index, column_width, column_height, positions));
+        }
+        let row_height = columns.iter().map(|(_, _, height, _)| *height).max().unwrap_or_default();
+        rows.push((columns, row_height));
+    }
+
+    // Category title (long titles are displayed in two lines)
+    let title = truncate(category, CATEGORY_TITLE_MAX_CHARS);
+    let max_chars =
+        (f64::from(CATEGORY_TITLE_MAX_LENGTH) / (f64::from(TITLE_FONT_SIZE) * CHAR_WIDTH_RATIO)) as usize;
+    let lines = wrap_text(&title, max_chars, 2);
+    let title_length = lines.iter().map(|l| l.chars().count()).max().unwrap_or_default();
+    let title_length = (title_length as f64 * f64::from(TITLE_FONT_SIZE) * CHAR_WIDTH_RATIO) as u32;
+    let min_height = title_length + 2 * 16 + BORDER;
+
+    // Extend the last row when the category title needs more space
+    let rows_height: u32 = rows.iter().map(|(_, height)| *height).sum();
+    if rows_height < min_height
+        && let Some((_, height)) = rows.last_mut()
+    {
+        *height += min_height - rows_height;
+    }
+    let category_height = rows_height.max(min_height);
+
+    poster.rects.push(PosterRect {
+        fill: color.to_string(),
+        height: category_height - BORDER,
+        width: CATEGORY_TITLE_WIDTH - BORDER,
+        x: PAGE_PADDING,
+        y,
+        ..Default::default()
+    });
+    let line_height = TITLE_FONT_SIZE + 4;
+    let lines_count = lines.len() as u32;
+    for (i, line) in lines.into_iter().enumerate() {
+        // Lines are rotated, so they are stacked horizontally
+        let title_center = PAGE_PADDING + (CATEGORY_TITLE_WIDTH - BORDER) / 2;
+        let offset = (i as u32) * line_height + TITLE_FONT_SIZE / 3;
+        poster.texts.push(PosterText {
+            anchor: "middle",
+            content: line,
+            fill: "#ffffff".to_string(),
+            size: TITLE_FONT_SIZE,
+            vertical: true,
+            x: title_center + offset - (lines_count - 1) * line_height / 2,
+            y: y + (category_height - BORDER) / 2,
+            ..Default::default()
+        });
+    }
+
+    // Subcategories
+    let mut row_y = y;
+    for (columns, row_height) in rows {
+        let mut column_x = x;
+        for (subcategory_index, column_width, _, positions) in columns {
+            let subcategory = &subcategories[subcategory_index];
+            let width = column_width.saturating_sub(BORDER);
+
+            // Title
+            poster.rects.push(PosterRect {
+                fill: color.to_string(),
+                height: SUBCATEGORY_TITLE_HEIGHT,
+                width,
+                x: column_x,
+                y: row_y,
+                ..Default::default()
+            });
+            let max_chars = (f64::from(width.saturating_sub(2 * ITEMS_PADDING))
+                / (f64::from(TITLE_FONT_SIZE) * CHAR_WIDTH_RATIO)) as usize;
+            poster.texts.push(PosterText {
+                anchor: "start",
+                content: truncate(subcategory.name, max_chars),
+                fill: "#ffffff".to_string(),
+                size: TITLE_FONT_SIZE,
+                x: column_x + ITEMS_PADDING,
+                y: row_y + SUBCATEGORY_TITLE_HEIGHT / 2 + TITLE_FONT_SIZE / 3,
+                ..Default::default()
+            });
+
+            // Items
+            poster.rects.push(PosterRect {
+                fill: "#fdfdfe".to_string(),
+                height: row_height - SUBCATEGORY_TITLE_HEIGHT - BORDER,
+                width,
+                x: column_x,
+                y: row_y + SUBCATEGORY_TITLE_HEIGHT,
+                ..Default::default()
+            });
+            let items_x = column_x + ITEMS_PADDING;
+            let items_y = row_y + SUBCATEGORY_TITLE_HEIGHT + ITEMS_PADDING;
+            for ((item, featured), (column, row, span)) in subcategory.items.iter().zip(positions) {
+                let span = span as u32;
+                let item_x = items_x + column as u32 * (item_width + ITEMS_GAP);
+                let item_y = items_y + row as u32 * (item_height + ITEMS_GAP);
+                let card_width = span * item_width + (span - 1) * ITEMS_GAP;
+                let card_height = span * item_height + (span - 1) * ITEMS_GAP;
+                layout_item(
+                    poster,
+                    item,
+                    *featured && span > 1,
+                    color,
+                    (item_x, item_y, card_width, card_height),
+                );
+            }
+
+            column_x += column_width;
+        }
+        row_y += row_height;
+    }
+
+    category_height
+}
+
+/// Lay out the item provided in the poster, in the card area given (x, y,
+/// width and height).
+fn layout_item(poster: &mut PosterSvg, item: &Item, featured: bool, color: &str, area: (u32, u32, u32, u32)) {
+    let (x, y, width, height) = area;
+    poster.rects.push(PosterRect {
+        fill: "#ffffff".to_string(),
+        height,
+        width,
+        x,
+        y,
+        stroke: Some(color.to_string()),
+    });
+
+    // Featured items may include a label at the bottom of the card
+    let mut logo_height = height;
+    if featured && let Some(label) = item.featured.as_ref().and_then(|f| f.label.as_ref()) {
+        logo_height -= LABEL_HEIGHT;
+        poster.rects.push(PosterRect {
+            fill: "#f8f9fa".to_string(),
+            height: LABEL_HEIGHT,
+            width,
+            x,
+            y: y + logo_height,
+            ..Default::default()
+        });
+        poster.texts.push(PosterText {
+            anchor: "middle",
+            content: label.to_uppercase(),
+            fill: "#212529".to_string(),
+            size: LABEL_FONT_SIZE,
+            x: x + width / 2,
+            y: y + logo_height + LABEL_HEIGHT / 2 + LABEL_FONT_SIZE / 3,
+            ..Default::default()
+        });
+    }
+
+    if !item.logo.is_empty() {
+        let padding = if featured {
+            FEATURED_ITEM_PADDING
+        } else {
+            ITEM_PADDING
+        };
+        poster.logos.push(PosterLogo {
+            height: logo_height.saturating_sub(2 * padding),
+            path: item.logo.clone(),
+            width: width.saturating_sub(2 * padding),
+            x: x + padding,
+            y: y + padding,
+            ..Default::default()
+        });
+    }
+}
+
+/// Return the subcategories of the category provided that contain items, as
+/// well as whether the category has been overridden in the settings or not.
+fn category_subcategories<'a>(
+    landscape_data: &'a LandscapeData,
+    settings: &'a LandscapeSettings,
+    category: &str,
+    group: Option<&str>,
+) -> (Vec<Subcategory<'a>>, bool) {
+    // Subcategories (in order) from the settings (if overridden) or the data
+    let overridden = settings.categories.iter().flatten().find(|c| c.name == category);
+    let subcategories_names: Vec<&str> = match overridden {
+        Some(c) => c.subcategories.iter().map(String::as_str).collect(),
+        None => match landscape_data.categories.iter().find(|c| c.name == category) {
+            Some(c) => c.subcategories.iter().map(|s| s.name.as_str()).collect(),
+            None => return (vec![], false),
+        },
+    };
+
+    // Collect the items in each subcategory (items may belong to it through
+    // their additional categories as well)
+    let mut items: HashMap<&str, Vec<(&Item, bool)>> = HashMap::new();
+    for item in &landscape_data.items {
+        let in_category = |c: &str, s: &str| c == category && subcategories_names.contains(&s);
+        let mut subcategories = vec![];
+        if in_category(&item.category, &item.subcategory) {
+            subcategories.push(item.subcategory.as_str());
+        }
+        for additional in item.additional_categories.iter().flatten() {
+            if in_category(&additional.category, &additional.subcategory) {
+                subcategories.push(additional.subcategory.as_str());
+            }
+        }
+        let featured = is_featured(item, group, settings.featured_items.as_ref());
+        for subcategory in subcategories {
+            items.entry(subcategory).or_default().push((item, featured));
+        }
+    }
+
+    let mut subcategories = vec![];
+    for name in subcategories_names {
+        let Some(mut items) = items.remove(name) else {
+            continue;
+        };
+        items.sort_by_key(|(item, _)| {
+            let order = item.featured.as_ref().and_then(|f| f.order);
+            (order.is_none(), order)
+        });
+        subcategories.push(Subcategory { name, items });
+    }
+
+    (subcategories, overridden.is_some())
+}
+
+/// Check if the item provided should be featured in the group given.
+fn is_featured(item: &Item, group: Option<&str>, featured_items: Option<&Vec<FeaturedItemRule>>) -> bool {
+    if item.featured.is_none() {
+        return false;
+    }
+    let (Some(group), Some(rules)) = (group, featured_items) else {
+        return true;
+    };
+
+    // Featured items may be excluded from some groups
+    for rule in rules {
+        let matches = rule.options.iter().any(|option| match rule.field.as_str() {
+            "maturity" => item.maturity.as_ref() == Some(&option.value),
+            "subcategory" => item.subcategory == option.value,
+            _ => false,
+        });
+        let excluded = rule
+            .exclude
+            .as_ref()
+            .and_then(|e| e.groups.as_ref())
+            .is_some_and(|groups| groups.iter().any(|g| g == group));
+        if matches && excluded {
+            return false;
+        }
+    }
+    true
+}
+
+/// Return the size (width and height) of the items in the grid.
+fn grid_item_size(grid_items_size: Option<&GridItemsSize>) -> (u32, u32) {
+    match grid_items_size {
+        Some(GridItemsSize::Small) | None => (46, 41),
+        Some(GridItemsSize::Medium) => (60, 54),
+        Some(GridItemsSize::Large) => (75, 68),
+    }
+}
+
+/// Distribute the subcategories provided (items count and featured items
+/// count) in rows of columns, returning the subcategory index and the width
+/// percentage of each of the columns in each row.
+#[allow(clippy::cast_precision_loss)]
+fn grid_category_layout(
+    subcategories: &[(usize, usize)],
+    overridden: bool,
+    container_width: u32,
+    item_width: u32,
+) -> Vec<Vec<(usize, f64)>> {
+    if subcategories.is_empty() {
+        return vec![];
+    }
+
+    // Calculate number of rows needed to display the subcategories
+    let rows_count = if overridden {
+        subcategories.len()
+    } else {
+        let max_columns = (container_width / COLUMN_RESERVED_WIDTH).max(1) as usize;
+        subcategories.len().div_ceil(max_columns)
+    };
+
+    // Normalized items count considering featured items (each one takes the
+    // space of ~4 items)
+    let normalized_items_count: Vec<usize> =
+        subcategories.iter().map(|(count, featured)| count + featured * 3).collect();
+
+    // Distribute subcategories in rows (one column per subcategory), assigning
+    // the next available largest subcategory to each of the rows
+    let mut indexes: Vec<usize> = (0..subcategories.len()).collect();
+    if !overridden {
+        indexes.sort_by(|a, b| normalized_items_count[*b].cmp(&normalized_items_count[*a]));
+    }
+    let mut rows: Vec<Vec<(usize, f64)>> = vec![vec![]; rows_count];
+    for (i, index) in indexes.into_iter().enumerate() {
+        rows[i % rows_count].push((index, 0.0));
+    }
+
+    // Calculate columns width percentage from the subcategory weight in the row
+    let total_items_count: usize = normalized_items_count.iter().sum();
+    let weight = |index: usize| normalized_items_count[index] as f64 / total_items_count.max(1) as f64;
+    for row in &mut rows {
+        let row_weight: f64 = row.iter().map(|(index, _)| weight(*index)).sum();
+        for (index, percentage) in row.iter_mut() {
+            *percentage = if row_weight > 0.0 {
+                weight(*index) / row_weight * 100.0
+            } else {
+                100.0 / subcategories.len() as f64
+            };
+        }
+    }
+
+    // Adjust columns percentages to respect the minimum width for a column
+    let min_width =
+        2 * COLUMN_PADDING + (MIN_COLUMN_ITEMS - 1) * COLUMN_ITEMS_SPACING + item_width * MIN_COLUMN_ITEMS;
+    let min_percentage = f64::from(min_width) * 100.0 / f64::from(container_width.max(1));
+    for row in &mut rows {
+        // Increase percentage of columns not reaching the minimum
+        let mut owed = 0.0;
+        let mut owers = vec![];
+        for (i, (_, percentage)) in row.iter_mut().enumerate() {
+            if *percentage < min_percentage {
+                owed += min_percentage - *percentage;
+                *percentage = min_percentage;
+            } else {
+                owers.push(i);
+            }
+        }
+
+        // Take percentage owed from the other columns
+        if owed > 0.0 && !owers.is_empty() {
+            let owed_per_column = owed / owers.len() as f64;
+            owers.retain(|i| {
+                let percentage = &mut row[*i].1;
+                if *percentage - owed_per_column < min_percentage {
+                    owed -= *percentage - min_percentage;
+                    *percentage = min_percentage;
+                    false
+                } else {
+                    true
+                }
+            });
+            for i in &owers {
+                row[*i].1 -= owed / owers.len() as f64;
+            }
+        }
+    }
+
+    rows
+}
+
+/// Place the items provided (featured or not) in a grid with the number of
+/// columns given. Featured items take the space of 2x2 items. Returns the
+/// position of each item (column, row and span) and the number of rows used.
+fn pack_items(featured: &[bool], columns: usize) -> (Vec<(usize, usize, usize)>, usize) {
+    let mut occupied: Vec<Vec<bool>> = vec![];
+    let mut positions = vec![];
+
+    for featured in featured {
+        let span = if *featured && columns >= 2 { 2 } else { 1 };
+
+        // Find the first position where the item fits
+        let mut row = 0;
+        let (column, row) = loop {
+            while occupied.len() < row + span {
+                occupied.push(vec![false; columns]);
+            }
+            let column = (0..=columns - span)
+                .find(|c| (row..row + span).all(|r| (*c..*c + span).all(|c| !occupied[r][c])));
+            if let Some(column) = column {
+                break (column, row);
+            }
+            row += 1;
+        };
+
+        for cells in &mut occupied[row..row + span] {
+            cells[column..column + span].fill(true);
+        }
+        positions.push((column, row, span));
+    }
+
+    (positions, occupied.len())
+}
+
+/// Render the poster provided in PNG format. The poster is rendered without
+/// the logos, which are drawn afterwards on top of it.
+#[allow(clippy::cast_precision_loss)]
+async fn render_png(
+    poster: &PosterSvg,
+    fontdb: Arc<fontdb::Database>,
+    output_dir: &Path,
+) -> Result<RgbaImage> {
+    // Render poster
+    let svg = PosterSvg {
+        logos: vec![],
+        ..poster.clone()
+    }
+    .render()?;
+    let (width, height) = (poster.width * PNG_SCALE, poster.height * PNG_SCALE);
+    let mut image = tokio::task::spawn_blocking(move || -> Result<RgbaImage> {
+        let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default())?;
+        tree.convert_text(&fontdb);
+        let mut pixmap = Pixmap::new(width, height).context("invalid pixmap size")?;
+        let scale = PNG_SCALE as f32;
+        resvg::Tree::from_usvg(&tree).render(Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+        pixmap_to_image(&pixmap)
+    })
+    .await??;
+
+    // Draw logos centered in their area
+    let mut concurrency = num_cpus::get();
+    if concurrency > RENDER_LOGOS_MAX_CONCURRENCY {
+        concurrency = RENDER_LOGOS_MAX_CONCURRENCY;
+    }
+    let mut logos = stream::iter(poster.logos.iter())
+        .map(|logo| async move {
+            let path = output_dir.join(&logo.path);
+            let size = logo.width.min(logo.height) * PNG_SCALE;
+            let rendered = tokio::task::spawn_blocking(move || {
+                let extension =
+                    path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+                render_logo(&fs::read(&path)?, &extension, size)
+            })
+            .await;
+            (logo, rendered)
+        })
+        .buffer_unordered(concurrency);
+    while let Some((logo, rendered)) = logos.next().await {
+        match rendered {
+            Ok(Ok(logo_image)) => {
+                let x = logo.x * PNG_SCALE + (logo.width * PNG_SCALE).saturating_sub(logo_image.width()) / 2;
+                let y =
+                    logo.y * PNG_SCALE + (logo.height * PNG_SCALE).saturating_sub(logo_image.height()) / 2;
+                imageops::overlay(&mut image, &logo_image, i64::from(x), i64::from(y));
+            }
+            Ok(Err(err)) => warn!(?err, ?logo.path, "error rendering logo"),
+            Err(err) => warn!(?err, ?logo.path, "error executing render_logo task"),
+        }
+    }
+
+    Ok(image)
+}
+
+/// Render a PDF document containing a single page that displays the image
+/// given. The page takes the size of the image (in points) unless a paper
+/// size is provided, in which case the image is fitted into it (using the
+/// orientation that matches the image best).
+#[allow(clippy::similar_names)]
+fn render_pdf(image: &RgbImage, size: (f64, f64), paper_size: Option<&PaperSize>) -> Result<Vec<u8>> {
+    let (page_width, page_height, x, y, width, height) = if let Some(paper_size) = paper_size {
+        let (mut page_width, mut page_height) = paper_size.dimensions();
+        if size.0 > size.1 {
+            (page_width, page_height) = (page_height, page_width);
+        }
+        let scale = ((page_width - 2.0 * PDF_PAPER_MARGIN) / size.0)
+            .min((page_height - 2.0 * PDF_PAPER_MARGIN) / size.1);
+        let (width, height) = (size.0 * scale, size.1 * scale);
+        let (x, y) = ((page_width - width) / 2.0, (page_height - height) / 2.0);
+        (page_width, page_height, x, y, width, height)
+    } else {
+        (size.0, size.1, 0.0, 0.0, size.0, size.1)
+    };
+
+    // Large pages are scaled using a user unit, as most PDF readers do not
+    // support pages larger than 200 inches
+    let user_unit = (page_width.max(page_height) / PDF_MAX_PAGE_SIZE).ceil().max(1.0);
+    let (page_width, page_height) = (page_width / user_unit, page_height / user_unit);
+    let (x, y, width, height) = (
+        x / user_unit,
+        y / user_unit,
+        width / user_unit,
+        height / user_unit,
+    );
+
+    // Compress image data
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(image.as_raw())?;
+    let image_data = encoder.finish()?;
+
+    // Prepare document objects
+    let content = format!("q\n{width:.2} 0 0 {height:.2} {x:.2} {y:.2} cm\n/Im0 Do\nQ\n");
+    let objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {page_width:.2} {page_height:.2}] /UserUnit {user_unit:.1} \
+             /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>"
+        )
+        .into_bytes(),
+        [
+            format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                 /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>\nstream\n",
+                image.width(),
+                image.height(),
+                image_data.len()
+            )
+            .as_bytes(),
+            &image_data,
+            b"\nendstream",
+        ]
+        .concat(),
+        [
+            format!("<< /Length {} >>\nstream\n", content.len()).as_bytes(),
+            content.as_bytes(),
+            b"\nendstream",
+        ]
+        .concat(),
+    ];
+
+    // Write document
+    let mut pdf = b"%PDF-1.6\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+    let xref_offset = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .as_bytes(),
+    );
+
+    Ok(pdf)
+}
+
+/// Wrap the text provided in lines of the number of characters given (at
+/// most), truncating the last line if needed.
+fn wrap_text(text: &str, max_chars: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(current);
+            current = String::new();
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    if lines.len() > max_lines {
+        let last = lines[max_lines - 1..].join(" ");
+        lines.truncate(max_lines - 1);
+        lines.push(last);
+    }
+    lines.into_iter().map(|line| truncate(&line, max_chars)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use landscape2_core::data::{Category, ItemFeatured, Subcategory as DataSubcategory};
+
+    use super::*;
+
+    #[test]
+    fn pack_items_places_featured_items() {
+        let (positions, rows) = pack_items(&[true, false, false, false, false], 3);
+        assert_eq!(
+            positions,
+            vec![(0, 0, 2), (2, 0, 1), (2, 1, 1), (0, 2, 1), (1, 2, 1)]
+        );
+        assert_eq!(rows, 3);
+
+        let (positions, rows) = pack_items(&[true, false], 1);
+        assert_eq!(positions, vec![(0, 0, 1), (0, 1, 1)]);
+        assert_eq!(rows, 2);
+    }
+
+    #[test]
+    fn grid_category_layout_distributes_subcategories() {
+        let layout = grid_category_layout(&[(10, 0), (30, 0), (10, 0)], false, 1000, 46);
+        assert_eq!(layout.len(), 2);
+        assert_eq!(layout[0].iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(layout[1].iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![0]);
+        assert!((layout[0].iter().map(|(_, p)| p).sum::<f64>() - 100.0).abs() < 0.001);
+        assert!((layout[1][0].1 - 100.0).abs() < 0.001);
+
+        let layout = grid_category_layout(&[(10, 0), (30, 0)], true, 1000, 46);
+        assert_eq!(layout, vec![vec![(0, 100.0)], vec![(1, 100.0)]]);
+    }
+
+    #[test]
+    fn layout_poster_succeeds() {
+        let landscape_data = LandscapeData {
+            categories: vec![Category {
+                name: "Category".to_string(),
+                normalized_name: "category".to_string(),
+                subcategories: vec![
+                    DataSubcategory {
+                        name: "Subcategory 1".to_string(),
+                        normalized_name: "subcategory-1".to_string(),
+                    },
+                    DataSubcategory {
+                        name: "Subcategory 2".to_string(),
+                        normalized_name: "subcategory-2".to_string(),
+                    },
+                ],
+            }],
+            items: vec![
+                Item {
+                    category: "Category".to_string(),
+                    subcategory: "Subcategory 1".to_string(),
+                    name: "Item 1".to_string(),
+                    logo: "logos/item1.svg".to_string(),
+                    featured: Some(ItemFeatured {
+                        label: Some("Graduated".to_string()),
+                        order: Some(1),
+                    }),
+                    ..Default::default()
+                },
+                Item {
+                    category: "Category".to_string(),
+                    subcategory: "Subcategory 1".to_string(),
+                    name: "Item 2".to_string(),
+                    logo: "logos/item2.svg".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let screenshot = Screenshot {
+            width: 1200,
+            ..Default::default()
+        };
+        let poster = layout_poster(&landscape_data, &LandscapeSettings::default(), &screenshot);
+        assert_eq!(poster.width, 1200);
+        assert!(poster.height > 0);
+        assert_eq!(poster.logos.len(), 2);
+        assert!(poster.logos[0].width > poster.logos[1].width);
+        assert!(poster.texts.iter().any(|t| t.content == "Category" && t.vertical));
+        assert!(poster.texts.iter().any(|t| t.content == "Subcategory 1"));
+        assert!(!poster.texts.iter().any(|t| t.content == "Subcategory 2"));
+        assert!(poster.texts.iter().any(|t| t.content == "GRADUATED"));
+        assert!(poster.render().is_ok());
+
+        // Only the category selected is displayed
+        let screenshot = Screenshot {
+            width: 1200,
+            category: Some("Other category".to_string()),
+            ..Default::default()
+        };
+        let poster = layout_poster(&landscape_data, &LandscapeSettings::default(), &screenshot);
+        assert!(poster.rects.is_empty());
+    }
+
+    #[test]
+    fn render_pdf_succeeds() {
+        let image = RgbImage::new(10, 20);
+        let pdf = render_pdf(&image, (7.5, 15.0), None).unwrap();
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with("%PDF-1.6"));
+        assert!(pdf.contains("/MediaBox [0 0 7.50 15.00]"));
+        assert!(pdf.contains("7.50 0 0 15.00 0.00 0.00 cm"));
+        assert!(pdf.contains("/Width 10 /Height 20"));
+        assert!(pdf.ends_with("%%EOF\n"));
+
+        // Landscape image fitted into an A4 paper
+        let pdf = render_pdf(&image, (2000.0, 1000.0), Some(&PaperSize::A4)).unwrap();
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.contains("/MediaBox [0 0 841.89 595.28]"));
+        assert!(pdf.contains("805.89 0 0 402.94 18.00 96.17 cm"));
+    }
+
+    #[test]
+    fn wrap_text_works() {
+        assert_eq!(wrap_text("Category", 20, 2), vec!["Category"]);
+        assert_eq!(
+            wrap_text("App Definition and Development", 20, 2),
+            vec!["App Definition and", "Development"]
+        );
+        assert_eq!(wrap_text("One two three four", 8, 2), vec!["One two", "three f…"]);
+    }
+}
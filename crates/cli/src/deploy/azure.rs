@@ -0,0 +1,475 @@
+//! This module defines the functionality of the deploy CLI subcommand for the
+//! Azure Blob Storage provider.
+//!
+//! The Blob service REST API is used directly, so an emulator like Azurite
+//! can be used as well by providing its endpoint url.
+
+use std::{collections::HashMap, env, fmt::Write};
+
+use anyhow::{Result, bail, format_err};
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose::STANDARD as b64};
+use chrono::Utc;
+use hmac::{Hmac, KeyInit, Mac};
+use md5::{Digest, Md5};
+use reqwest::{Method, StatusCode, header};
+use serde::Deserialize;
+use sha2::Sha256;
+use tracing::instrument;
+use url::Url;
+
+use super::{CommonArgs, Key, Object, Storage, base64_md5_to_checksum};
+
+/// Environment variable that contains the storage account access key.
+const AZURE_STORAGE_KEY: &str = "AZURE_STORAGE_KEY";
+
+/// Environment variable that contains a SAS token granting access to the
+/// container (used instead of the access key when provided).
+const AZURE_STORAGE_SAS_TOKEN: &str = "AZURE_STORAGE_SAS_TOKEN";
+
+/// Version of the Blob service REST API used.
+const API_VERSION: &str = "2021-08-06";
+
+/// Azure Blob Storage provider arguments.
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub config: Config,
+
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+/// Azure Blob Storage provider configuration.
+#[derive(clap::Args)]
+pub struct Config {
+    /// Storage account name.
+    #[arg(long)]
+    pub account: String,
+
+    /// Container to copy the landscape website files to (use `$web` when
+    /// hosting the website using the storage account static website feature).
+    #[arg(long)]
+    pub container: String,
+
+    /// Endpoint url of the Blob service (defaults to
+    /// https://{account}.blob.core.windows.net). When using an emulator like
+    /// Azurite it should include the account (i.e.
+    /// http://127.0.0.1:10000/devstoreaccount1).
+    #[arg(long)]
+    pub endpoint_url: Option<String>,
+}
+
+/// Credentials used to authorize the requests to the Blob service.
+enum Credentials {
+    /// Storage account access key (decoded).
+    SharedKey(Vec<u8>),
+    /// Shared access signature token.
+    SasToken(String),
+}
+
+/// Storage implementation backed by Azure Blob Storage.
+pub(crate) struct Azure {
+    account: String,
+    container_url: Url,
+    credentials: Credentials,
+    http_client: reqwest::Client,
+}
+
+impl Azure {
+    /// Create a new Azure instance.
+    pub(crate) fn new(config: &Config) -> Result<Self> {
+        // Prepare credentials from the environment
+        let credentials = match (env::var(AZURE_STORAGE_SAS_TOKEN), env::var(AZURE_STORAGE_KEY)) {
+            (Ok(token), _) if !token.is_empty() => {
+                Credentials::SasToken(token.trim_start_matches('?').to_string())
+            }
+            (_, Ok(key)) if !key.is_empty() => Credentials::SharedKey(
+                b64.decode(key.trim()).map_err(|_| format_err!("invalid {AZURE_STORAGE_KEY} provided"))?,
+            ),
+            _ => bail!(
+                "required environment variable {AZURE_STORAGE_KEY} or {AZURE_STORAGE_SAS_TOKEN} not provided"
+            ),
+        };
+
+        Self::new_with_credentials(config, credentials)
+    }
+
+    /// Create a new Azure instance using the credentials provided.
+    fn new_with_credentials(config: &Config, credentials: Credentials) -> Result<Self> {
+        let endpoint_url = match &config.endpoint_url {
+            Some(endpoint_url) => endpoint_url.clone(),
+            None => format!("https://{}.blob.core.windows.net", config.account),
+        };
+        let mut container_url = Url::parse(&endpoint_url)?;
+        container_url
+            .path_segments_mut()
+            .map_err(|()| format_err!("invalid endpoint url"))?
+            .pop_if_empty()
+            .push(&config.container);
+
+        Ok(Self {
+            account: config.account.clone(),
+            container_url,
+            credentials,
+            http_client: reqwest::Client::new(),
+        })
+    }
+
+    /// Return the url of the blob provided.
+    fn blob_url(&self, key: &str) -> Result<Url> {
+        let mut url = self.container_url.clone();
+        url.path_segments_mut()
+            .map_err(|()| format_err!("invalid container url"))?
+            .extend(key.split('/'));
+        Ok(url)
+    }
+
+    /// Prepare a request to the url provided, including the headers required
+    /// by the Blob service and the authorization details.
+    fn request(&self, method: Method, mut url: Url, headers: header::HeaderMap) -> Result<reqwest::Request> {
+        if let Credentials::SasToken(token) = &self.credentials {
+            let query = match url.query() {
+                Some(query) => format!("{query}&{token}"),
+                None => token.clone(),
+            };
+            url.set_query(Some(&query));
+        }
+
+        let mut request = reqwest::Request::new(method, url);
+        *request.headers_mut() = headers;
+        let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        request.headers_mut().insert("x-ms-date", date.parse()?);
+        request.headers_mut().insert("x-ms-version", API_VERSION.parse()?);
+
+        if let Credentials::SharedKey(key) = &self.credentials {
+            let string_to_sign = string_to_sign(&self.account, &request);
+            let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
+            mac.update(string_to_sign.as_bytes());
+            let signature = b64.encode(mac.finalize().into_bytes());
+            let mut authorization: header::HeaderValue =
+                format!("SharedKey {}:{signature}", self.account).parse()?;
+            authorization.set_sensitive(true);
+            request.headers_mut().insert(header::AUTHORIZATION, authorization);
+        }
+
+        Ok(request)
+    }
+}
+
+#[async_trait]
+impl Storage for Azure {
+    /// [Storage::delete_object]
+    #[instrument(skip(self), err)]
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        let request = self.request(Method::DELETE, self.blob_url(key)?, header::HeaderMap::new())?;
+        let response = self.http_client.execute(request).await?;
+        if !matches!(response.status(), StatusCode::ACCEPTED | StatusCode::NOT_FOUND) {
+            bail!("unexpected status code deleting blob: {:?}", response.status());
+        }
+        Ok(())
+    }
+
+    /// [Storage::get_object]
+    #[instrument(skip(self), err)]
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let request = self.request(Method::GET, self.blob_url(key)?, header::HeaderMap::new())?;
+        let response = self.http_client.execute(request).await?;
+        match response.status() {
+            StatusCode::OK => Ok(Some(response.bytes().await?.to_vec())),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => bail!("unexpected status code getting blob: {status:?}"),
+        }
+    }
+
+    /// [Storage::list_objects]
+    #[instrument(skip_all, err)]
+    async fn list_objects(&self) -> Result<HashMap<Key, Object>> {
+        let mut objects = HashMap::new();
+
+        let mut marker: Option<String> = None;
+        loop {
+            let mut url = self.container_url.clone();
+            url.query_pairs_mut().append_pair("restype", "container").append_pair("comp", "list");
+            if let Some(marker) = &marker {
+                url.query_pairs_mut().append_pair("marker", marker);
+            }
+            let request = self.request(Method::GET, url, header::HeaderMap::new())?;
+            let response = self.http_client.execute(request).await?;
+            if response.status() != StatusCode::OK {
+                bail!("unexpected status code listing blobs: {:?}", response.status());
+            }
+            let output: EnumerationResults = quick_xml::de::from_str(&response.text().await?)?;
+            for blob in output.blobs.blob {
+                let checksum = blob.properties.content_md5.as_deref().and_then(base64_md5_to_checksum);
+                let size = blob.properties.content_length.unwrap_or_default();
+                objects.insert(blob.name, Object { checksum, size });
+            }
+            marker = output.next_marker.filter(|marker| !marker.is_empty());
+            if marker.is_none() {
+                break;
+            }
+        }
+
+        Ok(objects)
+    }
+
+    /// [Storage::put_object]
+    #[instrument(skip(self, body), err)]
+    async fn put_object(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()> {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(header::CONTENT_LENGTH, body.len().into());
+        headers.insert("content-md5", b64.encode(Md5::digest(&body)).parse()?);
+        headers.insert(header::CONTENT_TYPE, content_type.parse()?);
+        headers.insert("x-ms-blob-type", "BlockBlob".parse()?);
+        let mut request = self.request(Method::PUT, self.blob_url(key)?, headers)?;
+        *request.body_mut() = Some(body.into());
+
+        let response = self.http_client.execute(request).await?;
+        if response.status() != StatusCode::CREATED {
+            bail!("unexpected status code uploading blob: {:?}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// Build the string to sign used to authorize the request provided using the
+/// Shared Key scheme.
+fn string_to_sign(account: &str, request: &reqwest::Request) -> String {
+    let headers = request.headers();
+    let header =
+        |name: header::HeaderName| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let content_length = match header(header::CONTENT_LENGTH) {
+        "0" => "",
+        content_length => content_length,
+    };
+
+    // Standard headers
+    let mut string_to_sign = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n",
+        request.method().as_str(),
+        header(header::CONTENT_ENCODING),
+        header(header::CONTENT_LANGUAGE),
+        content_length,
+        header(header::HeaderName::from_static("content-md5")),
+        header(header::CONTENT_TYPE),
+        header(header::DATE),
+        header(header::IF_MODIFIED_SINCE),
+        header(header::IF_MATCH),
+        header(header::IF_NONE_MATCH),
+        header(header::IF_UNMODIFIED_SINCE),
+        header(header::RANGE),
+    );
+
+    // Canonicalized headers
+    let mut ms_headers: Vec<(&str, &str)> = headers
+        .iter()
+        .filter(|(name, _)| name.as_str().starts_with("x-ms-"))
+        .map(|(name, value)| (name.as_str(), value.to_str().unwrap_or_default().trim()))
+        .collect();
+    ms_headers.sort_unstable();
+    for (name, value) in ms_headers {
+        let _ = writeln!(string_to_sign, "{name}:{value}");
+    }
+
+    // Canonicalized resource
+    let _ = write!(string_to_sign, "/{account}{}", request.url().path());
+    let mut params: Vec<(String, String)> = request
+        .url()
+        .query_pairs()
+        .map(|(name, value)| (name.to_lowercase(), value.into_owned()))
+        .collect();
+    params.sort_unstable();
+    for (name, value) in params {
+        let _ = write!(string_to_sign, "\n{name}:{value}");
+    }
+
+    string_to_sign
+}
+
+/// Blob service list blobs response.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct EnumerationResults {
+    #[serde(default)]
+    blobs: Blobs,
+    next_marker: Option<String>,
+}
+
+/// Blobs listed.
+#[derive(Debug, Default, Deserialize)]
+struct Blobs {
+    #[serde(rename = "Blob", default)]
+    blob: Vec<Blob>,
+}
+
+/// Blob details.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Blob {
+    name: String,
+    properties: BlobProperties,
+}
+
+/// Blob properties.
+#[derive(Debug, Deserialize)]
+struct BlobProperties {
+    #[serde(rename = "Content-Length")]
+    content_length: Option<u64>,
+    #[serde(rename = "Content-MD5")]
+    content_md5: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    /// Access key of the Azurite emulator well-known development account.
+    const DEV_ACCOUNT_KEY: &str =
+        "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==";
+
+    fn azure(endpoint_url: &str, credentials: Credentials) -> Azure {
+        let config = Config {
+            account: "devstoreaccount1".to_string(),
+            container: "$web".to_string(),
+            endpoint_url: Some(format!("{endpoint_url}/devstoreaccount1")),
+        };
+        Azure::new_with_credentials(&config, credentials).unwrap()
+    }
+
+    #[test]
+    fn string_to_sign_includes_canonicalized_headers_and_resource() {
+        let azure = azure(
+            "http://127.0.0.1:10000",
+            Credentials::SharedKey(b64.decode(DEV_ACCOUNT_KEY).unwrap()),
+        );
+        let mut url = azure.container_url.clone();
+        url.query_pairs_mut().append_pair("restype", "container").append_pair("comp", "list");
+        let request = azure.request(Method::GET, url, header::HeaderMap::new()).unwrap();
+
+        let date = request.headers().get("x-ms-date").unwrap().to_str().unwrap();
+        assert_eq!(
+            string_to_sign("devstoreaccount1", &request),
+            format!(
+                "GET\n\n\n\n\n\n\n\n\n\n\n\nx-ms-date:{date}\nx-ms-version:{API_VERSION}\n/devstoreaccount1/devstoreaccount1/$web\ncomp:list\nrestype:container"
+            )
+        );
+        assert!(
+            request
+                .headers()
+                .get(header::AUTHORIZATION)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("SharedKey devstoreaccount1:")
+        );
+    }
+
+    #[tokio::test]
+    async fn list_objects_follows_pagination() {
+        let mut server = mockito::Server::new_async().await;
+        let page1 = server
+            .mock("GET", "/devstoreaccount1/$web")
+            .match_query(Matcher::Regex("^restype=container&comp=list&sv=x$".into()))
+            .with_body(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+                <EnumerationResults ServiceEndpoint="http://127.0.0.1:10000/devstoreaccount1" ContainerName="$web">
+                  <Blobs>
+                    <Blob>
+                      <Name>index.html</Name>
+                      <Properties>
+                        <Content-Length>11</Content-Length>
+                        <Content-MD5>XrY7u+Ae7tCTyyK7j1rNww==</Content-MD5>
+                      </Properties>
+                    </Blob>
+                  </Blobs>
+                  <NextMarker>m1</NextMarker>
+                </EnumerationResults>"#,
+            )
+            .create_async()
+            .await;
+        let page2 = server
+            .mock("GET", "/devstoreaccount1/$web")
+            .match_query(Matcher::UrlEncoded("marker".into(), "m1".into()))
+            .with_body(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+                <EnumerationResults>
+                  <Blobs>
+                    <Blob>
+                      <Name>data/a&amp;b.json</Name>
+                      <Properties><Content-Length>2</Content-Length><Content-MD5 /></Properties>
+                    </Blob>
+                  </Blobs>
+                  <NextMarker />
+                </EnumerationResults>"#,
+            )
+            .create_async()
+            .await;
+
+        let azure = azure(&server.url(), Credentials::SasToken("sv=x".to_string()));
+        let objects = azure.list_objects().await.unwrap();
+        assert_eq!(
+            objects,
+            HashMap::from([
+                (
+                    "index.html".to_string(),
+                    Object {
+                        checksum: Some("5eb63bbbe01eeed093cb22bb8f5acdc3".to_string()),
+                        size: 11,
+                    }
+                ),
+                (
+                    "data/a&b.json".to_string(),
+                    Object {
+                        checksum: None,
+                        size: 2
+                    }
+                ),
+            ])
+        );
+        page1.assert_async().await;
+        page2.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn put_object_uploads_block_blob() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/devstoreaccount1/$web/data/full.json")
+            .match_header(
+                "authorization",
+                Matcher::Regex("^SharedKey devstoreaccount1:.+$".into()),
+            )
+            .match_header("content-md5", "mZFLkyvTelC5g8XnyQrpOw==")
+            .match_header("content-type", "application/json")
+            .match_header("x-ms-blob-type", "BlockBlob")
+            .match_body("{}")
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let azure = azure(
+            &server.url(),
+            Credentials::SharedKey(b64.decode(DEV_ACCOUNT_KEY).unwrap()),
+        );
+        azure.put_object("data/full.json", b"{}".to_vec(), "application/json").await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn delete_object_deletes_blob() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("DELETE", "/devstoreaccount1/$web/assets/index.js")
+            .match_query(Matcher::UrlEncoded("sv".into(), "x".into()))
+            .with_status(202)
+            .create_async()
+            .await;
+
+        let azure = azure(&server.url(), Credentials::SasToken("sv=x".to_string()));
+        azure.delete_object("assets/index.js").await.unwrap();
+        mock.assert_async().await;
+    }
+}
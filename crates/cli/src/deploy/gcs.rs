@@ -0,0 +1,304 @@
+//! This module defines the functionality of the deploy CLI subcommand for the
+//! Google Cloud Storage provider.
+//!
+//! The GCS JSON API is used directly, so an emulator like fake-gcs-server can
+//! be used as well by providing its endpoint url.
+
+use std::{collections::HashMap, env};
+
+use anyhow::{Result, bail, format_err};
+use async_trait::async_trait;
+use reqwest::{StatusCode, header};
+use serde::Deserialize;
+use tracing::instrument;
+use url::Url;
+
+use super::{CommonArgs, Key, Object, Storage, base64_md5_to_checksum};
+
+/// Google Cloud Storage default endpoint url.
+const GCS_ENDPOINT_URL: &str = "https://storage.googleapis.com";
+
+/// Environment variable that contains the OAuth2 access token used to
+/// authenticate the requests (i.e. `gcloud auth print-access-token`).
+const GCS_ACCESS_TOKEN: &str = "GCS_ACCESS_TOKEN";
+
+/// Google Cloud Storage provider arguments.
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub config: Config,
+
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+/// Google Cloud Storage provider configuration.
+#[derive(clap::Args)]
+pub struct Config {
+    /// Bucket to copy the landscape website files to.
+    #[arg(long)]
+    pub bucket: String,
+
+    /// Endpoint url of the GCS API (i.e. http://localhost:4443 when using an
+    /// emulator).
+    #[arg(long, default_value = GCS_ENDPOINT_URL)]
+    pub endpoint_url: String,
+}
+
+/// Storage implementation backed by Google Cloud Storage.
+pub(crate) struct Gcs {
+    bucket: String,
+    endpoint_url: String,
+    http_client: reqwest::Client,
+}
+
+impl Gcs {
+    /// Create a new Gcs instance.
+    pub(crate) fn new(config: &Config) -> Result<Self> {
+        // Setup HTTP client, authenticating requests when an access token
+        // is provided (it's not required by emulators)
+        let mut headers = header::HeaderMap::new();
+        match env::var(GCS_ACCESS_TOKEN) {
+            Ok(token) if !token.is_empty() => {
+                let mut value = header::HeaderValue::from_str(&format!("Bearer {token}"))?;
+                value.set_sensitive(true);
+                headers.insert(header::AUTHORIZATION, value);
+            }
+            _ if config.endpoint_url == GCS_ENDPOINT_URL => {
+                bail!("required environment variable {GCS_ACCESS_TOKEN} not provided");
+            }
+            _ => {}
+        }
+        let http_client = reqwest::Client::builder().default_headers(headers).build()?;
+
+        Ok(Self {
+            bucket: config.bucket.clone(),
+            endpoint_url: config.endpoint_url.trim_end_matches('/').to_string(),
+            http_client,
+        })
+    }
+
+    /// Return the url of the metadata of the object provided.
+    fn object_url(&self, key: &str) -> Result<Url> {
+        let mut url = Url::parse(&format!("{}/storage/v1/b/{}/o", self.endpoint_url, self.bucket))?;
+        url.path_segments_mut().map_err(|()| format_err!("invalid endpoint url"))?.push(key);
+        Ok(url)
+    }
+}
+
+#[async_trait]
+impl Storage for Gcs {
+    /// [Storage::delete_object]
+    #[instrument(skip(self), err)]
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        let response = self.http_client.delete(self.object_url(key)?).send().await?;
+        if !matches!(response.status(), StatusCode::NO_CONTENT | StatusCode::NOT_FOUND) {
+            bail!("unexpected status code deleting object: {:?}", response.status());
+        }
+        Ok(())
+    }
+
+    /// [Storage::get_object]
+    #[instrument(skip(self), err)]
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.http_client.get(self.object_url(key)?).query(&[("alt", "media")]).send().await?;
+        match response.status() {
+            StatusCode::OK => Ok(Some(response.bytes().await?.to_vec())),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => bail!("unexpected status code getting object: {status:?}"),
+        }
+    }
+
+    /// [Storage::list_objects]
+    #[instrument(skip_all, err)]
+    async fn list_objects(&self) -> Result<HashMap<Key, Object>> {
+        let mut objects = HashMap::new();
+
+        let url = format!("{}/storage/v1/b/{}/o", self.endpoint_url, self.bucket);
+        let mut page_token = None;
+        loop {
+            let mut request =
+                self.http_client.get(&url).query(&[("fields", "items(name,md5Hash,size),nextPageToken")]);
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+            let response = request.send().await?;
+            if response.status() != StatusCode::OK {
+                bail!("unexpected status code listing objects: {:?}", response.status());
+            }
+            let output: ListObjectsOutput = response.json().await?;
+            for object in output.items.unwrap_or_default() {
+                let checksum = object.md5_hash.as_deref().and_then(base64_md5_to_checksum);
+                let size = object.size.and_then(|size| size.parse().ok()).unwrap_or_default();
+                objects.insert(object.name, Object { checksum, size });
+            }
+            if output.next_page_token.is_none() {
+                break;
+            }
+            page_token = output.next_page_token;
+        }
+
+        Ok(objects)
+    }
+
+    /// [Storage::put_object]
+    #[instrument(skip(self, body), err)]
+    async fn put_object(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()> {
+        let url = format!("{}/upload/storage/v1/b/{}/o", self.endpoint_url, self.bucket);
+        let response = self
+            .http_client
+            .post(url)
+            .query(&[("uploadType", "media"), ("name", key)])
+            .header(header::CONTENT_TYPE, content_type)
+            .body(body)
+            .send()
+            .await?;
+        if response.status() != StatusCode::OK {
+            bail!("unexpected status code uploading object: {:?}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// GCS list objects response.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListObjectsOutput {
+    items: Option<Vec<ObjectMetadata>>,
+    next_page_token: Option<String>,
+}
+
+/// GCS object metadata.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ObjectMetadata {
+    name: String,
+    md5_hash: Option<String>,
+    size: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    fn gcs(endpoint_url: &str) -> Gcs {
+        Gcs::new(&Config {
+            bucket: "bucket".to_string(),
+            endpoint_url: endpoint_url.to_string(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn list_objects_follows_pagination() {
+        let mut server = mockito::Server::new_async().await;
+        let page1 = server
+            .mock("GET", "/storage/v1/b/bucket/o")
+            .match_query(Matcher::Regex("^fields=[^&]+$".into()))
+            .with_body(
+                r#"{"items": [{"name": "index.html", "md5Hash": "XrY7u+Ae7tCTyyK7j1rNww==", "size": "11"}], "nextPageToken": "t1"}"#,
+            )
+            .create_async()
+            .await;
+        let page2 = server
+            .mock("GET", "/storage/v1/b/bucket/o")
+            .match_query(Matcher::UrlEncoded("pageToken".into(), "t1".into()))
+            .with_body(r#"{"items": [{"name": "data/full.json", "size": "2"}]}"#)
+            .create_async()
+            .await;
+
+        let objects = gcs(&server.url()).list_objects().await.unwrap();
+        assert_eq!(
+            objects,
+            HashMap::from([
+                (
+                    "index.html".to_string(),
+                    Object {
+                        checksum: Some("5eb63bbbe01eeed093cb22bb8f5acdc3".to_string()),
+                        size: 11,
+                    }
+                ),
+                (
+                    "data/full.json".to_string(),
+                    Object {
+                        checksum: None,
+                        size: 2
+                    }
+                ),
+            ])
+        );
+        page1.assert_async().await;
+        page2.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn put_object_uploads_media() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/upload/storage/v1/b/bucket/o")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("uploadType".into(), "media".into()),
+                Matcher::UrlEncoded("name".into(), "data/full.json".into()),
+            ]))
+            .match_header("content-type", "application/json")
+            .match_body("{}")
+            .with_body("{}")
+            .create_async()
+            .await;
+
+        gcs(&server.url())
+            .put_object("data/full.json", b"{}".to_vec(), "application/json")
+            .await
+            .unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn put_object_fails_on_unexpected_status() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/upload/storage/v1/b/bucket/o").with_status(403).create_async().await;
+
+        assert!(gcs(&server.url()).put_object("index.html", vec![], "text/html").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn get_object_returns_none_when_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let found = server
+            .mock("GET", "/storage/v1/b/bucket/o/.landscape2-deploy.json")
+            .match_query(Matcher::UrlEncoded("alt".into(), "media".into()))
+            .with_body("{}")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let gcs = gcs(&server.url());
+        assert_eq!(
+            gcs.get_object(".landscape2-deploy.json").await.unwrap(),
+            Some(b"{}".to_vec())
+        );
+        found.remove_async().await;
+        server
+            .mock("GET", "/storage/v1/b/bucket/o/.landscape2-deploy.json")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+        assert_eq!(gcs.get_object(".landscape2-deploy.json").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn delete_object_encodes_object_name() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("DELETE", "/storage/v1/b/bucket/o/assets%2Findex.js")
+            .with_status(204)
+            .create_async()
+            .await;
+
+        gcs(&server.url()).delete_object("assets/index.js").await.unwrap();
+        mock.assert_async().await;
+    }
+}
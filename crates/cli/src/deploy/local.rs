@@ -0,0 +1,192 @@
+//! This module defines the functionality of the deploy CLI subcommand for the
+//! local directory provider. The landscape website files are synchronized
+//! (rsync style) with a directory, which could be the document root of a web
+//! server or a mounted network filesystem, for example.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, format_err};
+use async_trait::async_trait;
+use tracing::instrument;
+use walkdir::WalkDir;
+
+use super::{CommonArgs, Key, Object, Storage, md5sum, object_key, object_path};
+
+/// Local directory provider arguments.
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub config: Config,
+
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+/// Local directory provider configuration.
+#[derive(clap::Args)]
+pub struct Config {
+    /// Directory to copy the landscape website files to (it will be created
+    /// if it doesn't exist).
+    #[arg(long)]
+    pub target_dir: PathBuf,
+}
+
+/// Storage implementation backed by a local directory.
+pub(crate) struct Local {
+    target_dir: PathBuf,
+}
+
+impl Local {
+    /// Create a new Local instance.
+    pub(crate) fn new(config: &Config) -> Result<Self> {
+        fs::create_dir_all(&config.target_dir).context(format_err!(
+            "error creating target directory {}",
+            config.target_dir.display()
+        ))?;
+
+        Ok(Self {
+            target_dir: config.target_dir.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for Local {
+    /// [Storage::delete_object]
+    #[instrument(skip(self), err)]
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        let path = object_path(&self.target_dir, key);
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        }
+
+        // Remove parent directories left empty
+        let mut dir = path.parent();
+        while let Some(path) = dir {
+            if path == self.target_dir || tokio::fs::remove_dir(path).await.is_err() {
+                break;
+            }
+            dir = path.parent();
+        }
+
+        Ok(())
+    }
+
+    /// [Storage::get_object]
+    #[instrument(skip(self), err)]
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(object_path(&self.target_dir, key)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// [Storage::list_objects]
+    #[instrument(skip_all, err)]
+    async fn list_objects(&self) -> Result<HashMap<Key, Object>> {
+        let target_dir = self.target_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut objects = HashMap::new();
+            for entry in WalkDir::new(&target_dir) {
+                let entry = entry?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let key = object_key(&target_dir, entry.path());
+                let object = Object {
+                    checksum: Some(md5sum(entry.path())?),
+                    size: entry.metadata()?.len(),
+                };
+                objects.insert(key, object);
+            }
+            Ok(objects)
+        })
+        .await?
+    }
+
+    /// [Storage::put_object]
+    #[instrument(skip(self, body), err)]
+    async fn put_object(&self, key: &str, body: Vec<u8>, _content_type: &str) -> Result<()> {
+        let path = object_path(&self.target_dir, key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        write_atomically(&path, &body).await
+    }
+}
+
+/// Write the data provided to a temporary file next to the destination one,
+/// and rename it afterwards so that readers never see a partial file.
+async fn write_atomically(path: &Path, data: &[u8]) -> Result<()> {
+    let mut tmp_file_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_file_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_file_name);
+    tokio::fs::write(&tmp_path, data).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::{
+        super::{DEPLOY_STATE_OBJECT, sync},
+        *,
+    };
+    use crate::deploy::tests::common_args;
+
+    #[tokio::test]
+    async fn sync_to_local_directory_works() {
+        // Setup landscape and target directories
+        let landscape_tmp = tempdir().unwrap();
+        let landscape_dir = landscape_tmp.path();
+        fs::create_dir_all(landscape_dir.join("data")).unwrap();
+        fs::write(landscape_dir.join("index.html"), b"index").unwrap();
+        fs::write(landscape_dir.join("data").join("full.json"), b"{}").unwrap();
+        let target_tmp = tempdir().unwrap();
+        let target_dir = target_tmp.path().join("site");
+        let local = Local::new(&Config {
+            target_dir: target_dir.clone(),
+        })
+        .unwrap();
+        fs::create_dir_all(target_dir.join("old").join("assets")).unwrap();
+        fs::write(target_dir.join("old").join("assets").join("stale.js"), b"stale").unwrap();
+
+        // Synchronize files and check the target directory
+        let args = CommonArgs {
+            prune: true,
+            prune_retention_days: 0,
+            ..common_args(landscape_dir)
+        };
+        sync(&local, &args).await.unwrap();
+        assert_eq!(fs::read(target_dir.join("index.html")).unwrap(), b"index");
+        assert_eq!(
+            fs::read(target_dir.join("data").join("full.json")).unwrap(),
+            b"{}"
+        );
+
+        // Check the stale files have been pruned
+        assert!(!target_dir.join("old").exists());
+
+        // Check the objects listed match the files synchronized
+        let objects = local.list_objects().await.unwrap();
+        assert_eq!(objects.len(), 3);
+        assert_eq!(
+            objects.get("data/full.json"),
+            Some(&Object {
+                checksum: Some(md5sum(&landscape_dir.join("data").join("full.json")).unwrap()),
+                size: 2,
+            })
+        );
+        assert!(local.get_object(DEPLOY_STATE_OBJECT).await.unwrap().is_some());
+    }
+}
@@ -1,8 +1,51 @@
 //! This module defines the functionality of the deploy CLI subcommand.
+//!
+//! The landscape website files are synchronized with the destination using
+//! the same logic for all providers: only the files whose checksum differs
+//! from the one of the copy already deployed are uploaded, and the index
+//! document is uploaded last, once all the other files have been uploaded
+//! successfully. Providers only need to implement the [`Storage`] trait.
 
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result, bail, format_err};
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose::STANDARD as b64};
+use chrono::{Duration, Utc};
 use clap::Subcommand;
+use futures::stream::{self, StreamExt};
+use md5::{Digest, Md5};
+use mime_guess::mime;
+#[cfg(test)]
+use mockall::automock;
+use tracing::{debug, info, instrument};
+
+use self::plan::{DEPLOY_STATE_OBJECT, DeployState, Entry, Plan, Prune};
 
+pub mod azure;
+pub mod gcs;
+pub mod local;
+mod plan;
 pub mod s3;
+mod versions;
+
+/// File name of the index document.
+const INDEX_DOCUMENT: &str = "index.html";
+
+/// Number of objects to upload or delete concurrently.
+const OBJECTS_CONCURRENCY: usize = 50;
+
+/// Type alias to represent an object's checksum (hex encoded MD5 digest).
+pub(crate) type Checksum = String;
+
+/// Type alias to represent an object's key.
+pub(crate) type Key = String;
 
 /// Deploy command arguments.
 #[derive(clap::Args)]
@@ -16,6 +59,547 @@ pub struct DeployArgs {
 /// Provider used to deploy the landscape website.
 #[derive(Subcommand)]
 pub enum Provider {
-    /// Deploy landscape website to AWS S3.
+    /// Deploy landscape website to Azure Blob Storage.
+    Azure(azure::Args),
+
+    /// Deploy landscape website to Google Cloud Storage.
+    Gcs(gcs::Args),
+
+    /// Deploy landscape website to a local directory.
+    Local(local::Args),
+
+    /// Deploy landscape website to AWS S3 (or any S3 compatible service).
     S3(s3::Args),
+
+    /// Roll back to a previous version of the landscape website (versioned
+    /// deployments only).
+    Rollback(RollbackArgs),
+}
+
+/// Arguments shared by all providers.
+#[derive(clap::Args)]
+pub struct CommonArgs {
+    /// Location of the landscape website files (build subcommand output).
+    #[arg(long)]
+    pub landscape_dir: PathBuf,
+
+    /// Print the deploy plan (objects to upload, skip and delete) without
+    /// making any changes.
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// Delete the objects deployed that are not part of the build, once the
+    /// new index document is live.
+    #[arg(long, default_value_t = false)]
+    pub prune: bool,
+
+    /// Number of days objects that are not part of the build are kept before
+    /// being pruned, so that clients still running a previous version of the
+    /// website can load them.
+    #[arg(long, default_value_t = 7, requires = "prune")]
+    pub prune_retention_days: u32,
+
+    /// Upload the build under a new version prefix (versions/{version}),
+    /// switching the root index document to it once all the files have been
+    /// uploaded successfully (the other files are copied to the root before
+    /// the switch, so it is not atomic).
+    #[arg(long, default_value_t = false, conflicts_with = "prune")]
+    pub versioned: bool,
+
+    /// Identifier of the version deployed (defaults to the current UTC time,
+    /// i.e. 20240101120000).
+    #[arg(long, requires = "versioned")]
+    pub version_id: Option<String>,
+
+    /// Number of versions to keep (older ones are deleted).
+    #[arg(long, default_value_t = 5, requires = "versioned", value_parser = clap::value_parser!(u32).range(1..))]
+    pub keep_versions: u32,
+}
+
+/// Rollback command arguments.
+#[derive(clap::Args)]
+pub struct RollbackArgs {
+    /// Version to roll back to (the versions available are listed when it is
+    /// not provided).
+    #[arg(long)]
+    pub to: Option<String>,
+
+    /// Provider where the landscape website is deployed.
+    #[command(subcommand)]
+    pub provider: RollbackProvider,
+}
+
+/// Provider where the landscape website to roll back is deployed.
+#[derive(Subcommand)]
+pub enum RollbackProvider {
+    /// Azure Blob Storage.
+    Azure(azure::Config),
+
+    /// Google Cloud Storage.
+    Gcs(gcs::Config),
+
+    /// Local directory.
+    Local(local::Config),
+
+    /// AWS S3 (or any S3 compatible service).
+    S3(s3::Config),
+}
+
+/// Object deployed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Object {
+    pub checksum: Option<Checksum>,
+    pub size: u64,
+}
+
+/// Type alias to represent a Storage trait object.
+pub(crate) type DynStorage = Box<dyn Storage + Send + Sync>;
+
+/// Trait that defines the operations a deploy provider storage must support.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub(crate) trait Storage {
+    /// Delete an object.
+    async fn delete_object(&self, key: &str) -> Result<()>;
+
+    /// Get an object's content, returning None if it does not exist.
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Get the objects already deployed.
+    async fn list_objects(&self) -> Result<HashMap<Key, Object>>;
+
+    /// Upload an object, overwriting the existing one (if any).
+    async fn put_object(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()>;
+}
+
+/// Deploy landscape website using the provider selected.
+///
+/// # Errors
+/// Returns an error if the provider cannot be set up, uploads or deletions
+/// fail, or the source files cannot be read.
+#[instrument(skip_all, err)]
+pub async fn deploy(args: &DeployArgs) -> Result<()> {
+    // Setup provider storage (rollbacks are handled separately)
+    let (storage, common): (DynStorage, _) = match &args.provider {
+        Provider::Azure(args) => (Box::new(azure::Azure::new(&args.config)?), &args.common),
+        Provider::Gcs(args) => (Box::new(gcs::Gcs::new(&args.config)?), &args.common),
+        Provider::Local(args) => (Box::new(local::Local::new(&args.config)?), &args.common),
+        Provider::S3(args) => (Box::new(s3::S3::new(&args.config).await?), &args.common),
+        Provider::Rollback(args) => {
+            let storage: DynStorage = match &args.provider {
+                RollbackProvider::Azure(config) => Box::new(azure::Azure::new(config)?),
+                RollbackProvider::Gcs(config) => Box::new(gcs::Gcs::new(config)?),
+                RollbackProvider::Local(config) => Box::new(local::Local::new(config)?),
+                RollbackProvider::S3(config) => Box::new(s3::S3::new(config).await?),
+            };
+            return versions::rollback(storage.as_ref(), args.to.as_deref()).await;
+        }
+    };
+
+    info!("deploying landscape website..");
+    let start = Instant::now();
+
+    // Synchronize landscape website files
+    if common.versioned {
+        versions::deploy_version(storage.as_ref(), common).await?;
+    } else {
+        sync(storage.as_ref(), common).await?;
+    }
+
+    let duration = start.elapsed().as_secs_f64();
+    if common.dry_run {
+        info!("deploy plan ready, no changes made (took: {:.3}s)", duration);
+    } else {
+        info!("landscape website deployed! (took: {:.3}s)", duration);
+    }
+
+    Ok(())
+}
+
+/// Synchronize the landscape website files with the storage provided.
+#[instrument(skip_all, err)]
+async fn sync(storage: &(dyn Storage + Send + Sync), args: &CommonArgs) -> Result<()> {
+    // Get objects already deployed (and the deploy state when pruning)
+    let deployed_objects = storage.list_objects().await?;
+    let state = if args.prune {
+        get_deploy_state(storage).await?
+    } else {
+        DeployState::default()
+    };
+
+    // Prepare deploy plan
+    let prune = args.prune.then(|| Prune {
+        retention: Duration::days(i64::from(args.prune_retention_days)),
+        state: &state,
+    });
+    let plan = Plan::new(&args.landscape_dir, &deployed_objects, prune.as_ref(), Utc::now())?;
+    if args.dry_run {
+        println!("{}", plan.render());
+        return Ok(());
+    }
+
+    // Upload landscape website files (except index document)
+    let (index_document, objects): (Vec<_>, Vec<_>) =
+        plan.upload.iter().partition(|entry| entry.key == INDEX_DOCUMENT);
+    upload_objects(storage, &args.landscape_dir, &objects, "").await?;
+
+    // Upload index document if all the other files were uploaded successfully
+    if !index_document.is_empty() {
+        upload_index_document(storage, &args.landscape_dir).await?;
+    }
+
+    // Delete objects that are not part of the build anymore once the new
+    // index document is live, and keep track of the ones kept for now
+    if args.prune {
+        delete_objects(storage, &plan.delete).await?;
+        put_deploy_state(storage, &plan.state).await?;
+    }
+
+    Ok(())
+}
+
+/// Upload landscape website files to the storage, prepending the prefix
+/// provided to the objects keys.
+#[instrument(skip_all, err)]
+async fn upload_objects(
+    storage: &(dyn Storage + Send + Sync),
+    landscape_dir: &Path,
+    objects: &[&Entry],
+    prefix: &str,
+) -> Result<()> {
+    let results: Vec<Result<()>> = stream::iter(objects)
+        .map(|entry| async move {
+            // Prepare object's body and content type
+            let body = tokio::fs::read(object_path(landscape_dir, &entry.key)).await?;
+            let key = format!("{prefix}{}", entry.key);
+            let content_type = mime_guess::from_path(&key).first().unwrap_or(mime::APPLICATION_OCTET_STREAM);
+
+            // Upload file
+            storage
+                .put_object(&key, body, content_type.essence_str())
+                .await
+                .context(format_err!("error uploading file {key}"))?;
+
+            debug!(?key, "file uploaded");
+            Ok(())
+        })
+        .buffer_unordered(OBJECTS_CONCURRENCY)
+        .collect()
+        .await;
+
+    process_results(results)
+}
+
+/// Upload landscape website index document to the storage.
+#[instrument(skip_all, err)]
+async fn upload_index_document(storage: &(dyn Storage + Send + Sync), landscape_dir: &Path) -> Result<()> {
+    // Prepare object's body and content type
+    let body = tokio::fs::read(landscape_dir.join(INDEX_DOCUMENT)).await?;
+    let content_type = mime::TEXT_HTML.essence_str();
+
+    // Upload file
+    storage
+        .put_object(INDEX_DOCUMENT, body, content_type)
+        .await
+        .context("error uploading index document")?;
+
+    debug!("index document uploaded");
+    Ok(())
+}
+
+/// Delete the objects provided from the storage.
+#[instrument(skip_all, err)]
+async fn delete_objects(storage: &(dyn Storage + Send + Sync), objects: &[Entry]) -> Result<()> {
+    let results: Vec<Result<()>> = stream::iter(objects)
+        .map(|entry| async {
+            let key = &entry.key;
+            storage.delete_object(key).await.context(format_err!("error deleting object {key}"))?;
+
+            debug!(?key, "object deleted");
+            Ok(())
+        })
+        .buffer_unordered(OBJECTS_CONCURRENCY)
+        .collect()
+        .await;
+
+    process_results(results)
+}
+
+/// Get the deploy state from the storage (if available).
+async fn get_deploy_state(storage: &(dyn Storage + Send + Sync)) -> Result<DeployState> {
+    match storage.get_object(DEPLOY_STATE_OBJECT).await? {
+        Some(data) => serde_json::from_slice(&data).context("invalid deploy state"),
+        None => Ok(DeployState::default()),
+    }
+}
+
+/// Store the deploy state provided in the storage.
+async fn put_deploy_state(storage: &(dyn Storage + Send + Sync), state: &DeployState) -> Result<()> {
+    let body = serde_json::to_vec_pretty(state)?;
+    storage
+        .put_object(DEPLOY_STATE_OBJECT, body, mime::APPLICATION_JSON.essence_str())
+        .await
+        .context("error uploading deploy state")
+}
+
+/// Process the results of some concurrent operations, returning an error
+/// including all the errors found (if any).
+fn process_results(results: Vec<Result<()>>) -> Result<()> {
+    let mut errors_found = false;
+    let mut errors = String::new();
+    for result in results {
+        if let Err(err) = result {
+            errors_found = true;
+            let _ = writeln!(errors, "- {err:?}");
+        }
+    }
+    if errors_found {
+        bail!("{errors}");
+    }
+
+    Ok(())
+}
+
+/// Return the key of the object corresponding to the file provided, which is
+/// its path relative to the landscape directory using forward slashes.
+fn object_key(landscape_dir: &Path, file: &Path) -> Key {
+    let path = file.strip_prefix(landscape_dir).unwrap_or(file);
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Return the path of the file corresponding to the object key provided,
+/// relative to the directory given.
+fn object_path(dir: &Path, key: &str) -> PathBuf {
+    key.split('/').fold(dir.to_path_buf(), |path, part| path.join(part))
+}
+
+/// Calculate the MD5 digest of a file.
+pub(crate) fn md5sum(path: &Path) -> Result<Checksum> {
+    Ok(hex::encode(Md5::digest(fs::read(path)?)))
+}
+
+/// Convert the base64 encoded MD5 digest provided (as returned by some
+/// providers) to the hex encoded checksum format used to compare objects.
+pub(crate) fn base64_md5_to_checksum(md5: &str) -> Option<Checksum> {
+    b64.decode(md5.trim()).ok().filter(|digest| digest.len() == 16).map(hex::encode)
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::{always, eq};
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn md5sum_changes_with_file_contents() -> Result<()> {
+        // Setup a temporary file with known content
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let path = dir.join("file.txt");
+        fs::write(&path, b"hello world")?;
+
+        // Run the checksum calculation
+        let checksum = md5sum(&path)?;
+
+        // Check the digest matches the known value
+        assert_eq!(checksum, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+
+        // Change the file contents and calculate the checksum again
+        fs::write(&path, b"hello landscape2")?;
+        let changed_checksum = md5sum(&path)?;
+
+        // Check content changes produce a different digest
+        assert_ne!(checksum, changed_checksum);
+
+        Ok(())
+    }
+
+    #[test]
+    fn base64_md5_to_checksum_works() {
+        assert_eq!(
+            base64_md5_to_checksum("XrY7u+Ae7tCTyyK7j1rNww==").as_deref(),
+            Some("5eb63bbbe01eeed093cb22bb8f5acdc3")
+        );
+        assert_eq!(base64_md5_to_checksum("not base64!"), None);
+        assert_eq!(base64_md5_to_checksum(""), None);
+    }
+
+    #[test]
+    fn object_key_uses_forward_slashes() {
+        let landscape_dir = Path::new("/tmp/build");
+        assert_eq!(
+            object_key(landscape_dir, &landscape_dir.join("index.html")),
+            "index.html"
+        );
+        assert_eq!(
+            object_key(landscape_dir, &landscape_dir.join("data").join("full.json")),
+            "data/full.json"
+        );
+    }
+
+    #[tokio::test]
+    async fn sync_uploads_changed_files_and_index_document_last() {
+        // Setup landscape directory
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("data")).unwrap();
+        fs::write(dir.join("index.html"), b"index").unwrap();
+        fs::write(dir.join("data").join("full.json"), b"{}").unwrap();
+        fs::write(dir.join("unchanged.txt"), b"unchanged").unwrap();
+        fs::write(dir.join(".hidden"), b"hidden").unwrap();
+        let unchanged_checksum = md5sum(&dir.join("unchanged.txt")).unwrap();
+
+        // Setup storage mock
+        let mut seq = mockall::Sequence::new();
+        let mut storage = MockStorage::new();
+        storage.expect_list_objects().times(1).in_sequence(&mut seq).returning(move || {
+            Ok(HashMap::from([
+                ("index.html".to_string(), object(Some("outdated"))),
+                ("unchanged.txt".to_string(), object(Some(&unchanged_checksum))),
+                ("stale.js".to_string(), object(None)),
+            ]))
+        });
+        storage
+            .expect_put_object()
+            .with(eq("data/full.json"), eq(b"{}".to_vec()), eq("application/json"))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+        storage
+            .expect_put_object()
+            .with(eq("index.html"), always(), eq("text/html"))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+
+        // Synchronize files
+        sync(&storage, &common_args(dir)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn sync_does_not_upload_index_document_when_other_uploads_fail() {
+        // Setup landscape directory
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("index.html"), b"index").unwrap();
+        fs::write(dir.join("file.txt"), b"file").unwrap();
+
+        // Setup storage mock
+        let mut storage = MockStorage::new();
+        storage.expect_list_objects().times(1).returning(|| Ok(HashMap::new()));
+        storage
+            .expect_put_object()
+            .with(eq("file.txt"), always(), always())
+            .times(1)
+            .returning(|_, _, _| bail!("upload failed"));
+
+        // Synchronize files
+        assert!(sync(&storage, &common_args(dir)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn sync_dry_run_does_not_make_any_changes() {
+        // Setup landscape directory
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("index.html"), b"index").unwrap();
+
+        // Setup storage mock (no other calls expected)
+        let mut storage = MockStorage::new();
+        storage
+            .expect_list_objects()
+            .times(1)
+            .returning(|| Ok(HashMap::from([("stale.js".to_string(), object(None))])));
+        storage.expect_get_object().with(eq(DEPLOY_STATE_OBJECT)).times(1).returning(|_| Ok(None));
+
+        // Synchronize files
+        let args = CommonArgs {
+            dry_run: true,
+            prune: true,
+            prune_retention_days: 0,
+            ..common_args(dir)
+        };
+        sync(&storage, &args).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn sync_prunes_stale_objects_after_uploading_index_document() {
+        // Setup landscape directory
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("index.html"), b"index").unwrap();
+
+        // Setup storage mock
+        let mut seq = mockall::Sequence::new();
+        let mut storage = MockStorage::new();
+        storage.expect_list_objects().times(1).in_sequence(&mut seq).returning(|| {
+            Ok(HashMap::from([
+                ("expired.js".to_string(), object(None)),
+                ("recent.js".to_string(), object(None)),
+                (DEPLOY_STATE_OBJECT.to_string(), object(None)),
+            ]))
+        });
+        let state = DeployState {
+            stale_objects: [("expired.js".to_string(), Utc::now() - Duration::days(10))].into(),
+        };
+        storage
+            .expect_get_object()
+            .with(eq(DEPLOY_STATE_OBJECT))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_| Ok(Some(serde_json::to_vec(&state).unwrap())));
+        storage
+            .expect_put_object()
+            .with(eq("index.html"), always(), always())
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+        storage
+            .expect_delete_object()
+            .with(eq("expired.js"))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+        storage
+            .expect_put_object()
+            .withf(|key, body, _| {
+                let state: DeployState = serde_json::from_slice(body).unwrap();
+                key == DEPLOY_STATE_OBJECT && state.stale_objects.keys().eq(["recent.js"])
+            })
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+
+        // Synchronize files
+        let args = CommonArgs {
+            prune: true,
+            ..common_args(dir)
+        };
+        sync(&storage, &args).await.unwrap();
+    }
+
+    /// Return the default common arguments for the landscape directory
+    /// provided.
+    pub(crate) fn common_args(landscape_dir: &Path) -> CommonArgs {
+        CommonArgs {
+            landscape_dir: landscape_dir.to_path_buf(),
+            dry_run: false,
+            prune: false,
+            prune_retention_days: 7,
+            versioned: false,
+            version_id: None,
+            keep_versions: 5,
+        }
+    }
+
+    pub(crate) fn object(checksum: Option<&str>) -> Object {
+        Object {
+            checksum: checksum.map(ToString::to_string),
+            size: 1,
+        }
+    }
 }
@@ -0,0 +1,324 @@
+//! This module defines the deploy plan, which describes the changes needed to
+//! synchronize the objects deployed with the landscape website files.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    path::Path,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::{INDEX_DOCUMENT, Key, Object, md5sum, object_key};
+
+/// Key of the object used to keep track of the deploy state in the storage.
+pub(crate) const DEPLOY_STATE_OBJECT: &str = ".landscape2-deploy.json";
+
+/// Deploy state kept in the storage between deploys.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DeployState {
+    /// Objects deployed that are not part of the build, and the time they
+    /// were found not to be part of it for the first time.
+    pub stale_objects: BTreeMap<Key, DateTime<Utc>>,
+}
+
+/// Pruning options used when preparing the plan.
+#[derive(Debug, Clone)]
+pub(crate) struct Prune<'a> {
+    /// Objects that are not part of the build are kept for this duration
+    /// before being deleted.
+    pub retention: Duration,
+    /// Deploy state from the previous deploy.
+    pub state: &'a DeployState,
+}
+
+/// Deploy plan.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Plan {
+    /// Files to upload (the index document is always the last one).
+    pub upload: Vec<Entry>,
+    /// Files that are already up to date.
+    pub skip: Vec<Entry>,
+    /// Objects that are not part of the build to delete.
+    pub delete: Vec<Entry>,
+    /// Objects that are not part of the build to keep (for now).
+    pub keep: Vec<Entry>,
+    /// Deploy state to store once the plan has been applied.
+    pub state: DeployState,
+}
+
+/// Plan entry.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Entry {
+    pub key: Key,
+    pub size: u64,
+    /// Time the object will be deleted (only for objects kept while pruning).
+    pub delete_after: Option<DateTime<Utc>>,
+}
+
+impl Plan {
+    /// Prepare a new plan to synchronize the objects deployed with the files
+    /// in the landscape directory. Objects that are not part of the build are
+    /// only scheduled for deletion when pruning options are provided.
+    pub(crate) fn new(
+        landscape_dir: &Path,
+        deployed_objects: &HashMap<Key, Object>,
+        prune: Option<&Prune>,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        let mut plan = Plan::default();
+
+        // Compare files in the landscape directory with the objects deployed
+        let mut index_document = None;
+        let mut keys = HashSet::new();
+        for entry in WalkDir::new(landscape_dir).sort_by_file_name() {
+            // Check if the entry is a regular file
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            // Skip files that start with a dot
+            let file = entry.path();
+            let key = object_key(landscape_dir, file);
+            if key.starts_with('.') {
+                continue;
+            }
+            keys.insert(key.clone());
+
+            // Check if the object needs to be uploaded
+            let plan_entry = Entry {
+                size: entry.metadata()?.len(),
+                key,
+                delete_after: None,
+            };
+            let up_to_date = match deployed_objects.get(&plan_entry.key) {
+                Some(Object {
+                    checksum: Some(remote_checksum),
+                    ..
+                }) => md5sum(file)? == *remote_checksum,
+                _ => false,
+            };
+            if up_to_date {
+                plan.skip.push(plan_entry);
+            } else if plan_entry.key == INDEX_DOCUMENT {
+                // We'll upload the index document at the end when all the
+                // other files have been uploaded successfully
+                index_document = Some(plan_entry);
+            } else {
+                plan.upload.push(plan_entry);
+            }
+        }
+        plan.upload.extend(index_document);
+
+        // Objects deployed that are not part of the build
+        let mut stale_objects: Vec<(&Key, &Object)> = deployed_objects
+            .iter()
+            .filter(|(key, _)| !key.starts_with('.') && !keys.contains(*key))
+            .collect();
+        stale_objects.sort_by_key(|(key, _)| *key);
+        for (key, object) in stale_objects {
+            let mut plan_entry = Entry {
+                key: key.clone(),
+                size: object.size,
+                delete_after: None,
+            };
+            let Some(prune) = prune else {
+                plan.keep.push(plan_entry);
+                continue;
+            };
+            let stale_since = prune.state.stale_objects.get(key).copied().unwrap_or(now);
+            let delete_after = stale_since + prune.retention;
+            if delete_after <= now {
+                plan.delete.push(plan_entry);
+            } else {
+                plan_entry.delete_after = Some(delete_after);
+                plan.keep.push(plan_entry);
+                plan.state.stale_objects.insert(key.clone(), stale_since);
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Render the plan in text format.
+    pub(crate) fn render(&self) -> String {
+        let mut output = String::new();
+        let mut section = |title: &str, symbol: char, entries: &[Entry]| {
+            let size = entries.iter().map(|entry| entry.size).sum();
+            let objects = if entries.len() == 1 { "object" } else { "objects" };
+            let _ = writeln!(
+                output,
+                "{title}: {} {objects} ({})",
+                entries.len(),
+                format_size(size)
+            );
+            for entry in entries {
+                let _ = write!(output, "  {symbol} {} ({})", entry.key, format_size(entry.size));
+                if let Some(delete_after) = entry.delete_after {
+                    let _ = write!(
+                        output,
+                        " [deleted after {}]",
+                        delete_after.format("%Y-%m-%d %H:%M")
+                    );
+                }
+                output.push('\n');
+            }
+        };
+        section("Upload", '+', &self.upload);
+        section("Skip", '=', &self.skip);
+        section("Delete", '-', &self.delete);
+        section("Keep (not part of the build)", '~', &self.keep);
+        output.trim_end().to_string()
+    }
+}
+
+/// Format the size provided (in bytes) in a human readable way.
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn object(checksum: Option<&str>, size: u64) -> Object {
+        Object {
+            checksum: checksum.map(ToString::to_string),
+            size,
+        }
+    }
+
+    fn entry(key: &str, size: u64) -> Entry {
+        Entry {
+            key: key.to_string(),
+            size,
+            delete_after: None,
+        }
+    }
+
+    #[test]
+    fn plan_uploads_changed_files_and_index_document_last() {
+        // Setup landscape directory
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("data")).unwrap();
+        fs::write(dir.join("index.html"), b"index").unwrap();
+        fs::write(dir.join("data").join("full.json"), b"{}").unwrap();
+        fs::write(dir.join("unchanged.txt"), b"unchanged").unwrap();
+        fs::write(dir.join(".hidden"), b"hidden").unwrap();
+        let unchanged_checksum = md5sum(&dir.join("unchanged.txt")).unwrap();
+
+        // Prepare plan
+        let deployed_objects = HashMap::from([
+            ("index.html".to_string(), object(Some("outdated"), 3)),
+            ("unchanged.txt".to_string(), object(Some(&unchanged_checksum), 9)),
+            ("stale.js".to_string(), object(None, 100)),
+            (DEPLOY_STATE_OBJECT.to_string(), object(None, 10)),
+        ]);
+        let plan = Plan::new(dir, &deployed_objects, None, Utc::now()).unwrap();
+
+        assert_eq!(
+            plan,
+            Plan {
+                upload: vec![entry("data/full.json", 2), entry("index.html", 5)],
+                skip: vec![entry("unchanged.txt", 9)],
+                delete: vec![],
+                keep: vec![entry("stale.js", 100)],
+                state: DeployState::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn plan_deletes_stale_objects_once_retention_window_expires() {
+        // Setup landscape directory
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("index.html"), b"index").unwrap();
+
+        // Prepare plan
+        let now = Utc::now();
+        let deployed_objects = HashMap::from([
+            ("index.html".to_string(), object(None, 5)),
+            ("expired.js".to_string(), object(None, 1)),
+            ("recent.js".to_string(), object(None, 2)),
+            ("new.js".to_string(), object(None, 3)),
+        ]);
+        let state = DeployState {
+            stale_objects: BTreeMap::from([
+                ("expired.js".to_string(), now - Duration::days(8)),
+                ("recent.js".to_string(), now - Duration::days(1)),
+                ("removed.js".to_string(), now - Duration::days(1)),
+            ]),
+        };
+        let prune = Prune {
+            retention: Duration::days(7),
+            state: &state,
+        };
+        let plan = Plan::new(dir, &deployed_objects, Some(&prune), now).unwrap();
+
+        assert_eq!(plan.upload, vec![entry("index.html", 5)]);
+        assert_eq!(plan.delete, vec![entry("expired.js", 1)]);
+        assert_eq!(
+            plan.keep,
+            vec![
+                Entry {
+                    delete_after: Some(now + Duration::days(7)),
+                    ..entry("new.js", 3)
+                },
+                Entry {
+                    delete_after: Some(now + Duration::days(6)),
+                    ..entry("recent.js", 2)
+                },
+            ]
+        );
+        assert_eq!(
+            plan.state.stale_objects,
+            BTreeMap::from([
+                ("new.js".to_string(), now),
+                ("recent.js".to_string(), now - Duration::days(1)),
+            ])
+        );
+    }
+
+    #[test]
+    fn plan_render_works() {
+        let plan = Plan {
+            upload: vec![entry("index.html", 2048)],
+            delete: vec![entry("old.js", 10), entry("older.js", 20)],
+            ..Default::default()
+        };
+        assert_eq!(
+            plan.render(),
+            "Upload: 1 object (2.0 KiB)\n  + index.html (2.0 KiB)\nSkip: 0 objects (0 B)\nDelete: 2 objects (30 B)\n  - old.js (10 B)\n  - older.js (20 B)\nKeep (not part of the build): 0 objects (0 B)"
+        );
+    }
+
+    #[test]
+    fn format_size_works() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
@@ -1,302 +1,183 @@
 //! This module defines the functionality of the deploy CLI subcommand for the
-//! AWS S3 provider.
+//! AWS S3 provider. Any S3 compatible service (i.e. MinIO) can be used as
+//! well by providing its endpoint url.
 
-use std::{
-    collections::HashMap,
-    env,
-    fmt::Write,
-    fs,
-    path::{Path, PathBuf},
-    time::Instant,
-};
+use std::{collections::HashMap, env};
 
-use anyhow::{Context, Result, bail, format_err};
-use aws_sdk_s3::primitives::ByteStream;
-use futures::stream::{self, StreamExt};
-use md5::{Digest, Md5};
-use mime_guess::mime;
-use tracing::{debug, info, instrument};
-use walkdir::WalkDir;
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use aws_sdk_s3::{operation::get_object::GetObjectError, primitives::ByteStream};
+use tracing::instrument;
 
-/// File name of the index document.
-const INDEX_DOCUMENT: &str = "index.html";
-
-/// Number of files to upload concurrently.
-const UPLOAD_FILES_CONCURRENCY: usize = 50;
-
-/// Type alias to represent an object's checksum.
-type Checksum = String;
-
-/// Type alias to represent an object's key.
-type Key = String;
+use super::{CommonArgs, Key, Object, Storage};
 
 /// AWS S3 provider arguments.
 #[derive(clap::Args)]
 pub struct Args {
+    #[command(flatten)]
+    pub config: Config,
+
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+/// AWS S3 provider configuration.
+#[derive(clap::Args)]
+pub struct Config {
     /// Bucket to copy the landscape website files to.
     #[arg(long)]
     pub bucket: String,
 
-    /// Location of the landscape website files (build subcommand output).
+    /// Endpoint url of the S3 compatible service to use instead of AWS S3
+    /// (i.e. http://localhost:9000).
     #[arg(long)]
-    pub landscape_dir: PathBuf,
+    pub endpoint_url: Option<String>,
+
+    /// Use path-style addressing (i.e. {endpoint}/{bucket}/{key}) instead of
+    /// virtual hosted-style. Enabled by default when an endpoint url is
+    /// provided, as it's what most S3 compatible services expect (it can be
+    /// disabled using --force-path-style=false).
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub force_path_style: Option<bool>,
 }
 
-/// Deploy landscape website to AWS S3.
-///
-/// # Errors
-/// Returns an error if required environment variables are missing, uploads fail,
-/// or the source files cannot be read.
-#[instrument(skip_all, err)]
-pub async fn deploy(args: &Args) -> Result<()> {
-    info!("deploying landscape website..");
-    let start = Instant::now();
-
-    // Check required environment variables
-    check_env_vars()?;
-
-    // Setup AWS S3 client
-    let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
-    let s3_client = aws_sdk_s3::Client::new(&config);
-
-    // Get objects already deployed
-    let deployed_objects = get_deployed_objects(&s3_client, &args.bucket).await?;
-
-    // Upload landscape website files (except index document)
-    upload_objects(&s3_client, &args.bucket, &args.landscape_dir, &deployed_objects).await?;
-
-    // Upload index document if all the other files were uploaded successfully
-    let index_remote_checksum = deployed_objects.get(INDEX_DOCUMENT).and_then(|checksum| checksum.as_ref());
-    upload_index_document(
-        &s3_client,
-        &args.bucket,
-        &args.landscape_dir,
-        index_remote_checksum,
-    )
-    .await?;
-
-    let duration = start.elapsed().as_secs_f64();
-    info!("landscape website deployed! (took: {:.3}s)", duration);
-
-    Ok(())
+impl Config {
+    /// Check if path-style addressing should be used.
+    fn path_style(&self) -> bool {
+        self.force_path_style.unwrap_or(self.endpoint_url.is_some())
+    }
 }
 
-/// Check that the required environment variables have been provided.
-#[instrument(err)]
-fn check_env_vars() -> Result<()> {
-    let required_env_vars = ["AWS_REGION", "AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY"];
+/// Storage implementation backed by AWS S3 (or an S3 compatible service).
+pub(crate) struct S3 {
+    bucket: String,
+    client: aws_sdk_s3::Client,
+}
 
-    for var in required_env_vars {
-        let result = env::var(var);
-        if result.is_err() || result.expect("var to be set").is_empty() {
-            bail!("required environment variable {var} not provided");
+impl S3 {
+    /// Create a new S3 instance.
+    pub(crate) async fn new(config: &Config) -> Result<Self> {
+        // Check required environment variables
+        check_env_vars()?;
+
+        // Setup AWS S3 client
+        let sdk_config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        let mut s3_config = aws_sdk_s3::config::Builder::from(&sdk_config);
+        if let Some(endpoint_url) = &config.endpoint_url {
+            s3_config = s3_config.endpoint_url(endpoint_url);
         }
-    }
+        s3_config = s3_config.force_path_style(config.path_style());
+        let client = aws_sdk_s3::Client::from_conf(s3_config.build());
 
-    Ok(())
+        Ok(Self {
+            bucket: config.bucket.clone(),
+            client,
+        })
+    }
 }
 
-/// Get deployed objects returning their key and checksum.
-#[instrument(skip_all, err)]
-async fn get_deployed_objects(
-    s3_client: &aws_sdk_s3::Client,
-    bucket: &str,
-) -> Result<HashMap<Key, Option<Checksum>>> {
-    let mut deployed_objects = HashMap::new();
+#[async_trait]
+impl Storage for S3 {
+    /// [Storage::delete_object]
+    #[instrument(skip(self), err)]
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        self.client.delete_object().bucket(&self.bucket).key(key).send().await?;
+        Ok(())
+    }
 
-    let mut continuation_token = None;
-    loop {
-        let mut request = s3_client.list_objects_v2().bucket(bucket);
-        if let Some(token) = continuation_token {
-            request = request.continuation_token(token);
-        }
-        let output = request.send().await?;
-        if let Some(objects) = output.contents {
-            for object in objects {
-                let Some(key) = object.key else { continue };
-                let checksum = object.e_tag.map(|etag| etag.trim_matches('"').to_string());
-                deployed_objects.insert(key, checksum);
-            }
-        }
-        if !output.is_truncated.unwrap_or(false) {
-            break;
+    /// [Storage::get_object]
+    #[instrument(skip(self), err)]
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self.client.get_object().bucket(&self.bucket).key(key).send().await {
+            Ok(output) => Ok(Some(output.body.collect().await?.to_vec())),
+            Err(err) if err.as_service_error().is_some_and(GetObjectError::is_no_such_key) => Ok(None),
+            Err(err) => Err(err.into()),
         }
-        continuation_token = output.next_continuation_token;
     }
 
-    Ok(deployed_objects)
-}
-
-/// Upload landscape website files to S3 bucket.
-#[instrument(skip_all, err)]
-async fn upload_objects(
-    s3_client: &aws_sdk_s3::Client,
-    bucket: &str,
-    landscape_dir: &PathBuf,
-    deployed_objects: &HashMap<Key, Option<Checksum>>,
-) -> Result<()> {
-    // Upload files in the landscape directory to the bucket provided
-    let results: Vec<Result<()>> = stream::iter(WalkDir::new(landscape_dir))
-        .map(|entry| async {
-            // Check if the entry is a regular file
-            let entry = entry?;
-            if !entry.file_type().is_file() {
-                return Ok(());
-            }
-
-            // Prepare object key
-            let file = entry.path();
-            let key = file
-                .display()
-                .to_string()
-                .trim_start_matches(landscape_dir.display().to_string().as_str())
-                .trim_start_matches('/')
-                .to_string();
+    /// [Storage::list_objects]
+    #[instrument(skip_all, err)]
+    async fn list_objects(&self) -> Result<HashMap<Key, Object>> {
+        let mut objects = HashMap::new();
 
-            // We'll upload the index document at the end when all the other
-            // files have been uploaded successfully
-            if key == INDEX_DOCUMENT {
-                return Ok(());
+        let mut continuation_token = None;
+        loop {
+            let mut request = self.client.list_objects_v2().bucket(&self.bucket);
+            if let Some(token) = continuation_token {
+                request = request.continuation_token(token);
             }
-
-            // Skip files that start with a dot
-            if key.starts_with('.') {
-                return Ok(());
-            }
-
-            // Skip objects that don't need to be uploaded again
-            if deployed_objects.contains_key(&key) {
-                // Skip any other objects that haven't changed
-                let checksum = md5sum(file)?;
-                if let Some(remote_checksum) = deployed_objects.get(&key).expect("object to be present")
-                    && checksum == *remote_checksum
-                {
-                    return Ok(());
+            let output = request.send().await?;
+            if let Some(contents) = output.contents {
+                for object in contents {
+                    let Some(key) = object.key else { continue };
+                    let checksum = object.e_tag.map(|etag| etag.trim_matches('"').to_string());
+                    let size = object.size.and_then(|size| u64::try_from(size).ok()).unwrap_or_default();
+                    objects.insert(key, Object { checksum, size });
                 }
             }
-
-            // Prepare object's body and content type
-            let body = ByteStream::from_path(file).await?;
-            let content_type = mime_guess::from_path(&key).first().unwrap_or(mime::APPLICATION_OCTET_STREAM);
-
-            // Upload file
-            s3_client
-                .put_object()
-                .bucket(bucket)
-                .key(&key)
-                .body(body)
-                .content_type(content_type.essence_str())
-                .send()
-                .await
-                .context(format_err!("error uploading file {key}"))?;
-
-            debug!(?key, "file uploaded");
-            Ok(())
-        })
-        .buffer_unordered(UPLOAD_FILES_CONCURRENCY)
-        .collect()
-        .await;
-
-    // Process results
-    let mut errors_found = false;
-    let mut errors = String::new();
-    for result in results {
-        if let Err(err) = result {
-            errors_found = true;
-            let _ = writeln!(errors, "- {err:?}");
+            if !output.is_truncated.unwrap_or(false) {
+                break;
+            }
+            continuation_token = output.next_continuation_token;
         }
-    }
-    if errors_found {
-        bail!("{errors}");
-    }
-
-    Ok(())
-}
-
-/// Upload landscape website index document to S3 bucket.
-#[instrument(skip_all, err)]
-async fn upload_index_document(
-    s3_client: &aws_sdk_s3::Client,
-    bucket: &str,
-    landscape_dir: &Path,
-    remote_checksum: Option<&Checksum>,
-) -> Result<()> {
-    // Prepare object's checksum, key, body and content type
-    let file = landscape_dir.join(INDEX_DOCUMENT);
-    let checksum = md5sum(&file)?;
-    let key = INDEX_DOCUMENT.to_string();
-    let body = ByteStream::from_path(&file).await?;
-    let content_type = mime::TEXT_HTML.essence_str();
 
-    // Check if the remote copy is up to date
-    if let Some(remote_checksum) = remote_checksum
-        && checksum == *remote_checksum
-    {
-        return Ok(());
+        Ok(objects)
     }
 
-    // Upload file
-    s3_client
-        .put_object()
-        .bucket(bucket)
-        .key(key)
-        .body(body)
-        .content_type(content_type)
-        .send()
-        .await
-        .context("error uploading index document")?;
-
-    debug!("index document uploaded");
-    Ok(())
+    /// [Storage::put_object]
+    #[instrument(skip(self, body), err)]
+    async fn put_object(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(ByteStream::from(body))
+            .content_type(content_type)
+            .send()
+            .await?;
+        Ok(())
+    }
 }
 
-/// Calculate the MD5 digest of a file.
-fn md5sum(path: &Path) -> Result<String> {
-    let mut hasher = Md5::new();
-    hasher.update(fs::read(path)?);
-    let result = hasher.finalize();
+/// Check that the required environment variables have been provided.
+#[instrument(err)]
+fn check_env_vars() -> Result<()> {
+    let required_env_vars = ["AWS_REGION", "AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY"];
 
-    let mut checksum = String::with_capacity(result.len() * 2);
-    for byte in result {
-        write!(&mut checksum, "{byte:02x}")?;
+    for var in required_env_vars {
+        let result = env::var(var);
+        if result.is_err() || result.expect("var to be set").is_empty() {
+            bail!("required environment variable {var} not provided");
+        }
     }
 
-    Ok(checksum)
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        env,
-        time::{SystemTime, UNIX_EPOCH},
-    };
+    use clap::Parser;
 
     use super::*;
 
-    #[test]
-    fn md5sum_changes_with_file_contents() -> Result<()> {
-        // Setup a temporary file with known content
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system time to be after unix epoch")
-            .as_nanos();
-        let path = env::temp_dir().join(format!("landscape2-md5sum-{timestamp}.txt"));
-        std::fs::write(&path, b"hello world")?;
-
-        // Run the checksum calculation
-        let checksum = md5sum(&path)?;
-
-        // Check the digest matches the known value
-        assert_eq!(checksum, "5eb63bbbe01eeed093cb22bb8f5acdc3");
-
-        // Change the file contents and calculate the checksum again
-        std::fs::write(&path, b"hello landscape2")?;
-        let changed_checksum = md5sum(&path)?;
-        std::fs::remove_file(&path)?;
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        config: Config,
+    }
 
-        // Check content changes produce a different digest
-        assert_ne!(checksum, changed_checksum);
+    fn config(args: &[&str]) -> Config {
+        Cli::parse_from(["s3", "--bucket", "bucket"].iter().chain(args)).config
+    }
 
-        Ok(())
+    #[test]
+    fn config_path_style_succeeds() {
+        let endpoint = ["--endpoint-url", "http://localhost:9000"];
+
+        assert!(!config(&[]).path_style());
+        assert!(config(&["--force-path-style"]).path_style());
+        assert!(config(&endpoint).path_style());
+        assert!(config(&[&endpoint[..], &["--force-path-style"]].concat()).path_style());
+        assert!(!config(&[&endpoint[..], &["--force-path-style=false"]].concat()).path_style());
     }
 }
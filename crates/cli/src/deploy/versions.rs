@@ -0,0 +1,578 @@
+//! This module defines the functionality used to deploy versioned builds of
+//! the landscape website and to roll back to a previous version.
+//!
+//! Each build is uploaded under its own prefix (versions/{version}/), and the
+//! root index document is replaced by a pointer to it (the version's index
+//! document with a base element, so that its assets are loaded from the
+//! version prefix).
+//!
+//! The other entry points of the landscape website (data files, API, embeds,
+//! items pages, feeds, etc) are expected to be available at the root as well,
+//! so the files of the version that differ from the ones published at the
+//! root are copied there before switching the index document, and root copies
+//! left by other versions are deleted afterwards. This means that switching
+//! versions is not atomic: while the root copies are being published, the
+//! root entry points may serve a mix of files from both versions. Rolling
+//! back follows the same steps, copying again to the root the files of the
+//! version that differ from the ones currently published.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    fs,
+};
+
+use anyhow::{Context, Result, bail, format_err};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use mime_guess::mime;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument};
+
+use super::{
+    CommonArgs, INDEX_DOCUMENT, Key, OBJECTS_CONCURRENCY, Object, Storage, delete_objects, plan::Entry,
+    plan::Plan, process_results, upload_objects,
+};
+
+/// Key of the object used to keep track of the versions deployed.
+pub(crate) const VERSIONS_OBJECT: &str = ".landscape2-versions.json";
+
+/// Prefix of the keys of the versions deployed.
+const VERSIONS_PREFIX: &str = "versions/";
+
+/// Path of the base dataset, relative to the landscape directory.
+const BASE_DATASET: &str = "data/base.json";
+
+/// Versions deployed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Versions {
+    /// Version the index document currently points to.
+    pub current: Option<String>,
+    /// Versions available, sorted by deploy time (oldest first).
+    pub versions: Vec<Version>,
+}
+
+/// Version deployed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Version {
+    pub id: String,
+    pub deployed_at: DateTime<Utc>,
+}
+
+impl Versions {
+    /// Add the version provided, making it the current one.
+    fn add(&mut self, id: &str, deployed_at: DateTime<Utc>) {
+        self.versions.retain(|version| version.id != id);
+        self.versions.push(Version {
+            id: id.to_string(),
+            deployed_at,
+        });
+        self.current = Some(id.to_string());
+    }
+
+    /// Remove the oldest versions so that only the number of versions
+    /// provided are kept (the current version is never removed). The
+    /// versions removed are returned.
+    fn trim(&mut self, keep: usize) -> Vec<Version> {
+        let mut removed = vec![];
+        while self.versions.len() > keep {
+            let Some(pos) =
+                self.versions.iter().position(|version| Some(&version.id) != self.current.as_ref())
+            else {
+                break;
+            };
+            removed.push(self.versions.remove(pos));
+        }
+        removed
+    }
+}
+
+/// Deploy the landscape website files as a new version. The root copies of
+/// the files are published once all the files of the version have been
+/// uploaded successfully, and the index document is switched last.
+#[instrument(skip_all, err)]
+pub(crate) async fn deploy_version(storage: &(dyn Storage + Send + Sync), args: &CommonArgs) -> Result<()> {
+    // Prepare version identifier
+    let now = Utc::now();
+    let version_id = match &args.version_id {
+        Some(version_id) => version_id.clone(),
+        None => now.format("%Y%m%d%H%M%S").to_string(),
+    };
+    validate_version_id(&version_id)?;
+    let prefix = version_prefix(&version_id);
+
+    // Prepare deploy plan (files of this version already uploaded, from a
+    // previous attempt for example, are not uploaded again)
+    let deployed_objects = storage.list_objects().await?;
+    let version_objects: HashMap<Key, Object> = deployed_objects
+        .iter()
+        .filter_map(|(key, object)| Some((key.strip_prefix(&prefix)?.to_string(), object.clone())))
+        .collect();
+    let plan = Plan::new(&args.landscape_dir, &version_objects, None, now)?;
+
+    // Prepare root copies of the files of the new version (only the ones
+    // that differ from the copy already published at the root are uploaded)
+    let root_objects: HashMap<Key, Object> = deployed_objects
+        .iter()
+        .filter(|(key, _)| !key.starts_with(VERSIONS_PREFIX))
+        .map(|(key, object)| (key.clone(), object.clone()))
+        .collect();
+    let root_plan = Plan::new(&args.landscape_dir, &root_objects, None, now)?;
+    let root_copies: Vec<&Entry> =
+        root_plan.upload.iter().filter(|entry| entry.key != INDEX_DOCUMENT).collect();
+    let version_keys: HashSet<&str> =
+        root_plan.upload.iter().chain(&root_plan.skip).map(|entry| entry.key.as_str()).collect();
+    let stale_root_copies = get_stale_root_copies(&deployed_objects, &version_keys);
+
+    // Prepare the index document pointing to the new version
+    let index_document = fs::read_to_string(args.landscape_dir.join(INDEX_DOCUMENT))
+        .context("error reading index document")?;
+    let base_path = match fs::read(args.landscape_dir.join(BASE_DATASET)) {
+        Ok(data) => get_base_path(&data)?,
+        Err(_) => None,
+    };
+    let pointer = prepare_pointer(&index_document, base_path.as_deref(), &version_id)?;
+
+    // Update versions, removing the oldest ones
+    let mut versions = get_versions(storage).await?;
+    versions.add(&version_id, now);
+    let removed_versions = versions.trim(args.keep_versions as usize);
+    let mut objects_to_delete: Vec<Entry> = vec![];
+    for version in &removed_versions {
+        let prefix = version_prefix(&version.id);
+        objects_to_delete.extend(
+            deployed_objects
+                .iter()
+                .filter(|(key, _)| key.starts_with(&prefix))
+                .map(|(key, object)| Entry {
+                    key: key.clone(),
+                    size: object.size,
+                    delete_after: None,
+                }),
+        );
+    }
+    objects_to_delete.sort_by(|a, b| a.key.cmp(&b.key));
+
+    if args.dry_run {
+        let mut output = format!("Version: {version_id} ({prefix})\n{}\n", plan.render());
+        let _ = writeln!(
+            output,
+            "Root copies: {} to upload, {} to delete",
+            root_copies.len(),
+            stale_root_copies.len()
+        );
+        let _ = writeln!(output, "Index document: switched to version {version_id}");
+        let removed: Vec<&str> = removed_versions.iter().map(|version| version.id.as_str()).collect();
+        let removed = if removed.is_empty() {
+            "none".to_string()
+        } else {
+            removed.join(", ")
+        };
+        let _ = write!(
+            output,
+            "Versions to delete: {removed} ({} objects)",
+            objects_to_delete.len()
+        );
+        println!("{output}");
+        return Ok(());
+    }
+
+    // Upload landscape website files under the version prefix
+    let objects: Vec<&Entry> = plan.upload.iter().collect();
+    upload_objects(storage, &args.landscape_dir, &objects, &prefix).await?;
+
+    // Publish the root copies of the new version's files
+    upload_objects(storage, &args.landscape_dir, &root_copies, "").await?;
+
+    // Switch the index document to the new version once all the files have
+    // been uploaded successfully
+    put_index_document(storage, pointer).await?;
+    put_versions(storage, &versions).await?;
+    info!(version = version_id, "index document switched to new version");
+
+    // Delete the versions and root copies that are not needed anymore
+    delete_objects(storage, &objects_to_delete).await?;
+    delete_objects(storage, &stale_root_copies).await?;
+
+    Ok(())
+}
+
+/// Roll back to the version provided. When no version is provided, the
+/// versions available are listed.
+#[instrument(skip(storage), err)]
+pub(crate) async fn rollback(storage: &(dyn Storage + Send + Sync), to: Option<&str>) -> Result<()> {
+    let mut versions = get_versions(storage).await?;
+    if versions.versions.is_empty() {
+        bail!("no versioned deployments found");
+    }
+
+    // List versions available when no version has been provided
+    let Some(version_id) = to else {
+        println!("{}", render_versions(&versions));
+        return Ok(());
+    };
+    if !versions.versions.iter().any(|version| version.id == version_id) {
+        bail!("version {version_id} not found");
+    }
+
+    // Prepare the index document pointing to the version provided
+    let prefix = version_prefix(version_id);
+    let Some(index_document) = storage.get_object(&format!("{prefix}{INDEX_DOCUMENT}")).await? else {
+        bail!("index document of version {version_id} not found");
+    };
+    let index_document = String::from_utf8(index_document).context("invalid index document")?;
+    let base_path = match storage.get_object(&format!("{prefix}{BASE_DATASET}")).await? {
+        Some(data) => get_base_path(&data)?,
+        None => None,
+    };
+    let pointer = prepare_pointer(&index_document, base_path.as_deref(), version_id)?;
+
+    // Publish the root copies of the version's files that differ from the
+    // ones currently published
+    let deployed_objects = storage.list_objects().await?;
+    let version_objects: HashMap<&str, &Object> = deployed_objects
+        .iter()
+        .filter_map(|(key, object)| Some((key.strip_prefix(&prefix)?, object)))
+        .collect();
+    let mut root_copies: Vec<&str> = version_objects
+        .iter()
+        .filter(|(key, object)| {
+            **key != INDEX_DOCUMENT
+                && (object.checksum.is_none()
+                    || deployed_objects.get(**key).is_none_or(|root| root.checksum != object.checksum))
+        })
+        .map(|(key, _)| *key)
+        .collect();
+    root_copies.sort_unstable();
+    copy_objects(storage, &prefix, &root_copies).await?;
+
+    // Switch the index document to the version provided
+    put_index_document(storage, pointer).await?;
+
+    // Update current version
+    versions.current = Some(version_id.to_string());
+    put_versions(storage, &versions).await?;
+
+    // Delete the root copies of other versions' files
+    let version_keys: HashSet<&str> = version_objects.into_keys().collect();
+    delete_objects(storage, &get_stale_root_copies(&deployed_objects, &version_keys)).await?;
+
+    info!("rolled back to version {version_id}");
+    Ok(())
+}
+
+/// Check that the version identifier provided is valid.
+fn validate_version_id(version_id: &str) -> Result<()> {
+    if version_id.is_empty()
+        || version_id.starts_with('.')
+        || !version_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        bail!(
+            "invalid version id {version_id:?} (only letters, digits, '.', '_' and '-' are allowed, and it cannot start with '.')"
+        );
+    }
+    Ok(())
+}
+
+/// Return the prefix of the keys of the version provided.
+fn version_prefix(version_id: &str) -> String {
+    format!("{VERSIONS_PREFIX}{version_id}/")
+}
+
+/// Get the base path from the base dataset provided (if set).
+fn get_base_path(base_dataset: &[u8]) -> Result<Option<String>> {
+    let base_dataset: serde_json::Value =
+        serde_json::from_slice(base_dataset).context("invalid base dataset")?;
+    Ok(base_dataset["base_path"].as_str().map(ToString::to_string))
+}
+
+/// Prepare the index document used as pointer to the version provided. It's
+/// the version's index document with a base element, so that all relative
+/// urls (assets, data files, etc) are resolved from the version prefix.
+fn prepare_pointer(index_document: &str, base_path: Option<&str>, version_id: &str) -> Result<String> {
+    let head_start = index_document.find("<head").ok_or_else(|| format_err!("head element not found"))?;
+    let head_end = index_document[head_start..]
+        .find('>')
+        .map(|pos| head_start + pos + 1)
+        .ok_or_else(|| format_err!("head element not found"))?;
+    let base_path = base_path.unwrap_or_default().trim_end_matches('/');
+
+    Ok(format!(
+        "{}\n<base href=\"{base_path}/{}\" />{}",
+        &index_document[..head_end],
+        version_prefix(version_id),
+        &index_document[head_end..]
+    ))
+}
+
+/// Return the objects published at the root that are copies of files of
+/// other versions not present in the version provided (identified by its
+/// keys). Other objects at the root are never returned, so files not managed
+/// by the versioned deploys are kept.
+fn get_stale_root_copies(
+    deployed_objects: &HashMap<Key, Object>,
+    version_keys: &HashSet<&str>,
+) -> Vec<Entry> {
+    let managed_keys: HashSet<&str> = deployed_objects
+        .keys()
+        .filter_map(|key| key.strip_prefix(VERSIONS_PREFIX)?.split_once('/').map(|(_, key)| key))
+        .collect();
+    let mut stale_root_copies: Vec<Entry> = deployed_objects
+        .iter()
+        .filter(|(key, _)| {
+            !key.starts_with('.')
+                && !key.starts_with(VERSIONS_PREFIX)
+                && *key != INDEX_DOCUMENT
+                && !version_keys.contains(key.as_str())
+                && managed_keys.contains(key.as_str())
+        })
+        .map(|(key, object)| Entry {
+            key: key.clone(),
+            size: object.size,
+            delete_after: None,
+        })
+        .collect();
+    stale_root_copies.sort_by(|a, b| a.key.cmp(&b.key));
+    stale_root_copies
+}
+
+/// Copy the objects provided from the prefix provided to the root.
+#[instrument(skip_all, err)]
+async fn copy_objects(storage: &(dyn Storage + Send + Sync), prefix: &str, keys: &[&str]) -> Result<()> {
+    let results: Vec<Result<()>> = stream::iter(keys)
+        .map(|key| async move {
+            // Get object's body and prepare its content type
+            let src_key = format!("{prefix}{key}");
+            let Some(body) = storage.get_object(&src_key).await? else {
+                bail!("object {src_key} not found");
+            };
+            let content_type = mime_guess::from_path(key).first().unwrap_or(mime::APPLICATION_OCTET_STREAM);
+
+            // Copy object
+            storage
+                .put_object(key, body, content_type.essence_str())
+                .await
+                .context(format_err!("error copying object {src_key}"))?;
+
+            debug!(?key, "object copied");
+            Ok(())
+        })
+        .buffer_unordered(OBJECTS_CONCURRENCY)
+        .collect()
+        .await;
+
+    process_results(results)
+}
+
+/// Render the versions provided in text format.
+fn render_versions(versions: &Versions) -> String {
+    let mut output = String::new();
+    for version in versions.versions.iter().rev() {
+        let current = versions.current.as_ref() == Some(&version.id);
+        let _ = writeln!(
+            output,
+            "{} {} (deployed at {})",
+            if current { '*' } else { ' ' },
+            version.id,
+            version.deployed_at.format("%Y-%m-%d %H:%M:%S"),
+        );
+    }
+    output.trim_end().to_string()
+}
+
+/// Store the index document provided in the storage.
+async fn put_index_document(storage: &(dyn Storage + Send + Sync), index_document: String) -> Result<()> {
+    storage
+        .put_object(
+            INDEX_DOCUMENT,
+            index_document.into_bytes(),
+            mime::TEXT_HTML.essence_str(),
+        )
+        .await
+        .context("error uploading index document")
+}
+
+/// Get the versions deployed from the storage (if available).
+async fn get_versions(storage: &(dyn Storage + Send + Sync)) -> Result<Versions> {
+    match storage.get_object(VERSIONS_OBJECT).await? {
+        Some(data) => serde_json::from_slice(&data).context("invalid versions object"),
+        None => Ok(Versions::default()),
+    }
+}
+
+/// Store the versions provided in the storage.
+async fn put_versions(storage: &(dyn Storage + Send + Sync), versions: &Versions) -> Result<()> {
+    let body = serde_json::to_vec_pretty(versions)?;
+    storage
+        .put_object(VERSIONS_OBJECT, body, mime::APPLICATION_JSON.essence_str())
+        .await
+        .context("error uploading versions object")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use tempfile::tempdir;
+
+    use super::{
+        super::{
+            local::{Config, Local},
+            tests::{common_args, object},
+        },
+        *,
+    };
+
+    fn versions(ids: &[&str], current: &str) -> Versions {
+        let now = Utc::now();
+        Versions {
+            current: Some(current.to_string()),
+            versions: ids
+                .iter()
+                .zip(0..)
+                .map(|(id, i)| Version {
+                    id: (*id).to_string(),
+                    deployed_at: now + Duration::minutes(i),
+                })
+                .collect(),
+        }
+    }
+
+    fn ids(versions: &[Version]) -> Vec<&str> {
+        versions.iter().map(|version| version.id.as_str()).collect()
+    }
+
+    #[test]
+    fn versions_trim_keeps_current_version() {
+        let mut versions = versions(&["v1", "v2", "v3", "v4"], "v1");
+        let removed = versions.trim(2);
+        assert_eq!(ids(&removed), vec!["v2", "v3"]);
+        assert_eq!(ids(&versions.versions), vec!["v1", "v4"]);
+    }
+
+    #[test]
+    fn versions_add_replaces_existing_version() {
+        let mut versions = versions(&["v1", "v2"], "v2");
+        versions.add("v1", Utc::now());
+        assert_eq!(ids(&versions.versions), vec!["v2", "v1"]);
+        assert_eq!(versions.current, Some("v1".to_string()));
+    }
+
+    #[test]
+    fn validate_version_id_works() {
+        assert!(validate_version_id("20240101120000").is_ok());
+        assert!(validate_version_id("v1.2.3_rc-1").is_ok());
+        assert!(validate_version_id("").is_err());
+        assert!(validate_version_id("..").is_err());
+        assert!(validate_version_id("v1/../v2").is_err());
+    }
+
+    #[test]
+    fn prepare_pointer_works() {
+        let index_document = "<html>\n<head lang=\"en\">\n<title>Landscape</title>\n</head></html>";
+        assert_eq!(
+            prepare_pointer(index_document, None, "v1").unwrap(),
+            "<html>\n<head lang=\"en\">\n<base href=\"/versions/v1/\" />\n<title>Landscape</title>\n</head></html>"
+        );
+        assert_eq!(
+            prepare_pointer(index_document, Some("/landscape"), "v1").unwrap(),
+            "<html>\n<head lang=\"en\">\n<base href=\"/landscape/versions/v1/\" />\n<title>Landscape</title>\n</head></html>"
+        );
+        assert!(prepare_pointer("<html></html>", None, "v1").is_err());
+    }
+
+    #[test]
+    fn get_stale_root_copies_works() {
+        let deployed_objects: HashMap<Key, Object> = [
+            ".landscape2-versions.json",
+            "CNAME",
+            "data/full.json",
+            "index.html",
+            "sitemap.xml",
+            "versions/v1/data/full.json",
+            "versions/v1/index.html",
+            "versions/v2/data/full.json",
+            "versions/v2/index.html",
+            "versions/v2/sitemap.xml",
+        ]
+        .into_iter()
+        .map(|key| (key.to_string(), object(None)))
+        .collect();
+
+        let version_keys = HashSet::from(["data/full.json", "index.html"]);
+        let stale_root_copies = get_stale_root_copies(&deployed_objects, &version_keys);
+        let keys: Vec<&str> = stale_root_copies.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, vec!["sitemap.xml"]);
+    }
+
+    #[tokio::test]
+    async fn deploy_version_and_rollback_work() {
+        // Setup landscape and target directories
+        let landscape_tmp = tempdir().unwrap();
+        let landscape_dir = landscape_tmp.path();
+        fs::create_dir_all(landscape_dir.join("data")).unwrap();
+        fs::write(
+            landscape_dir.join("data").join("base.json"),
+            br#"{"base_path":"/l"}"#,
+        )
+        .unwrap();
+        let target_tmp = tempdir().unwrap();
+        let target_dir = target_tmp.path();
+        fs::create_dir_all(target_dir).unwrap();
+        fs::write(target_dir.join("CNAME"), "landscape.example.com").unwrap();
+        let local = Local::new(&Config {
+            target_dir: target_dir.to_path_buf(),
+        })
+        .unwrap();
+
+        // Deploy three versions, keeping only the last two (the last one
+        // includes an extra file)
+        for version_id in ["v1", "v2", "v3"] {
+            fs::write(
+                landscape_dir.join("index.html"),
+                format!("<head><title>{version_id}</title></head>"),
+            )
+            .unwrap();
+            fs::write(landscape_dir.join("data").join("full.json"), version_id).unwrap();
+            if version_id == "v3" {
+                fs::write(landscape_dir.join("sitemap.xml"), version_id).unwrap();
+            }
+            let args = CommonArgs {
+                versioned: true,
+                version_id: Some(version_id.to_string()),
+                keep_versions: 2,
+                ..common_args(landscape_dir)
+            };
+            deploy_version(&local, &args).await.unwrap();
+        }
+        let index_document = fs::read_to_string(target_dir.join("index.html")).unwrap();
+        assert_eq!(
+            index_document,
+            "<head>\n<base href=\"/l/versions/v3/\" /><title>v3</title></head>"
+        );
+        assert!(!target_dir.join("versions").join("v1").exists());
+        assert!(target_dir.join("versions").join("v2").join("data").join("base.json").exists());
+
+        // Files other than the index document are reachable at the root
+        let full_dataset = fs::read_to_string(target_dir.join("data").join("full.json")).unwrap();
+        assert_eq!(full_dataset, "v3");
+        assert!(target_dir.join("sitemap.xml").exists());
+
+        // Roll back to the previous version
+        rollback(&local, Some("v2")).await.unwrap();
+        let index_document = fs::read_to_string(target_dir.join("index.html")).unwrap();
+        assert_eq!(
+            index_document,
+            "<head>\n<base href=\"/l/versions/v2/\" /><title>v2</title></head>"
+        );
+        let full_dataset = fs::read_to_string(target_dir.join("data").join("full.json")).unwrap();
+        assert_eq!(full_dataset, "v2");
+        assert!(!target_dir.join("sitemap.xml").exists());
+        assert!(target_dir.join("CNAME").exists());
+        let versions = get_versions(&local).await.unwrap();
+        assert_eq!(versions.current, Some("v2".to_string()));
+        assert_eq!(ids(&versions.versions), vec!["v2", "v3"]);
+
+        // Versions that have been removed cannot be rolled back to
+        assert!(rollback(&local, Some("v1")).await.is_err());
+    }
+}
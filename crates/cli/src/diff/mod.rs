@@ -0,0 +1,254 @@
+//! This module defines the functionality of the diff CLI subcommand.
+
+use std::fmt::Write;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use landscape2_core::{
+    data::{DataSource, LandscapeData},
+    diff::{ItemKey, LandscapeDataDiff},
+};
+use tracing::instrument;
+
+/// Diff command arguments.
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// Previous version of the landscape data file (local path or url).
+    pub old: String,
+
+    /// New version of the landscape data file (local path or url).
+    pub new: String,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+}
+
+/// Format used to output the differences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// JSON document with all the differences.
+    Json,
+    /// Markdown document (i.e. for pull requests comments).
+    Markdown,
+    /// Human readable text.
+    Text,
+}
+
+/// Compare two versions of the landscape data file, printing the differences
+/// found between them.
+///
+/// # Errors
+/// Returns an error if any of the landscape data files is invalid or cannot
+/// be read, or if the differences cannot be serialized.
+#[instrument(skip_all)]
+pub async fn diff(args: &DiffArgs) -> Result<()> {
+    let old = LandscapeData::new(&data_source(&args.old))
+        .await
+        .context("the old landscape data file provided is not valid")?;
+    let new = LandscapeData::new(&data_source(&args.new))
+        .await
+        .context("the new landscape data file provided is not valid")?;
+    let diff = LandscapeDataDiff::new(&old, &new);
+
+    let output = match args.format {
+        Format::Json => serde_json::to_string_pretty(&diff)?,
+        Format::Markdown => render_markdown(&diff),
+        Format::Text => render_text(&diff),
+    };
+    println!("{output}");
+
+    Ok(())
+}
+
+/// Get the data source for the location provided (local path or url).
+fn data_source(location: &str) -> DataSource {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        DataSource::new_from_url(location.to_string())
+    } else {
+        DataSource {
+            data_file: Some(location.into()),
+            data_url: None,
+        }
+    }
+}
+
+/// Render the differences provided in markdown format.
+fn render_markdown(diff: &LandscapeDataDiff) -> String {
+    let mut md = String::from("## Landscape data changes\n\n");
+    if diff.is_empty() {
+        md.push_str("No changes found.\n");
+        return md;
+    }
+    let _ = writeln!(md, "{}\n", summary(diff));
+
+    let fmt_key = |key: &ItemKey| format!("**{}** ({} / {})", key.name, key.category, key.subcategory);
+    let mut section = |title: &str, lines: Vec<String>| {
+        if !lines.is_empty() {
+            let _ = writeln!(md, "### {title} ({})\n", lines.len());
+            for line in lines {
+                let _ = writeln!(md, "- {line}");
+            }
+            md.push('\n');
+        }
+    };
+    section("Added", diff.added.iter().map(fmt_key).collect());
+    section("Removed", diff.removed.iter().map(fmt_key).collect());
+    section(
+        "Renamed",
+        diff.renamed.iter().map(|m| format!("**{}** → {}", m.from.name, fmt_key(&m.to))).collect(),
+    );
+    section(
+        "Moved",
+        diff.moved
+            .iter()
+            .map(|m| {
+                format!(
+                    "**{}**: {} / {} → {} / {}",
+                    m.to.name, m.from.category, m.from.subcategory, m.to.category, m.to.subcategory
+                )
+            })
+            .collect(),
+    );
+    section(
+        "Changed",
+        diff.changed
+            .iter()
+            .map(|c| {
+                let mut line = fmt_key(&c.item);
+                for change in &c.changes {
+                    let _ = write!(
+                        line,
+                        "\n  - `{}`: {} → {}",
+                        change.field,
+                        md_value(change.old.as_ref()),
+                        md_value(change.new.as_ref())
+                    );
+                }
+                line
+            })
+            .collect(),
+    );
+
+    md.trim_end().to_string()
+}
+
+/// Render the differences provided in text format.
+fn render_text(diff: &LandscapeDataDiff) -> String {
+    if diff.is_empty() {
+        return "No changes found.".to_string();
+    }
+    let mut text = format!("{}\n", summary(diff));
+
+    let fmt_key = |key: &ItemKey| format!("{} ({} / {})", key.name, key.category, key.subcategory);
+    for key in &diff.added {
+        let _ = write!(text, "\n+ {}", fmt_key(key));
+    }
+    for key in &diff.removed {
+        let _ = write!(text, "\n- {}", fmt_key(key));
+    }
+    for m in &diff.renamed {
+        let _ = write!(text, "\n~ {} renamed to {}", m.from.name, fmt_key(&m.to));
+    }
+    for m in &diff.moved {
+        let _ = write!(
+            text,
+            "\n~ {} moved from {} / {} to {} / {}",
+            m.to.name, m.from.category, m.from.subcategory, m.to.category, m.to.subcategory
+        );
+    }
+    for c in &diff.changed {
+        let _ = write!(text, "\n~ {}", fmt_key(&c.item));
+        for change in &c.changes {
+            let _ = write!(
+                text,
+                "\n    {}: {} -> {}",
+                change.field,
+                change.old.as_deref().unwrap_or("(none)"),
+                change.new.as_deref().unwrap_or("(none)")
+            );
+        }
+    }
+
+    text
+}
+
+/// Return a summary of the number of differences of each kind.
+fn summary(diff: &LandscapeDataDiff) -> String {
+    format!(
+        "{} added, {} removed, {} renamed, {} moved, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.renamed.len(),
+        diff.moved.len(),
+        diff.changed.len()
+    )
+}
+
+/// Format the field value provided to be displayed in markdown.
+fn md_value(value: Option<&String>) -> String {
+    match value {
+        Some(value) => format!("`{}`", value.replace('`', "'")),
+        None => "_(none)_".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use landscape2_core::diff::{FieldChange, ItemChanges, ItemMove};
+
+    use super::*;
+
+    fn key(name: &str, subcategory: &str) -> ItemKey {
+        ItemKey {
+            category: "Category".to_string(),
+            subcategory: subcategory.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn render_markdown_succeeds() {
+        let diff = LandscapeDataDiff {
+            added: vec![key("Item1", "Subcategory")],
+            moved: vec![ItemMove {
+                from: key("Item2", "Subcategory1"),
+                to: key("Item2", "Subcategory2"),
+            }],
+            changed: vec![ItemChanges {
+                item: key("Item3", "Subcategory"),
+                changes: vec![FieldChange {
+                    field: "maturity".to_string(),
+                    old: Some("sandbox".to_string()),
+                    new: Some("incubating".to_string()),
+                }],
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render_markdown(&diff),
+            "## Landscape data changes
+
+1 added, 0 removed, 0 renamed, 1 moved, 1 changed
+
+### Added (1)
+
+- **Item1** (Category / Subcategory)
+
+### Moved (1)
+
+- **Item2**: Category / Subcategory1 → Category / Subcategory2
+
+### Changed (1)
+
+- **Item3** (Category / Subcategory)
+  - `maturity`: `sandbox` → `incubating`"
+        );
+    }
+
+    #[test]
+    fn render_text_no_changes() {
+        assert_eq!(render_text(&LandscapeDataDiff::default()), "No changes found.");
+    }
+}
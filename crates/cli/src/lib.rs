@@ -7,6 +7,8 @@
 
 pub mod build;
 pub mod deploy;
+pub mod diff;
+pub mod migrate;
 pub mod new;
 pub mod serve;
 pub mod validate;
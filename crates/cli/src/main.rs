@@ -8,12 +8,14 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use landscape2::build::{BuildArgs, build};
-use landscape2::deploy::s3::{self};
-use landscape2::deploy::{DeployArgs, Provider};
+use landscape2::deploy::{DeployArgs, deploy};
+use landscape2::diff::{DiffArgs, diff};
+use landscape2::migrate::{self, MigrateArgs, migrate_data};
 use landscape2::new::{NewArgs, new};
 use landscape2::serve::{ServeArgs, serve};
 use landscape2::validate::{
-    Target, ValidateArgs, validate_data, validate_games, validate_guide, validate_settings,
+    Target, ValidateArgs, validate_all, validate_data, validate_games, validate_guide, validate_logos,
+    validate_settings,
 };
 use tracing_subscriber::EnvFilter;
 
@@ -40,6 +42,12 @@ enum Command {
     /// Deploy landscape website (experimental).
     Deploy(DeployArgs),
 
+    /// Compare two versions of the landscape data file.
+    Diff(DiffArgs),
+
+    /// Migrate landscape data sources files to the latest format.
+    Migrate(MigrateArgs),
+
     /// Create a new landscape from the built-in template.
     New(NewArgs),
 
@@ -61,22 +69,26 @@ async fn main() -> Result<()> {
                 EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("landscape2=debug"));
             tracing_subscriber::fmt().with_env_filter(env_filter).init();
         }
-        Command::Validate(_) => {}
+        Command::Diff(_) | Command::Migrate(_) | Command::Validate(_) => {}
     }
 
     // Run command
     match &cli.command {
         Command::Build(args) => build(args).await?,
-        Command::Deploy(args) => match &args.provider {
-            Provider::S3(args) => s3::deploy(args).await?,
+        Command::Deploy(args) => deploy(args).await?,
+        Command::Diff(args) => diff(args).await?,
+        Command::Migrate(args) => match &args.target {
+            migrate::Target::Data(args) => migrate_data(args).await?,
         },
         Command::New(args) => new(args)?,
         Command::Serve(args) => serve(args).await?,
         Command::Validate(args) => match &args.target {
-            Target::Data(src) => validate_data(src).await?,
-            Target::Games(src) => validate_games(src).await?,
-            Target::Guide(src) => validate_guide(src).await?,
-            Target::Settings(src) => validate_settings(src).await?,
+            Target::All(all_args) => validate_all(all_args, args.format).await?,
+            Target::Data(data_args) => validate_data(data_args, args.format).await?,
+            Target::Games(src) => validate_games(src, args.format).await?,
+            Target::Guide(src) => validate_guide(src, args.format).await?,
+            Target::Logos(logos_args) => validate_logos(logos_args, args.format).await?,
+            Target::Settings(src) => validate_settings(src, args.format).await?,
         },
     }
 
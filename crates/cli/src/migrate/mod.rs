@@ -0,0 +1,59 @@
+//! This module defines the functionality of the migrate CLI subcommand.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use landscape2_core::data::{DataSource, LandscapeData};
+use tracing::instrument;
+
+/// Migrate command arguments.
+#[derive(clap::Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct MigrateArgs {
+    /// Landscape file to migrate.
+    #[command(subcommand)]
+    pub target: Target,
+}
+
+/// Landscape file to migrate.
+#[derive(Subcommand)]
+pub enum Target {
+    /// Migrate landscape data file to the native format.
+    Data(MigrateDataArgs),
+}
+
+/// Migrate data command arguments.
+#[derive(clap::Args)]
+pub struct MigrateDataArgs {
+    /// Data source.
+    #[command(flatten)]
+    pub data_source: DataSource,
+
+    /// File to write the migrated landscape data to. When none is provided,
+    /// it will be written to the standard output.
+    #[arg(long)]
+    pub output_file: Option<PathBuf>,
+}
+
+/// Migrate landscape data file to the native format.
+///
+/// # Errors
+/// Returns an error if the landscape data file is invalid or cannot be read,
+/// or if the migrated data cannot be written.
+#[instrument(skip_all)]
+pub async fn migrate_data(args: &MigrateDataArgs) -> Result<()> {
+    let landscape_data = LandscapeData::new(&args.data_source)
+        .await
+        .context("the landscape data file provided is not valid")?;
+    let raw_data = landscape_data.to_native_yaml()?;
+
+    if let Some(output_file) = &args.output_file {
+        fs::write(output_file, raw_data).context("error writing migrated landscape data file")?;
+        println!("The landscape data file has been migrated to the native format!");
+    } else {
+        print!("{raw_data}");
+    }
+
+    Ok(())
+}
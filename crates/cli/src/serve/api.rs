@@ -0,0 +1,278 @@
+//! This module provides the dynamic query API available when serving a
+//! landscape with the `--api` flag. The full dataset of the landscape is loaded
+//! in memory, and items can be filtered, sorted and paginated on the server,
+//! instead of having to download and process the static API files.
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use landscape2_core::data;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::build::{api::Item, crunchbase::get_permalink};
+
+use super::data::DataLoader;
+
+/// Path of the endpoint used to query the landscape items.
+const ITEMS_PATH: &str = "/api/items";
+
+/// Default number of items returned per page.
+const DEFAULT_PER_PAGE: usize = 20;
+
+/// Maximum number of items that can be requested per page.
+pub(super) const MAX_PER_PAGE: usize = 100;
+
+/// Setup the query API router using the data loader provided.
+pub(super) fn setup_api_router(loader: Arc<DataLoader>) -> Router {
+    Router::new().route(ITEMS_PATH, get(query_items)).with_state(loader)
+}
+
+/// Filters and options supported by the items query endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub(super) struct ItemsQuery {
+    pub category: Option<String>,
+    pub subcategory: Option<String>,
+    pub tag: Option<String>,
+    pub maturity: Option<String>,
+    pub organization: Option<String>,
+    pub license: Option<String>,
+    pub min_stars: Option<i64>,
+    pub q: Option<String>,
+    pub sort: SortBy,
+    pub order: Option<SortOrder>,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+/// Field used to sort the items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum SortBy {
+    #[default]
+    Name,
+    Stars,
+}
+
+/// Sort order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Items query results page.
+#[derive(Debug, Clone, Serialize)]
+struct ItemsPage {
+    items: Vec<Item>,
+    page: usize,
+    per_page: usize,
+    total: usize,
+}
+
+/// Handler that returns the items matching the query provided.
+async fn query_items(State(loader): State<Arc<DataLoader>>, Query(query): Query<ItemsQuery>) -> Response {
+    let data = match loader.data().await {
+        Ok(data) => data,
+        Err(err) => {
+            error!(?err, "error loading landscape data");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "error loading landscape data").into_response();
+        }
+    };
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+    let items = filter_and_sort(&data.landscape_data.items, &query);
+    let total = items.len();
+    let items = paginate(items, page, per_page)
+        .map(|item| Item::from_data_item(item, &data.landscape_url))
+        .collect();
+
+    Json(ItemsPage {
+        items,
+        page,
+        per_page,
+        total,
+    })
+    .into_response()
+}
+
+/// Return the items matching the filters in the query provided, sorted as
+/// requested.
+pub(super) fn filter_and_sort<'a>(items: &'a [data::Item], query: &ItemsQuery) -> Vec<&'a data::Item> {
+    let eq = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+    let text = query.q.as_ref().map(|q| q.trim().to_lowercase());
+
+    let mut items: Vec<&data::Item> = items
+        .iter()
+        .filter(|i| query.category.as_ref().is_none_or(|c| eq(&i.category, c)))
+        .filter(|i| query.subcategory.as_ref().is_none_or(|s| eq(&i.subcategory, s)))
+        .filter(|i| {
+            query
+                .tag
+                .as_ref()
+                .is_none_or(|t| i.tag.as_ref().is_some_and(|tags| tags.iter().any(|tag| eq(tag, t))))
+        })
+        .filter(|i| {
+            query
+                .maturity
+                .as_ref()
+                .is_none_or(|m| i.maturity.as_ref().is_some_and(|maturity| eq(maturity, m)))
+        })
+        .filter(|i| query.organization.as_ref().is_none_or(|o| organization_matches(i, o)))
+        .filter(|i| query.license.as_ref().is_none_or(|l| license_matches(i, l)))
+        .filter(|i| query.min_stars.is_none_or(|min| stars(i).is_some_and(|stars| stars >= min)))
+        .filter(|i| {
+            text.as_ref().is_none_or(|text| {
+                i.name.to_lowercase().contains(text)
+                    || i.description().is_some_and(|d| d.to_lowercase().contains(text))
+            })
+        })
+        .collect();
+
+    match query.sort {
+        SortBy::Name => {
+            items.sort_by_cached_key(|i| i.name.to_lowercase());
+            if query.order == Some(SortOrder::Desc) {
+                items.reverse();
+            }
+        }
+        SortBy::Stars => {
+            items.sort_by_key(|i| stars(i).unwrap_or_default());
+            if query.order != Some(SortOrder::Asc) {
+                items.reverse();
+            }
+        }
+    }
+
+    items
+}
+
+/// Return the items in the page requested. Pages past the last one are empty.
+fn paginate(items: Vec<&data::Item>, page: usize, per_page: usize) -> impl Iterator<Item = &data::Item> {
+    items.into_iter().skip(page.saturating_sub(1).saturating_mul(per_page)).take(per_page)
+}
+
+/// Check if the item belongs to the organization provided (Crunchbase
+/// permalink or organization name).
+fn organization_matches(item: &data::Item, organization: &str) -> bool {
+    let permalink_matches = item
+        .crunchbase_url
+        .as_ref()
+        .and_then(|url| get_permalink(url).ok())
+        .is_some_and(|permalink| permalink.eq_ignore_ascii_case(organization));
+    let name_matches = item
+        .crunchbase_data
+        .as_ref()
+        .and_then(|org| org.name.as_ref())
+        .is_some_and(|name| name.eq_ignore_ascii_case(organization));
+    permalink_matches || name_matches
+}
+
+/// Check if any of the item's repositories uses the license provided.
+fn license_matches(item: &data::Item, license: &str) -> bool {
+    item.repositories.as_ref().is_some_and(|repos| {
+        repos.iter().any(|r| {
+            let gh_license = r.github_data.as_ref().and_then(|gh| gh.license.as_ref());
+            r.license.iter().chain(gh_license).any(|l| l.eq_ignore_ascii_case(license))
+        })
+    })
+}
+
+/// Return the number of stars of the item's primary repository.
+fn stars(item: &data::Item) -> Option<i64> {
+    item.primary_repository().and_then(|r| r.github_data.as_ref()).map(|gh| gh.stars)
+}
+
+#[cfg(test)]
+mod tests {
+    use landscape2_core::data::{Repository, RepositoryGithubData};
+
+    use super::*;
+
+    fn item(name: &str, maturity: Option<&str>, stars: i64) -> data::Item {
+        data::Item {
+            category: "Category".to_string(),
+            maturity: maturity.map(ToString::to_string),
+            name: name.to_string(),
+            repositories: Some(vec![Repository {
+                github_data: Some(RepositoryGithubData {
+                    license: Some("Apache-2.0".to_string()),
+                    stars,
+                    ..Default::default()
+                }),
+                primary: Some(true),
+                url: format!("https://github.com/org/{}", name.to_lowercase()),
+                ..Default::default()
+            }]),
+            subcategory: "Subcategory".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filter_and_sort_succeeds() {
+        let items = vec![
+            item("Item1", Some("graduated"), 10),
+            item("Item2", Some("sandbox"), 300),
+            item("Item3", Some("graduated"), 200),
+            item("Item4", None, 1000),
+        ];
+        let names = |query: &ItemsQuery| -> Vec<String> {
+            filter_and_sort(&items, query).iter().map(|i| i.name.clone()).collect()
+        };
+
+        let query = ItemsQuery {
+            maturity: Some("Graduated".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&query), vec!["Item1", "Item3"]);
+
+        let query = ItemsQuery {
+            license: Some("apache-2.0".to_string()),
+            min_stars: Some(100),
+            sort: SortBy::Stars,
+            ..Default::default()
+        };
+        assert_eq!(names(&query), vec!["Item4", "Item2", "Item3"]);
+
+        let query = ItemsQuery {
+            q: Some("item2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&query), vec!["Item2"]);
+    }
+
+    #[test]
+    fn paginate_succeeds() {
+        let items = [
+            item("Item1", None, 0),
+            item("Item2", None, 0),
+            item("Item3", None, 0),
+        ];
+        let names = |page: usize, per_page: usize| -> Vec<String> {
+            paginate(items.iter().collect(), page, per_page).map(|i| i.name.clone()).collect()
+        };
+
+        assert_eq!(names(1, 2), vec!["Item1", "Item2"]);
+        assert_eq!(names(2, 2), vec!["Item3"]);
+        assert!(names(3, 2).is_empty());
+    }
+
+    #[test]
+    fn paginate_page_overflow_returns_no_items() {
+        let items = [item("Item1", None, 0)];
+        assert_eq!(
+            paginate(items.iter().collect(), usize::MAX, MAX_PER_PAGE).count(),
+            0
+        );
+    }
+}
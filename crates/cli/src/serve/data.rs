@@ -0,0 +1,233 @@
+//! This module provides the functionality used to load the landscape data
+//! served by the query API and the GraphQL endpoint from the built datasets,
+//! as well as the items aliases used to redirect requests.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use landscape2_core::{
+    data::{CrunchbaseData, GithubData, ItemsAliases, LandscapeData},
+    datasets::{base::Base, full::Full},
+};
+use tokio::sync::RwLock;
+
+use crate::build::aliases::{ITEMS_ALIASES_FILE, load_items_aliases};
+
+/// Landscape data loader.
+///
+/// The data is loaded from the full dataset the first time it is requested,
+/// and reloaded when the dataset is modified (i.e. rebuilt in watch mode).
+/// Datasets are read and parsed on a blocking thread, so that the requests
+/// handled concurrently don't block the runtime workers.
+pub(super) struct DataLoader {
+    landscape_dir: PathBuf,
+    data: RwLock<Option<(SystemTime, Arc<LoadedData>)>>,
+}
+
+impl DataLoader {
+    /// Create a new data loader for the landscape directory provided.
+    pub(super) fn new(landscape_dir: &Path) -> Self {
+        Self {
+            landscape_dir: landscape_dir.to_path_buf(),
+            data: RwLock::new(None),
+        }
+    }
+
+    /// Return the landscape data, (re)loading it if needed.
+    pub(super) async fn data(&self) -> Result<Arc<LoadedData>> {
+        let full_path = self.landscape_dir.join("data").join("full.json");
+        let modified = tokio::fs::metadata(&full_path)
+            .await
+            .and_then(|m| m.modified())
+            .context("error reading full dataset metadata")?;
+
+        // Return the data loaded previously if it's still up to date
+        if let Some(data) = Self::cached(self.data.read().await.as_ref(), modified) {
+            return Ok(data);
+        }
+
+        // Otherwise load it again (unless another request already did it
+        // while we were waiting for the lock)
+        let mut cached = self.data.write().await;
+        if let Some(data) = Self::cached(cached.as_ref(), modified) {
+            return Ok(data);
+        }
+        let landscape_dir = self.landscape_dir.clone();
+        let loaded = Arc::new(tokio::task::spawn_blocking(move || LoadedData::load(&landscape_dir)).await??);
+        *cached = Some((modified, loaded.clone()));
+        Ok(loaded)
+    }
+
+    /// Return the data cached if it was loaded from the full dataset version
+    /// provided (identified by its modification time).
+    fn cached(
+        cached: Option<&(SystemTime, Arc<LoadedData>)>,
+        modified: SystemTime,
+    ) -> Option<Arc<LoadedData>> {
+        cached
+            .filter(|(data_modified, _)| *data_modified == modified)
+            .map(|(_, data)| data.clone())
+    }
+}
+
+/// Items aliases loader.
+///
+/// The aliases are loaded the first time they are requested, and reloaded
+/// when the aliases file is modified (i.e. rebuilt in watch mode).
+pub(super) struct AliasesLoader {
+    landscape_dir: PathBuf,
+    aliases: Mutex<Option<(SystemTime, Arc<ItemsAliases>)>>,
+}
+
+impl AliasesLoader {
+    /// Create a new aliases loader for the landscape directory provided.
+    pub(super) fn new(landscape_dir: &Path) -> Self {
+        Self {
+            landscape_dir: landscape_dir.to_path_buf(),
+            aliases: Mutex::new(None),
+        }
+    }
+
+    /// Return the items aliases, (re)loading them if needed. No aliases are
+    /// returned when the aliases file is not available.
+    pub(super) fn aliases(&self) -> Arc<ItemsAliases> {
+        let aliases_path = self.landscape_dir.join("data").join(ITEMS_ALIASES_FILE);
+        let Ok(modified) = fs::metadata(&aliases_path).and_then(|m| m.modified()) else {
+            return Arc::default();
+        };
+
+        let mut cached = self.aliases.lock().expect("lock not to be poisoned");
+        if let Some((aliases_modified, aliases)) = cached.as_ref()
+            && *aliases_modified == modified
+        {
+            return aliases.clone();
+        }
+        let loaded = Arc::new(load_items_aliases(&self.landscape_dir));
+        *cached = Some((modified, loaded.clone()));
+        loaded
+    }
+}
+
+/// Landscape data loaded from the built datasets.
+#[derive(Debug, Clone, Default)]
+pub(super) struct LoadedData {
+    pub crunchbase_data: CrunchbaseData,
+    pub github_data: GithubData,
+    pub landscape_data: LandscapeData,
+    pub landscape_url: String,
+}
+
+impl LoadedData {
+    /// Load the landscape data from the landscape directory provided.
+    fn load(landscape_dir: &Path) -> Result<Self> {
+        let full: Full = serde_json::from_slice(&fs::read(landscape_dir.join("data").join("full.json"))?)
+            .context("invalid full dataset")?;
+        let base: Base = serde_json::from_slice(&fs::read(landscape_dir.join("data").join("base.json"))?)
+            .context("invalid base dataset")?;
+
+        // Crunchbase and GitHub data is not included in the items when the
+        // full dataset is serialized, so we need to add it back
+        let mut landscape_data = LandscapeData {
+            categories: base.categories,
+            items: full.items,
+        };
+        landscape_data.add_crunchbase_data(&full.crunchbase_data);
+        landscape_data.add_github_data(&full.github_data);
+
+        Ok(Self {
+            crunchbase_data: full.crunchbase_data,
+            github_data: full.github_data,
+            landscape_data,
+            landscape_url: base.url,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn write_full_dataset(landscape_dir: &Path, items_names: &[&str], modified: SystemTime) {
+        let full = Full {
+            items: items_names
+                .iter()
+                .map(|name| landscape2_core::data::Item {
+                    name: (*name).to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let full_path = landscape_dir.join("data").join("full.json");
+        fs::write(&full_path, serde_json::to_vec(&full).unwrap()).unwrap();
+        fs::File::options().write(true).open(&full_path).unwrap().set_modified(modified).unwrap();
+    }
+
+    #[tokio::test]
+    async fn data_loader_reloads_modified_dataset() {
+        // Setup landscape directory
+        let tmp = tempdir().unwrap();
+        let landscape_dir = tmp.path();
+        fs::create_dir_all(landscape_dir.join("data")).unwrap();
+        let base = Base {
+            url: "https://landscape.example.com".to_string(),
+            ..Default::default()
+        };
+        fs::write(
+            landscape_dir.join("data").join("base.json"),
+            serde_json::to_vec(&base).unwrap(),
+        )
+        .unwrap();
+        let loader = DataLoader::new(landscape_dir);
+
+        // No data available yet
+        assert!(loader.data().await.is_err());
+
+        // Data is loaded once, and reloaded when the dataset is modified
+        let now = SystemTime::now();
+        write_full_dataset(landscape_dir, &["Item1"], now);
+        let data = loader.data().await.unwrap();
+        assert_eq!(data.landscape_data.items[0].name, "Item1");
+        assert_eq!(data.landscape_url, "https://landscape.example.com");
+        assert!(Arc::ptr_eq(&data, &loader.data().await.unwrap()));
+        write_full_dataset(landscape_dir, &["Item1", "Item2"], now + Duration::from_secs(1));
+        assert_eq!(loader.data().await.unwrap().landscape_data.items.len(), 2);
+    }
+
+    #[test]
+    fn aliases_loader_reloads_modified_aliases() {
+        // Setup landscape directory
+        let tmp = tempdir().unwrap();
+        let landscape_dir = tmp.path();
+        fs::create_dir_all(landscape_dir.join("data")).unwrap();
+        let aliases_path = landscape_dir.join("data").join("aliases.json");
+        let loader = AliasesLoader::new(landscape_dir);
+
+        // No aliases available yet
+        assert!(loader.aliases().is_empty());
+
+        // Aliases are loaded once, and reloaded when the file is modified
+        fs::write(&aliases_path, r#"{"a--b--old":"a--b--new"}"#).unwrap();
+        let aliases = loader.aliases();
+        assert_eq!(aliases.get("a--b--old"), Some(&"a--b--new".to_string()));
+        assert!(Arc::ptr_eq(&aliases, &loader.aliases()));
+        fs::write(&aliases_path, r#"{"a--b--old":"a--c--new"}"#).unwrap();
+        let modified = SystemTime::now() + Duration::from_secs(1);
+        fs::File::options()
+            .write(true)
+            .open(&aliases_path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(loader.aliases().get("a--b--old"), Some(&"a--c--new".to_string()));
+    }
+}
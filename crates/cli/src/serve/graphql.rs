@@ -0,0 +1,850 @@
+//! This module provides the GraphQL endpoint available when serving a
+//! landscape with the `--graphql` flag. It exposes the landscape items along
+//! with their repositories, GitHub and Crunchbase data, allowing clients to
+//! run nested queries that the static API cannot express.
+
+use std::sync::Arc;
+
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Enum, InputObject, Object, Result, Schema, SimpleObject,
+    http::GraphiQLSource,
+};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use landscape2_core::data::{self, Organization, RepositoryGithubData};
+use tracing::error;
+
+use crate::build::{api::absolute_url, crunchbase::get_permalink};
+
+use super::{
+    api::{self, ItemsQuery, MAX_PER_PAGE, filter_and_sort},
+    data::{DataLoader, LoadedData},
+};
+
+/// Path of the GraphQL endpoint.
+const GRAPHQL_PATH: &str = "/graphql";
+
+/// Maximum depth of the queries accepted.
+const MAX_QUERY_DEPTH: usize = 10;
+
+/// Maximum complexity of the queries accepted. The complexity of a query is
+/// the number of fields requested, where the fields of the items in a list
+/// are counted once per item that can be returned.
+const MAX_QUERY_COMPLEXITY: usize = 10_000;
+
+/// Landscape GraphQL schema.
+type LandscapeSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// GraphQL endpoint state.
+#[derive(Clone)]
+struct GraphqlState {
+    loader: Arc<DataLoader>,
+    schema: LandscapeSchema,
+}
+
+/// Setup the GraphQL router using the data loader provided.
+pub(super) fn setup_graphql_router(loader: Arc<DataLoader>) -> Router {
+    let state = GraphqlState {
+        loader,
+        schema: build_schema(),
+    };
+    Router::new().route(GRAPHQL_PATH, get(graphiql).post(graphql)).with_state(state)
+}
+
+/// Build the landscape GraphQL schema.
+fn build_schema() -> LandscapeSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+/// Handler that returns the GraphiQL IDE.
+async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint(GRAPHQL_PATH).finish())
+}
+
+/// Handler that executes the GraphQL request provided.
+async fn graphql(State(state): State<GraphqlState>, Json(req): Json<async_graphql::Request>) -> Response {
+    let data = match state.loader.data().await {
+        Ok(data) => data,
+        Err(err) => {
+            error!(?err, "error loading landscape data");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "error loading landscape data").into_response();
+        }
+    };
+
+    Json(state.schema.execute(req.data(data)).await).into_response()
+}
+
+/// Filters that can be applied to the landscape items.
+#[derive(Debug, Clone, Default, InputObject)]
+struct ItemsFilter {
+    category: Option<String>,
+    subcategory: Option<String>,
+    tag: Option<String>,
+    maturity: Option<String>,
+    /// Crunchbase permalink or organization name.
+    organization: Option<String>,
+    license: Option<String>,
+    min_stars: Option<i64>,
+    /// Text to search for in the items names and descriptions.
+    q: Option<String>,
+}
+
+impl ItemsFilter {
+    /// Convert the filter into an items query using the sort options
+    /// provided.
+    fn into_query(self, sort: Option<ItemsSortBy>, order: Option<ItemsSortOrder>) -> ItemsQuery {
+        ItemsQuery {
+            category: self.category,
+            subcategory: self.subcategory,
+            tag: self.tag,
+            maturity: self.maturity,
+            organization: self.organization,
+            license: self.license,
+            min_stars: self.min_stars,
+            q: self.q,
+            sort: sort.map(Into::into).unwrap_or_default(),
+            order: order.map(Into::into),
+            page: None,
+            per_page: None,
+        }
+    }
+}
+
+/// Field used to sort the items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(name = "SortBy")]
+enum ItemsSortBy {
+    Name,
+    Stars,
+}
+
+impl From<ItemsSortBy> for api::SortBy {
+    fn from(sort: ItemsSortBy) -> Self {
+        match sort {
+            ItemsSortBy::Name => api::SortBy::Name,
+            ItemsSortBy::Stars => api::SortBy::Stars,
+        }
+    }
+}
+
+/// Sort order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(name = "SortOrder")]
+enum ItemsSortOrder {
+    Asc,
+    Desc,
+}
+
+impl From<ItemsSortOrder> for api::SortOrder {
+    fn from(order: ItemsSortOrder) -> Self {
+        match order {
+            ItemsSortOrder::Asc => api::SortOrder::Asc,
+            ItemsSortOrder::Desc => api::SortOrder::Desc,
+        }
+    }
+}
+
+/// GraphQL query root.
+struct QueryRoot;
+
+#[Object(name = "Query")]
+#[allow(clippy::unused_async)]
+impl QueryRoot {
+    /// Landscape categories.
+    async fn categories<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Vec<CategoryObject<'ctx>>> {
+        let data = loaded_data(ctx)?;
+        Ok(data
+            .landscape_data
+            .categories
+            .iter()
+            .map(|category| CategoryObject { category, data })
+            .collect())
+    }
+
+    /// Item with the id provided.
+    async fn item<'ctx>(&self, ctx: &Context<'ctx>, id: String) -> Result<Option<ItemObject<'ctx>>> {
+        let data = loaded_data(ctx)?;
+        Ok(data
+            .landscape_data
+            .items
+            .iter()
+            .find(|i| i.id == id)
+            .map(|item| ItemObject { item, data }))
+    }
+
+    /// Items matching the filter provided (up to 100 per query, use `offset`
+    /// to fetch the next ones).
+    #[graphql(complexity = "page_size(limit).saturating_mul(child_complexity)")]
+    async fn items<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        filter: Option<ItemsFilter>,
+        sort: Option<ItemsSortBy>,
+        order: Option<ItemsSortOrder>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<ItemObject<'ctx>>> {
+        let data = loaded_data(ctx)?;
+        let query = filter.unwrap_or_default().into_query(sort, order);
+        Ok(
+            paginate(filter_and_sort(&data.landscape_data.items, &query), limit, offset)
+                .map(|item| ItemObject { item, data })
+                .collect(),
+        )
+    }
+
+    /// Number of items matching the filter provided.
+    async fn items_count(&self, ctx: &Context<'_>, filter: Option<ItemsFilter>) -> Result<usize> {
+        let data = loaded_data(ctx)?;
+        let query = filter.unwrap_or_default().into_query(None, None);
+        Ok(filter_and_sort(&data.landscape_data.items, &query).len())
+    }
+
+    /// Organization with the Crunchbase permalink provided.
+    async fn organization<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        permalink: String,
+    ) -> Result<Option<OrganizationObject<'ctx>>> {
+        let data = loaded_data(ctx)?;
+        Ok(data
+            .crunchbase_data
+            .iter()
+            .find(|(url, _)| get_permalink(url).is_ok_and(|p| p.eq_ignore_ascii_case(&permalink)))
+            .map(|(url, org)| OrganizationObject { url, org, data }))
+    }
+
+    /// Organizations of the landscape items (collected from Crunchbase, up to
+    /// 100 per query, use `offset` to fetch the next ones).
+    #[graphql(complexity = "page_size(limit).saturating_mul(child_complexity)")]
+    async fn organizations<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<OrganizationObject<'ctx>>> {
+        let data = loaded_data(ctx)?;
+        Ok(paginate(&data.crunchbase_data, limit, offset)
+            .map(|(url, org)| OrganizationObject { url, org, data })
+            .collect())
+    }
+}
+
+/// Landscape category.
+struct CategoryObject<'a> {
+    category: &'a data::Category,
+    data: &'a LoadedData,
+}
+
+#[Object(name = "Category")]
+impl<'a> CategoryObject<'a> {
+    async fn name(&self) -> &str {
+        &self.category.name
+    }
+
+    async fn normalized_name(&self) -> &str {
+        &self.category.normalized_name
+    }
+
+    async fn subcategories(&self) -> Vec<SubcategoryObject<'a>> {
+        self.category
+            .subcategories
+            .iter()
+            .map(|subcategory| SubcategoryObject {
+                category: self.category,
+                subcategory,
+                data: self.data,
+            })
+            .collect()
+    }
+
+    /// Items in the category (up to 100 per query, use `offset` to fetch the
+    /// next ones).
+    #[graphql(complexity = "page_size(limit).saturating_mul(child_complexity)")]
+    async fn items(&self, limit: Option<usize>, offset: Option<usize>) -> Vec<ItemObject<'a>> {
+        let items = self.data.landscape_data.items.iter().filter(|i| i.category == self.category.name);
+        paginate(items, limit, offset)
+            .map(|item| ItemObject {
+                item,
+                data: self.data,
+            })
+            .collect()
+    }
+}
+
+/// Landscape subcategory.
+struct SubcategoryObject<'a> {
+    category: &'a data::Category,
+    subcategory: &'a data::Subcategory,
+    data: &'a LoadedData,
+}
+
+#[Object(name = "Subcategory")]
+impl<'a> SubcategoryObject<'a> {
+    async fn name(&self) -> &str {
+        &self.subcategory.name
+    }
+
+    async fn normalized_name(&self) -> &str {
+        &self.subcategory.normalized_name
+    }
+
+    /// Items in the subcategory (up to 100 per query, use `offset` to fetch
+    /// the next ones).
+    #[graphql(complexity = "page_size(limit).saturating_mul(child_complexity)")]
+    async fn items(&self, limit: Option<usize>, offset: Option<usize>) -> Vec<ItemObject<'a>> {
+        let items = (self.data.landscape_data.items.iter())
+            .filter(|i| i.category == self.category.name && i.subcategory == self.subcategory.name);
+        paginate(items, limit, offset)
+            .map(|item| ItemObject {
+                item,
+                data: self.data,
+            })
+            .collect()
+    }
+}
+
+/// Landscape item.
+struct ItemObject<'a> {
+    item: &'a data::Item,
+    data: &'a LoadedData,
+}
+
+#[Object(name = "Item")]
+impl<'a> ItemObject<'a> {
+    async fn id(&self) -> &str {
+        &self.item.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.item.name
+    }
+
+    async fn category(&self) -> &str {
+        &self.item.category
+    }
+
+    async fn subcategory(&self) -> &str {
+        &self.item.subcategory
+    }
+
+    async fn description(&self) -> Option<&String> {
+        self.item.description()
+    }
+
+    async fn homepage_url(&self) -> &str {
+        &self.item.homepage_url
+    }
+
+    async fn logo_url(&self) -> String {
+        absolute_url(&self.data.landscape_url, &self.item.logo)
+    }
+
+    async fn maturity(&self) -> Option<&String> {
+        self.item.maturity.as_ref()
+    }
+
+    async fn tags(&self) -> Option<&Vec<String>> {
+        self.item.tag.as_ref()
+    }
+
+    async fn oss(&self) -> Option<bool> {
+        self.item.oss
+    }
+
+    async fn enduser(&self) -> Option<bool> {
+        self.item.enduser
+    }
+
+    async fn member_subcategory(&self) -> Option<&String> {
+        self.item.member_subcategory.as_ref()
+    }
+
+    async fn accepted_at(&self) -> Option<NaiveDate> {
+        self.item.accepted_at
+    }
+
+    async fn incubating_at(&self) -> Option<NaiveDate> {
+        self.item.incubating_at
+    }
+
+    async fn graduated_at(&self) -> Option<NaiveDate> {
+        self.item.graduated_at
+    }
+
+    async fn archived_at(&self) -> Option<NaiveDate> {
+        self.item.archived_at
+    }
+
+    async fn crunchbase_url(&self) -> Option<&String> {
+        self.item.crunchbase_url.as_ref()
+    }
+
+    async fn devstats_url(&self) -> Option<&String> {
+        self.item.devstats_url.as_ref()
+    }
+
+    async fn twitter_url(&self) -> Option<&String> {
+        self.item.twitter_url.as_ref()
+    }
+
+    /// Item's repositories.
+    async fn repositories(&self) -> Vec<RepositoryObject<'a>> {
+        self.item
+            .repositories
+            .iter()
+            .flatten()
+            .map(|repository| RepositoryObject {
+                repository,
+                data: self.data,
+            })
+            .collect()
+    }
+
+    /// Item's primary repository.
+    async fn primary_repository(&self) -> Option<RepositoryObject<'a>> {
+        self.item.primary_repository().map(|repository| RepositoryObject {
+            repository,
+            data: self.data,
+        })
+    }
+
+    /// Organization the item belongs to (collected from Crunchbase).
+    async fn organization(&self) -> Option<OrganizationObject<'a>> {
+        let url = self.item.crunchbase_url.as_ref()?;
+        let (url, org) = self.data.crunchbase_data.get_key_value(url)?;
+        Some(OrganizationObject {
+            url,
+            org,
+            data: self.data,
+        })
+    }
+}
+
+/// Item's repository.
+struct RepositoryObject<'a> {
+    repository: &'a data::Repository,
+    data: &'a LoadedData,
+}
+
+#[Object(name = "Repository")]
+impl<'a> RepositoryObject<'a> {
+    async fn url(&self) -> &str {
+        &self.repository.url
+    }
+
+    async fn branch(&self) -> Option<&String> {
+        self.repository.branch.as_ref()
+    }
+
+    async fn license(&self) -> Option<&String> {
+        self.repository.license.as_ref()
+    }
+
+    async fn primary(&self) -> bool {
+        self.repository.primary.unwrap_or_default()
+    }
+
+    /// Repository information collected from GitHub.
+    async fn github_data(&self) -> Option<GithubDataObject<'a>> {
+        self.data
+            .github_data
+            .get(&self.repository.url)
+            .map(|gh_data| GithubDataObject { gh_data })
+    }
+}
+
+/// Repository information collected from GitHub.
+struct GithubDataObject<'a> {
+    gh_data: &'a RepositoryGithubData,
+}
+
+#[Object(name = "GithubData")]
+impl GithubDataObject<'_> {
+    async fn url(&self) -> &str {
+        &self.gh_data.url
+    }
+
+    async fn description(&self) -> &str {
+        &self.gh_data.description
+    }
+
+    async fn stars(&self) -> i64 {
+        self.gh_data.stars
+    }
+
+    async fn license(&self) -> Option<&String> {
+        self.gh_data.license.as_ref()
+    }
+
+    async fn topics(&self) -> &Vec<String> {
+        &self.gh_data.topics
+    }
+
+    async fn contributors_count(&self) -> usize {
+        self.gh_data.contributors.count
+    }
+
+    async fn contributors_url(&self) -> &str {
+        &self.gh_data.contributors.url
+    }
+
+    async fn first_commit_ts(&self) -> Option<DateTime<Utc>> {
+        self.gh_data.first_commit.as_ref().and_then(|c| c.ts)
+    }
+
+    async fn latest_commit_ts(&self) -> Option<DateTime<Utc>> {
+        self.gh_data.latest_commit.ts
+    }
+
+    async fn latest_commit_url(&self) -> &str {
+        &self.gh_data.latest_commit.url
+    }
+
+    async fn latest_release_ts(&self) -> Option<DateTime<Utc>> {
+        self.gh_data.latest_release.as_ref().and_then(|r| r.ts)
+    }
+
+    async fn latest_release_url(&self) -> Option<&String> {
+        self.gh_data.latest_release.as_ref().map(|r| &r.url)
+    }
+
+    /// Languages used in the repository (bytes of code per language).
+    async fn languages(&self) -> Vec<Language> {
+        self.gh_data
+            .languages
+            .iter()
+            .flatten()
+            .map(|(name, bytes)| Language {
+                name: name.clone(),
+                bytes: *bytes,
+            })
+            .collect()
+    }
+
+    /// Weekly commits during the last year.
+    async fn participation_stats(&self) -> &Vec<i64> {
+        &self.gh_data.participation_stats
+    }
+
+    async fn generated_at(&self) -> DateTime<Utc> {
+        self.gh_data.generated_at
+    }
+}
+
+/// Language used in a repository.
+#[derive(SimpleObject)]
+struct Language {
+    name: String,
+    bytes: i64,
+}
+
+/// Organization information collected from Crunchbase.
+struct OrganizationObject<'a> {
+    url: &'a str,
+    org: &'a Organization,
+    data: &'a LoadedData,
+}
+
+#[Object(name = "Organization")]
+impl<'a> OrganizationObject<'a> {
+    async fn crunchbase_url(&self) -> &str {
+        self.url
+    }
+
+    async fn permalink(&self) -> Option<String> {
+        get_permalink(self.url).ok()
+    }
+
+    async fn name(&self) -> Option<&String> {
+        self.org.name.as_ref()
+    }
+
+    async fn description(&self) -> Option<&String> {
+        self.org.description.as_ref()
+    }
+
+    async fn homepage_url(&self) -> Option<&String> {
+        self.org.homepage_url.as_ref()
+    }
+
+    async fn city(&self) -> Option<&String> {
+        self.org.city.as_ref()
+    }
+
+    async fn region(&self) -> Option<&String> {
+        self.org.region.as_ref()
+    }
+
+    async fn country(&self) -> Option<&String> {
+        self.org.country.as_ref()
+    }
+
+    async fn company_type(&self) -> Option<&String> {
+        self.org.company_type.as_ref()
+    }
+
+    async fn kind(&self) -> Option<&String> {
+        self.org.kind.as_ref()
+    }
+
+    async fn categories(&self) -> Option<&Vec<String>> {
+        self.org.categories.as_ref()
+    }
+
+    async fn funding(&self) -> Option<i64> {
+        self.org.funding
+    }
+
+    async fn num_employees_min(&self) -> Option<i64> {
+        self.org.num_employees_min
+    }
+
+    async fn num_employees_max(&self) -> Option<i64> {
+        self.org.num_employees_max
+    }
+
+    async fn stock_exchange(&self) -> Option<&String> {
+        self.org.stock_exchange.as_ref()
+    }
+
+    async fn ticker(&self) -> Option<&String> {
+        self.org.ticker.as_ref()
+    }
+
+    async fn linkedin_url(&self) -> Option<&String> {
+        self.org.linkedin_url.as_ref()
+    }
+
+    async fn twitter_url(&self) -> Option<&String> {
+        self.org.twitter_url.as_ref()
+    }
+
+    /// Landscape items that belong to the organization (up to 100 per query,
+    /// use `offset` to fetch the next ones).
+    #[graphql(complexity = "page_size(limit).saturating_mul(child_complexity)")]
+    async fn items(&self, limit: Option<usize>, offset: Option<usize>) -> Vec<ItemObject<'a>> {
+        let items =
+            (self.data.landscape_data.items.iter()).filter(|i| i.crunchbase_url.as_deref() == Some(self.url));
+        paginate(items, limit, offset)
+            .map(|item| ItemObject {
+                item,
+                data: self.data,
+            })
+            .collect()
+    }
+}
+
+/// Return the number of entries returned by a list for the limit provided
+/// (up to MAX_PER_PAGE).
+fn page_size(limit: Option<usize>) -> usize {
+    limit.unwrap_or(MAX_PER_PAGE).min(MAX_PER_PAGE)
+}
+
+/// Return the page of the list provided selected by the limit and offset
+/// provided.
+fn paginate<T: IntoIterator>(
+    list: T,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> impl Iterator<Item = T::Item> {
+    list.into_iter().skip(offset.unwrap_or_default()).take(page_size(limit))
+}
+
+/// Return the landscape data available in the context provided.
+fn loaded_data<'ctx>(ctx: &Context<'ctx>) -> Result<&'ctx LoadedData> {
+    Ok(ctx.data::<Arc<LoadedData>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use landscape2_core::data::{Category, LandscapeData, Repository, Subcategory};
+    use serde_json::json;
+
+    use super::*;
+
+    const CB_URL: &str = "https://www.crunchbase.com/organization/org";
+    const REPO_URL: &str = "https://github.com/org/item";
+
+    fn loaded_data() -> LoadedData {
+        LoadedData {
+            crunchbase_data: [(
+                CB_URL.to_string(),
+                Organization {
+                    name: Some("Org".to_string()),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            github_data: [(
+                REPO_URL.to_string(),
+                RepositoryGithubData {
+                    stars: 42,
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            landscape_data: LandscapeData {
+                items: vec![
+                    data::Item {
+                        id: "category--subcategory--item".to_string(),
+                        name: "Item".to_string(),
+                        crunchbase_url: Some(CB_URL.to_string()),
+                        maturity: Some("graduated".to_string()),
+                        repositories: Some(vec![Repository {
+                            url: REPO_URL.to_string(),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    },
+                    data::Item {
+                        id: "category--subcategory--other".to_string(),
+                        name: "Other".to_string(),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            landscape_url: "https://landscape.example.com".to_string(),
+        }
+    }
+
+    async fn execute(data: LoadedData, query: &str) -> async_graphql::Response {
+        build_schema().execute(async_graphql::Request::new(query).data(Arc::new(data))).await
+    }
+
+    #[tokio::test]
+    async fn graphql_nested_query_succeeds() {
+        let query = r#"{
+            items(filter: {maturity: "graduated"}) {
+                name
+                repositories { githubData { stars } }
+                organization { name permalink items(limit: 10) { id } }
+            }
+            itemsCount
+        }"#;
+        let resp = execute(loaded_data(), query).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        assert_eq!(
+            resp.data.into_json().unwrap(),
+            json!({
+                "items": [{
+                    "name": "Item",
+                    "repositories": [{"githubData": {"stars": 42}}],
+                    "organization": {
+                        "name": "Org",
+                        "permalink": "org",
+                        "items": [{"id": "category--subcategory--item"}],
+                    },
+                }],
+                "itemsCount": 2,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql_items_limit_is_capped() {
+        let mut data = loaded_data();
+        data.landscape_data.items = (0..MAX_PER_PAGE + 10)
+            .map(|i| data::Item {
+                id: format!("category--subcategory--item{i}"),
+                name: format!("Item{i}"),
+                ..Default::default()
+            })
+            .collect();
+
+        let resp = execute(data, "{ items(sort: NAME, order: DESC, limit: 1000) { id } }").await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        let json = resp.data.into_json().unwrap();
+        assert_eq!(json["items"].as_array().unwrap().len(), MAX_PER_PAGE);
+        assert_eq!(json["items"][0]["id"], "category--subcategory--item99");
+    }
+
+    #[tokio::test]
+    async fn graphql_nested_items_limit_is_capped() {
+        let mut data = loaded_data();
+        data.landscape_data.categories = vec![Category {
+            name: "Category".to_string(),
+            normalized_name: "category".to_string(),
+            subcategories: vec![Subcategory {
+                name: "Subcategory".to_string(),
+                normalized_name: "subcategory".to_string(),
+            }],
+        }];
+        data.landscape_data.items = (0..MAX_PER_PAGE + 10)
+            .map(|i| data::Item {
+                category: "Category".to_string(),
+                crunchbase_url: Some(CB_URL.to_string()),
+                id: format!("category--subcategory--item{i}"),
+                name: format!("Item{i}"),
+                subcategory: "Subcategory".to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let query = r"{
+            categories { items(limit: 1000) { id } subcategories { items { id } } }
+            organizations(limit: 1) { items(offset: 105) { id } }
+        }";
+        let resp = execute(data, query).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        let json = resp.data.into_json().unwrap();
+        let category = &json["categories"][0];
+        assert_eq!(category["items"].as_array().unwrap().len(), MAX_PER_PAGE);
+        assert_eq!(
+            category["subcategories"][0]["items"].as_array().unwrap().len(),
+            MAX_PER_PAGE
+        );
+        assert_eq!(json["organizations"][0]["items"].as_array().unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn graphql_query_too_deep_is_rejected() {
+        let mut query = "id".to_string();
+        for _ in 0..MAX_QUERY_DEPTH {
+            query = format!("items(limit: 1) {{ organization {{ {query} }} }}");
+        }
+        let query = format!("{{ {query} }}");
+
+        let resp = execute(loaded_data(), &query).await;
+        assert!(
+            resp.errors.iter().any(|err| err.message.contains("too deep")),
+            "{:?}",
+            resp.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql_query_too_complex_is_rejected() {
+        let fields: Vec<String> = (0..=MAX_QUERY_COMPLEXITY).map(|i| format!("c{i}: itemsCount")).collect();
+        let query = format!("{{ {} }}", fields.join(" "));
+
+        let resp = execute(loaded_data(), &query).await;
+        assert!(
+            resp.errors.iter().any(|err| err.message.contains("too complex")),
+            "{:?}",
+            resp.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql_query_nested_lists_too_complex_is_rejected() {
+        let query = "{ items { organization { items { id } } } }";
+
+        let resp = execute(loaded_data(), query).await;
+        assert!(
+            resp.errors.iter().any(|err| err.message.contains("too complex")),
+            "{:?}",
+            resp.errors
+        );
+    }
+}
@@ -1,30 +1,75 @@
 //! This module defines the functionality of the serve CLI subcommand.
 
-use std::{env, net::SocketAddr, path::PathBuf};
+use std::{
+    env,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use axum::{
     Router,
-    extract::Request,
-    http::{HeaderValue, header::CACHE_CONTROL},
+    extract::{Request, State},
+    http::{HeaderValue, Uri, header::CACHE_CONTROL},
     middleware::{self, Next},
-    response::IntoResponse,
+    response::{IntoResponse, Redirect, Response},
 };
+use clap::{Args, Command, Id};
+use landscape2_core::data::ItemsAliases;
 use tokio::{net::TcpListener, signal};
 use tower_http::services::{ServeDir, ServeFile};
 use tracing::{info, instrument};
+use url::form_urlencoded;
+
+use crate::build::BuildArgs;
+
+/// Query parameter used by the web application to select an item.
+const ITEM_PARAM: &str = "item";
+
+mod api;
+mod data;
+mod graphql;
+mod watch;
 
 /// Serve arguments.
+///
+/// The build arguments are only used (and required) in watch mode. Clap does
+/// not populate the group of flattened arguments that contain other flattened
+/// groups, so we set its members explicitly (all the build arguments) to
+/// detect when they are provided.
 #[derive(clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
+#[command(
+    mut_group("BuildArgs", |group| group.args(build_args_ids()).requires("watch")),
+    mut_arg("output_dir", |arg| arg.required(false)),
+    mut_group("DataSource", |group| group.required(false)),
+    mut_group("LogosSource", |group| group.required(false)),
+    mut_group("SettingsSource", |group| group.required(false))
+)]
 pub struct ServeArgs {
     /// Address the web server will listen on.
     #[arg(long, default_value = "127.0.0.1:8000")]
     pub addr: String,
 
+    /// Enable the query API, which allows filtering, sorting and paginating
+    /// the landscape items on the server (available at /api/items).
+    #[arg(long, default_value_t = false)]
+    pub api: bool,
+
+    /// Build arguments (used in watch mode).
+    #[command(flatten)]
+    pub build_args: Option<BuildArgs>,
+
     /// Whether the server should stop gracefully or not.
     #[arg(long, default_value_t = false)]
     pub graceful_shutdown: bool,
 
+    /// Enable the GraphQL endpoint, which allows running nested queries over
+    /// the landscape data (available at /graphql).
+    #[arg(long, default_value_t = false)]
+    pub graphql: bool,
+
     /// Location of the landscape website files (build subcommand output).
     /// The current path will be used when none is provided.
     #[arg(long)]
@@ -33,23 +78,73 @@ pub struct ServeArgs {
     /// Enable silent mode.
     #[arg(long, default_value_t = false)]
     pub silent: bool,
+
+    /// Build the landscape from the sources provided and rebuild it when any
+    /// of the local ones changes, reloading the browsers automatically.
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "landscape_dir",
+        requires_all = ["output_dir", "DataSource", "LogosSource", "SettingsSource"]
+    )]
+    pub watch: bool,
+}
+
+/// Return the ids of all the build arguments.
+fn build_args_ids() -> Vec<Id> {
+    BuildArgs::augment_args(Command::new("build"))
+        .get_arguments()
+        .map(|arg| arg.get_id().clone())
+        .collect()
 }
 
 /// Serve landscape website.
 ///
 /// # Errors
 /// Returns an error if the server cannot bind, start, or read the current
-/// directory when needed.
+/// directory when needed, as well as if the initial build fails in watch mode.
 #[instrument(skip_all)]
 pub async fn serve(args: &ServeArgs) -> Result<()> {
-    // Setup router
+    if !args.watch {
+        return serve_landscape(args).await;
+    }
+    let Some(build_args) = &args.build_args else {
+        bail!("the landscape build sources must be provided in watch mode");
+    };
+    watch::serve_and_watch(args, build_args).await
+}
+
+/// Serve the landscape website files available in the landscape directory.
+#[instrument(skip_all, err)]
+pub(crate) async fn serve_landscape(args: &ServeArgs) -> Result<()> {
     let landscape_dir = args.landscape_dir.clone().unwrap_or(env::current_dir()?);
+    let router = setup_router(&landscape_dir, args);
+    run_server(args, router).await
+}
+
+/// Setup router to serve the landscape website files in the directory
+/// provided, along with the query API and GraphQL endpoints when enabled.
+fn setup_router(landscape_dir: &Path, args: &ServeArgs) -> Router {
     let index_path = landscape_dir.join("index.html");
-    let router: Router<()> = Router::new()
-        .fallback_service(ServeDir::new(&landscape_dir).not_found_service(ServeFile::new(&index_path)))
-        .layer(middleware::from_fn(set_cache_control_header));
+    let loader = Arc::new(data::DataLoader::new(landscape_dir));
+    let mut router = Router::new();
+    if args.api {
+        router = router.merge(api::setup_api_router(loader.clone()));
+    }
+    if args.graphql {
+        router = router.merge(graphql::setup_graphql_router(loader));
+    }
+    router
+        .fallback_service(ServeDir::new(landscape_dir).not_found_service(ServeFile::new(&index_path)))
+        .layer(middleware::from_fn(set_cache_control_header))
+        .layer(middleware::from_fn_with_state(
+            Arc::new(data::AliasesLoader::new(landscape_dir)),
+            redirect_items_aliases,
+        ))
+}
 
-    // Setup and launch HTTP server
+/// Setup and launch HTTP server using the router provided.
+async fn run_server(args: &ServeArgs, router: Router) -> Result<()> {
     let addr: SocketAddr = args.addr.parse()?;
     let listener = TcpListener::bind(addr).await?;
     if !args.silent {
@@ -84,6 +179,52 @@ async fn set_cache_control_header(req: Request, next: Next) -> impl IntoResponse
     resp
 }
 
+/// Middleware that redirects the requests that refer to an item using one of
+/// its previous ids to the same location using its current id.
+async fn redirect_items_aliases(
+    State(aliases_loader): State<Arc<data::AliasesLoader>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if has_item_param(req.uri())
+        && let Some(location) = item_alias_location(req.uri(), &aliases_loader.aliases())
+    {
+        return Redirect::temporary(&location).into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Check if the uri provided includes the item parameter.
+fn has_item_param(uri: &Uri) -> bool {
+    uri.query()
+        .is_some_and(|query| form_urlencoded::parse(query.as_bytes()).any(|(key, _)| key == ITEM_PARAM))
+}
+
+/// Return the location the uri provided should be redirected to when its item
+/// parameter refers to one of the items aliases.
+fn item_alias_location(uri: &Uri, items_aliases: &ItemsAliases) -> Option<String> {
+    let query = uri.query()?;
+    let mut redirect = false;
+    let params: Vec<(String, String)> = form_urlencoded::parse(query.as_bytes())
+        .map(|(key, value)| {
+            if key == ITEM_PARAM
+                && let Some(id) = items_aliases.get(value.as_ref())
+            {
+                redirect = true;
+                return (key.into_owned(), id.clone());
+            }
+            (key.into_owned(), value.into_owned())
+        })
+        .collect();
+    if !redirect {
+        return None;
+    }
+    let query = form_urlencoded::Serializer::new(String::new()).extend_pairs(params).finish();
+
+    Some(format!("{}?{query}", uri.path()))
+}
+
 /// Return a future that will complete when the program is asked to stop via a
 /// ctrl+c or terminate signal.
 async fn shutdown_signal() {
@@ -109,3 +250,88 @@ async fn shutdown_signal() {
         () = terminate => {},
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser, error::ErrorKind};
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        serve: ServeArgs,
+    }
+
+    #[test]
+    fn serve_args_build_args_group_includes_all_build_args() {
+        let cmd = Cli::command();
+        let group = cmd.get_groups().find(|group| group.get_id() == "BuildArgs").unwrap();
+        let group_args: Vec<&Id> = group.get_args().collect();
+        let build_args = BuildArgs::augment_args(Command::new("build"));
+        for arg in build_args.get_arguments() {
+            assert!(
+                group_args.contains(&arg.get_id()),
+                "{} not in group",
+                arg.get_id()
+            );
+        }
+    }
+
+    #[test]
+    fn serve_args_build_args_require_watch() {
+        let build_args = BuildArgs::augment_args(Command::new("build"));
+        for arg in build_args.get_arguments() {
+            let mut argv = vec!["serve".to_string(), format!("--{}", arg.get_long().unwrap())];
+            if arg.get_action().takes_values() {
+                argv.push("value".to_string());
+            }
+            let Err(err) = Cli::try_parse_from(&argv) else {
+                panic!("{argv:?} accepted without --watch");
+            };
+            assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument, "{argv:?}");
+            assert!(err.to_string().contains("--watch"), "{argv:?}");
+        }
+
+        let cli = Cli::try_parse_from([
+            "serve",
+            "--watch",
+            "--data-file",
+            "data.yml",
+            "--logos-path",
+            "logos",
+            "--output-dir",
+            "build",
+            "--settings-file",
+            "settings.yml",
+        ])
+        .unwrap();
+        assert!(cli.serve.build_args.is_some());
+    }
+
+    #[test]
+    fn has_item_param_succeeds() {
+        let uri: Uri = "/embed?theme=dark&item=c--s1--item".parse().unwrap();
+        assert!(has_item_param(&uri));
+
+        let uri: Uri = "/?items_view=grid&group=item".parse().unwrap();
+        assert!(!has_item_param(&uri));
+
+        let uri: Uri = "/item".parse().unwrap();
+        assert!(!has_item_param(&uri));
+    }
+
+    #[test]
+    fn item_alias_location_succeeds() {
+        let items_aliases = ItemsAliases::from([("c--s1--item".to_string(), "c--s2--item".to_string())]);
+
+        let uri: Uri = "/embed?item=c--s1--item&theme=dark".parse().unwrap();
+        assert_eq!(
+            item_alias_location(&uri, &items_aliases),
+            Some("/embed?item=c--s2--item&theme=dark".to_string())
+        );
+
+        let uri: Uri = "/?item=c--s2--item".parse().unwrap();
+        assert_eq!(item_alias_location(&uri, &items_aliases), None);
+    }
+}
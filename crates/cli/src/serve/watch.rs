@@ -0,0 +1,310 @@
+//! This module provides the functionality used by the serve subcommand when
+//! running in watch mode. In this mode the landscape is rebuilt every time any
+//! of the local sources changes, and the browsers displaying it are notified
+//! so that they reload the page.
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{StatusCode, header::CONTENT_LENGTH, header::CONTENT_TYPE},
+    middleware::{self, Next},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use futures::stream::{self, Stream};
+use tokio::{sync::broadcast, time::sleep};
+use tracing::{debug, error, info, instrument, warn};
+use walkdir::WalkDir;
+
+use crate::build::{BuildArgs, build};
+
+use super::{ServeArgs, run_server, setup_router};
+
+/// Path of the endpoint used to notify browsers when the landscape is rebuilt.
+const LIVERELOAD_PATH: &str = "/_livereload";
+
+/// Script injected in the html documents served in watch mode. It subscribes
+/// to the live reload events and reloads the page when one is received.
+const LIVERELOAD_SCRIPT: &str = r#"<script>new EventSource("/_livereload").addEventListener("reload", () => window.location.reload());</script>"#;
+
+/// How often the sources are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Type alias to represent a snapshot of the state of the sources files.
+type Snapshot = BTreeMap<PathBuf, (SystemTime, u64)>;
+
+/// Build the landscape and serve it, rebuilding it when any of the local
+/// sources changes.
+pub(super) async fn serve_and_watch(args: &ServeArgs, build_args: &BuildArgs) -> Result<()> {
+    // Build landscape
+    build(build_args).await?;
+
+    // Setup router, adding live reload support
+    let (reload_tx, _) = broadcast::channel(1);
+    let router = setup_router(&build_args.output_dir, args)
+        .route(LIVERELOAD_PATH, get(livereload).with_state(reload_tx.clone()))
+        .layer(middleware::from_fn(inject_livereload_script));
+
+    // Launch HTTP server and watch sources for changes
+    tokio::select! {
+        res = run_server(args, router) => res,
+        res = watch_sources(build_args, reload_tx) => res,
+    }
+}
+
+/// Watch the local sources used to build the landscape, rebuilding it when any
+/// of them changes and sending a reload notification after each successful
+/// build.
+#[instrument(skip_all, err)]
+async fn watch_sources(build_args: &BuildArgs, reload_tx: broadcast::Sender<()>) -> Result<()> {
+    let paths = get_sources_paths(build_args);
+    if paths.is_empty() {
+        warn!("no local sources provided, changes won't be detected");
+        return Ok(());
+    }
+    debug!(?paths, "watching sources");
+
+    // The output directory is ignored, as it may be located inside any of the
+    // sources paths (i.e. logos path set to the current directory)
+    let output_dir = fs::canonicalize(&build_args.output_dir).ok();
+    let output_dir = output_dir.as_deref();
+
+    let mut snapshot = take_snapshot(&paths, output_dir);
+    loop {
+        sleep(POLL_INTERVAL).await;
+
+        // Check if any of the sources has changed
+        let mut new_snapshot = take_snapshot(&paths, output_dir);
+        if new_snapshot == snapshot {
+            continue;
+        }
+
+        // Wait until the changes settle (editors may write files in several
+        // steps, and several files can be updated at once)
+        loop {
+            sleep(POLL_INTERVAL).await;
+            let latest_snapshot = take_snapshot(&paths, output_dir);
+            if latest_snapshot == new_snapshot {
+                break;
+            }
+            new_snapshot = latest_snapshot;
+        }
+        snapshot = new_snapshot;
+
+        // Rebuild landscape and notify browsers
+        info!("changes detected, rebuilding landscape..");
+        if let Err(err) = build(build_args).await {
+            error!(?err, "error rebuilding landscape");
+            continue;
+        }
+        _ = reload_tx.send(());
+    }
+}
+
+/// Return the paths of the local sources provided in the build arguments.
+fn get_sources_paths(build_args: &BuildArgs) -> Vec<PathBuf> {
+    [
+        build_args.data_source.data_file.as_ref(),
+        build_args.games_source.games_file.as_ref(),
+        build_args.guide_source.guide_file.as_ref(),
+        build_args.logos_source.logos_path.as_ref(),
+        build_args.settings_source.settings_file.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .cloned()
+    .collect()
+}
+
+/// Take a snapshot of the modification time and size of all the files in the
+/// paths provided (directories are walked recursively). The content of the
+/// ignored directory provided (which must be canonicalized) is skipped.
+fn take_snapshot(paths: &[PathBuf], ignored_dir: Option<&Path>) -> Snapshot {
+    let is_ignored = |entry: &walkdir::DirEntry| {
+        let Some(ignored_dir) = ignored_dir else {
+            return false;
+        };
+        entry.file_type().is_dir() && fs::canonicalize(entry.path()).is_ok_and(|path| path == ignored_dir)
+    };
+
+    let mut snapshot = Snapshot::new();
+    for path in paths {
+        let entries =
+            WalkDir::new(path).follow_links(true).into_iter().filter_entry(|entry| !is_ignored(entry));
+        for entry in entries.filter_map(Result::ok) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            snapshot.insert(entry.into_path(), (modified, metadata.len()));
+        }
+    }
+    snapshot
+}
+
+/// Handler that streams a reload event to the browser every time the
+/// landscape is rebuilt.
+async fn livereload(
+    State(reload_tx): State<broadcast::Sender<()>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = stream::unfold(reload_tx.subscribe(), |mut reload_rx| async move {
+        match reload_rx.recv().await {
+            Ok(()) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                let event = Event::default().event("reload").data("");
+                Some((Ok(event), reload_rx))
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Middleware that injects the live reload script in the html documents.
+async fn inject_livereload_script(req: Request, next: Next) -> Response {
+    // Execute next handler
+    let resp = next.run(req).await;
+
+    // Only successful html responses are modified
+    let is_html = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/html"));
+    if resp.status() != StatusCode::OK || !is_html {
+        return resp;
+    }
+
+    // Inject script before the closing body tag (or at the end of the document)
+    let (mut parts, body) = resp.into_parts();
+    let Ok(body) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let mut html = String::from_utf8_lossy(&body).into_owned();
+    if let Some(pos) = html.rfind("</body>") {
+        html.insert_str(pos, LIVERELOAD_SCRIPT);
+    } else {
+        html.push_str(LIVERELOAD_SCRIPT);
+    }
+    parts.headers.remove(CONTENT_LENGTH);
+
+    Response::from_parts(parts, Body::from(html))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use tempfile::tempdir;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    /// Return the body of the response generated by a handler that returns
+    /// the status, content type and body provided, when the live reload
+    /// middleware is enabled.
+    async fn get_with_livereload(
+        status: StatusCode,
+        content_type: &'static str,
+        body: &'static str,
+    ) -> String {
+        let router = Router::new()
+            .route(
+                "/",
+                get(move || async move { (status, [(CONTENT_TYPE, content_type)], body) }),
+            )
+            .layer(middleware::from_fn(inject_livereload_script));
+        let resp = router.oneshot(Request::builder().uri("/").body(Body::empty()).unwrap()).await.unwrap();
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn take_snapshot_detects_changes() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("logos")).unwrap();
+        fs::write(dir.join("data.yml"), b"data").unwrap();
+        fs::write(dir.join("logos").join("logo.svg"), b"<svg/>").unwrap();
+        let paths = vec![dir.join("data.yml"), dir.join("logos")];
+
+        // Snapshot includes all files, and it's stable when nothing changes
+        let snapshot = take_snapshot(&paths, None);
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(take_snapshot(&paths, None), snapshot);
+
+        // Files updated (size changed) or added are detected
+        fs::write(dir.join("data.yml"), b"data updated").unwrap();
+        let updated_snapshot = take_snapshot(&paths, None);
+        assert_ne!(updated_snapshot, snapshot);
+        fs::write(dir.join("logos").join("new.svg"), b"<svg/>").unwrap();
+        let added_snapshot = take_snapshot(&paths, None);
+        assert_eq!(added_snapshot.len(), 3);
+
+        // Files removed are detected
+        fs::remove_file(dir.join("logos").join("new.svg")).unwrap();
+        assert_eq!(take_snapshot(&paths, None), updated_snapshot);
+    }
+
+    #[test]
+    fn take_snapshot_ignores_output_dir() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("build").join("data")).unwrap();
+        fs::write(dir.join("logo.svg"), b"<svg/>").unwrap();
+        fs::write(dir.join("build").join("index.html"), b"index").unwrap();
+        let paths = vec![dir.to_path_buf()];
+        let output_dir = fs::canonicalize(dir.join("build")).unwrap();
+
+        // Files in the output directory are not included in the snapshot
+        let snapshot = take_snapshot(&paths, Some(&output_dir));
+        assert_eq!(snapshot.keys().collect::<Vec<_>>(), vec![&dir.join("logo.svg")]);
+
+        // Changes in the output directory are not detected
+        fs::write(dir.join("build").join("data").join("full.json"), b"{}").unwrap();
+        assert_eq!(take_snapshot(&paths, Some(&output_dir)), snapshot);
+        assert_eq!(take_snapshot(&paths, None).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn inject_livereload_script_before_closing_body_tag() {
+        let body =
+            get_with_livereload(StatusCode::OK, "text/html", "<html><body>landscape</body></html>").await;
+        assert_eq!(
+            body,
+            format!("<html><body>landscape{LIVERELOAD_SCRIPT}</body></html>")
+        );
+    }
+
+    #[tokio::test]
+    async fn inject_livereload_script_without_closing_body_tag() {
+        let body = get_with_livereload(StatusCode::OK, "text/html; charset=utf-8", "<p>landscape</p>").await;
+        assert_eq!(body, format!("<p>landscape</p>{LIVERELOAD_SCRIPT}"));
+    }
+
+    #[tokio::test]
+    async fn inject_livereload_script_skips_non_html_responses() {
+        let body = get_with_livereload(StatusCode::OK, "application/json", r#"{"body": "</body>"}"#).await;
+        assert_eq!(body, r#"{"body": "</body>"}"#);
+    }
+
+    #[tokio::test]
+    async fn inject_livereload_script_skips_unsuccessful_responses() {
+        let body = get_with_livereload(StatusCode::NOT_FOUND, "text/html", "<body></body>").await;
+        assert_eq!(body, "<body></body>");
+    }
+}
@@ -0,0 +1,313 @@
+//! This module provides the functionality used to check that the links of the
+//! landscape items are not broken (online link checking mode).
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use landscape2_core::{
+    data::{Item, LandscapeData},
+    validation::ValidationReport,
+};
+use leaky_bucket::RateLimiter;
+use reqwest::StatusCode;
+use tracing::{instrument, warn};
+use url::Url;
+
+use crate::build::cache::Cache;
+
+/// File used to cache the links checked successfully.
+const LINKS_CACHE_FILE: &str = "links.json";
+
+/// How long a successful link check in the cache is valid (in days).
+const LINKS_CACHE_TTL: i64 = 3;
+
+/// Maximum number of links checked concurrently.
+const LINKS_CONCURRENCY: usize = 20;
+
+/// Interval for the rate limiters used when sending requests to each host.
+const LINKS_HOST_RATE_LIMITER_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Timeout used for each of the link check requests.
+const LINKS_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Domains used by domain parking services.
+const PARKED_DOMAINS: [&str; 9] = [
+    "afternic.com",
+    "bodis.com",
+    "dan.com",
+    "domainmarket.com",
+    "hugedomains.com",
+    "parkingcrew.net",
+    "sedo.com",
+    "sedoparking.com",
+    "undeveloped.com",
+];
+
+/// Type alias to represent the links cache (url -> last successful check).
+type LinksCache = BTreeMap<String, DateTime<Utc>>;
+
+/// Result of checking a link.
+#[derive(Debug, Clone, PartialEq)]
+enum LinkStatus {
+    /// The link works.
+    Ok,
+    /// The link is broken (reported as an error).
+    Broken(String),
+    /// The link may be broken, but it could also be a transient problem or the
+    /// site blocking automated requests (reported as a warning).
+    Suspicious(String),
+}
+
+/// Check the links of all the items in the landscape, returning a validation
+/// report with the ones that are broken. Links checked successfully recently
+/// are cached and not checked again.
+#[instrument(skip_all, err)]
+pub(crate) async fn check_links(cache: &Cache, landscape_data: &LandscapeData) -> Result<ValidationReport> {
+    // Read cached data (if available)
+    let mut cached_links = LinksCache::new();
+    match cache.read(LINKS_CACHE_FILE) {
+        Ok(Some((_, json_data))) => match serde_json::from_slice(&json_data) {
+            Ok(links) => cached_links = links,
+            Err(err) => warn!("error parsing links cache file: {err:?}"),
+        },
+        Ok(None) => {}
+        Err(err) => warn!("error reading links cache file: {err:?}"),
+    }
+    cached_links.retain(|_, checked_at| *checked_at + chrono::Duration::days(LINKS_CACHE_TTL) > Utc::now());
+
+    // Collect the links that need to be checked
+    let links: Vec<(&Item, &str, &str)> = landscape_data
+        .items
+        .iter()
+        .flat_map(|item| get_item_links(item).into_iter().map(move |(kind, url)| (item, kind, url)))
+        .collect();
+    let urls: BTreeSet<&str> = links
+        .iter()
+        .map(|(_, _, url)| *url)
+        .filter(|url| !cached_links.contains_key(*url))
+        .collect();
+
+    // Setup a rate limiter for each of the hosts
+    let limiters: HashMap<String, RateLimiter> = urls
+        .iter()
+        .filter_map(|url| Url::parse(url).ok()?.host_str().map(ToString::to_string))
+        .map(|host| {
+            let limiter = RateLimiter::builder()
+                .initial(1)
+                .max(1)
+                .interval(LINKS_HOST_RATE_LIMITER_INTERVAL)
+                .build();
+            (host, limiter)
+        })
+        .collect();
+
+    // Check links
+    let user_agent = format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let http_client =
+        reqwest::Client::builder().user_agent(user_agent).timeout(LINKS_REQUEST_TIMEOUT).build()?;
+    let (http_client, limiters) = (&http_client, &limiters);
+    let results: BTreeMap<&str, LinkStatus> = stream::iter(urls)
+        .map(|url| async move {
+            let host = Url::parse(url).ok().and_then(|url| url.host_str().map(ToString::to_string));
+            if let Some(limiter) = host.and_then(|host| limiters.get(&host)) {
+                limiter.acquire_one().await;
+            }
+            (url, check_link(http_client, url).await)
+        })
+        .buffer_unordered(LINKS_CONCURRENCY)
+        .collect()
+        .await;
+
+    // Prepare report
+    let mut report = ValidationReport::new();
+    for (item, kind, url) in links {
+        let path = [&item.category, &item.subcategory, &item.name];
+        match results.get(url) {
+            Some(LinkStatus::Broken(reason)) => {
+                report.add_error(&path, format!("{kind} link {url} is broken: {reason}"));
+            }
+            Some(LinkStatus::Suspicious(reason)) => {
+                report.add_warning(&path, format!("{kind} link {url} may be broken: {reason}"));
+            }
+            Some(LinkStatus::Ok) | None => {}
+        }
+    }
+
+    // Write links checked successfully to cache
+    let now = Utc::now();
+    for (url, status) in results {
+        if status == LinkStatus::Ok {
+            cached_links.insert(url.to_string(), now);
+        }
+    }
+    cache.write(LINKS_CACHE_FILE, &serde_json::to_vec_pretty(&cached_links)?)?;
+
+    Ok(report)
+}
+
+/// Check the link provided.
+async fn check_link(http_client: &reqwest::Client, url: &str) -> LinkStatus {
+    match http_client.get(url).send().await {
+        Ok(resp) => {
+            if let Some(host) = resp.url().host_str()
+                && is_parked_domain(host)
+            {
+                return LinkStatus::Broken(format!("redirects to a parked domain ({host})"));
+            }
+            match resp.status() {
+                status if status.is_success() => LinkStatus::Ok,
+                status @ (StatusCode::NOT_FOUND | StatusCode::GONE) => {
+                    LinkStatus::Broken(format!("unexpected status code: {status}"))
+                }
+                status => LinkStatus::Suspicious(format!("unexpected status code: {status}")),
+            }
+        }
+        Err(err) if err.is_timeout() => LinkStatus::Suspicious("request timed out".to_string()),
+        Err(err) => {
+            let cause = root_cause(&err);
+            if is_tls_error(&err) {
+                LinkStatus::Broken(format!("tls error: {cause}"))
+            } else {
+                LinkStatus::Broken(format!("request failed: {cause}"))
+            }
+        }
+    }
+}
+
+/// Return the links of the item provided that should be checked.
+///
+/// NOTE: links to some social networks are not checked, as they usually block
+/// automated requests.
+fn get_item_links(item: &Item) -> Vec<(&'static str, &str)> {
+    let mut links = vec![("homepage", Some(&item.homepage_url))];
+    links.extend([
+        ("artwork", item.artwork_url.as_ref()),
+        ("blog", item.blog_url.as_ref()),
+        ("chat channel", item.chat_channel.as_ref()),
+        ("devstats", item.devstats_url.as_ref()),
+        ("discord", item.discord_url.as_ref()),
+        ("docker", item.docker_url.as_ref()),
+        ("documentation", item.documentation_url.as_ref()),
+        ("funding", item.funding_url.as_ref()),
+        ("github discussions", item.github_discussions_url.as_ref()),
+        ("gitter", item.gitter_url.as_ref()),
+        ("latest annual review", item.latest_annual_review_url.as_ref()),
+        ("mailing list", item.mailing_list_url.as_ref()),
+        ("openssf best practices", item.openssf_best_practices_url.as_ref()),
+        ("package manager", item.package_manager_url.as_ref()),
+        ("slack", item.slack_url.as_ref()),
+        ("stack overflow", item.stack_overflow_url.as_ref()),
+        ("youtube", item.youtube_url.as_ref()),
+    ]);
+    for repo in item.repositories.iter().flatten() {
+        links.push(("repository", Some(&repo.url)));
+    }
+    for audit in item.audits.iter().flatten() {
+        links.push(("audit", Some(&audit.url)));
+    }
+    for link in item.other_links.iter().flatten() {
+        links.push(("other", Some(&link.url)));
+    }
+
+    links
+        .into_iter()
+        .filter_map(|(kind, url)| url.map(|url| (kind, url.as_str())))
+        .filter(|(_, url)| url.starts_with("http://") || url.starts_with("https://"))
+        .collect()
+}
+
+/// Check if the host provided belongs to a domain parking service.
+fn is_parked_domain(host: &str) -> bool {
+    PARKED_DOMAINS
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{domain}")))
+}
+
+/// Check if the error provided was caused by a TLS problem.
+fn is_tls_error(err: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn Error> = Some(err);
+    while let Some(err) = source {
+        let msg = err.to_string().to_lowercase();
+        if msg.contains("certificate") || msg.contains("tls") || msg.contains("ssl") {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// Return the message of the root cause of the error provided.
+fn root_cause(err: &reqwest::Error) -> String {
+    let mut cause: &dyn Error = err;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn check_link_ok() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/ok").with_status(200).create_async().await;
+
+        let status = check_link(&reqwest::Client::new(), &format!("{}/ok", server.url())).await;
+        assert_eq!(status, LinkStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn check_link_redirect_ok() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/old")
+            .with_status(301)
+            .with_header("location", "/new")
+            .create_async()
+            .await;
+        server.mock("GET", "/new").with_status(200).create_async().await;
+
+        let status = check_link(&reqwest::Client::new(), &format!("{}/old", server.url())).await;
+        assert_eq!(status, LinkStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn check_link_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/missing").with_status(404).create_async().await;
+
+        let status = check_link(&reqwest::Client::new(), &format!("{}/missing", server.url())).await;
+        assert_eq!(
+            status,
+            LinkStatus::Broken("unexpected status code: 404 Not Found".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn check_link_forbidden() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/forbidden").with_status(403).create_async().await;
+
+        let status = check_link(&reqwest::Client::new(), &format!("{}/forbidden", server.url())).await;
+        assert_eq!(
+            status,
+            LinkStatus::Suspicious("unexpected status code: 403 Forbidden".to_string())
+        );
+    }
+
+    #[test]
+    fn is_parked_domain_matches_domain_and_subdomains() {
+        assert!(is_parked_domain("sedoparking.com"));
+        assert!(is_parked_domain("www.hugedomains.com"));
+        assert!(!is_parked_domain("notsedoparking.com"));
+        assert!(!is_parked_domain("example.com"));
+    }
+}
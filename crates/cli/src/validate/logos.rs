@@ -0,0 +1,63 @@
+//! This module provides the functionality used to audit the landscape items
+//! logos from the validate subcommand.
+
+use futures::stream::{self, StreamExt};
+use landscape2_core::{data::LandscapeData, validation::ValidationReport};
+use tracing::instrument;
+
+use crate::build::logos::{LogosSource, audit_logo, get_logo};
+
+/// Maximum number of logos audited concurrently.
+const AUDIT_LOGOS_CONCURRENCY: usize = 10;
+
+/// Audit the logos of all the items in the landscape, returning a validation
+/// report with the problems found. Problems are reported as warnings, unless
+/// strict mode is enabled.
+#[instrument(skip_all)]
+pub(crate) async fn audit_logos(
+    landscape_data: &LandscapeData,
+    logos_source: &LogosSource,
+    strict: bool,
+) -> ValidationReport {
+    let http_client = reqwest::Client::new();
+    let results: Vec<_> = stream::iter(&landscape_data.items)
+        .map(|item| {
+            let http_client = http_client.clone();
+            async move {
+                let problems = match get_logo(http_client, logos_source, &item.logo).await {
+                    Ok(logo_data) => {
+                        let file_name = item.logo.clone();
+                        tokio::task::spawn_blocking(move || audit_logo(&logo_data, &file_name))
+                            .await
+                            .map_err(|err| format!("error auditing logo: {err}"))
+                    }
+                    Err(err) => Err(format!("error getting logo: {err:#}")),
+                };
+                (item, problems)
+            }
+        })
+        .buffer_unordered(AUDIT_LOGOS_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut report = ValidationReport::new();
+    for (item, problems) in results {
+        let path = [&item.category, &item.subcategory, &item.name];
+        match problems {
+            Ok(problems) => {
+                for problem in problems {
+                    let message = format!("{problem} ({})", item.logo);
+                    if strict {
+                        report.add_error(&path, message);
+                    } else {
+                        report.add_warning(&path, message);
+                    }
+                }
+            }
+            Err(err) => report.add_error(&path, format!("{err} ({})", item.logo)),
+        }
+    }
+    report.errors.sort_by(|a, b| a.path.cmp(&b.path));
+
+    report
+}
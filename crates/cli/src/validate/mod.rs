@@ -1,29 +1,44 @@
 //! This module defines the functionality of the validate CLI subcommand.
 
-use anyhow::{Context, Result};
-use clap::Subcommand;
+use std::{fs, path::PathBuf};
+
+use anyhow::{Result, format_err};
+use clap::{Subcommand, ValueEnum};
 use landscape2_core::{
     data::{DataSource, LandscapeData},
     games::{GamesSource, LandscapeGames},
     guide::{GuideSource, LandscapeGuide},
     settings::{LandscapeSettings, SettingsSource},
+    validation::{Severity, ValidationReport, check_cross_references},
 };
+use serde::Serialize;
 use tracing::instrument;
 
+use crate::build::{cache::Cache, logos::LogosSource};
+
+mod links;
+mod logos;
+
 /// Validate command arguments.
 #[derive(clap::Args)]
-#[command(args_conflicts_with_subcommands = true)]
 pub struct ValidateArgs {
     /// Landscape file to validate.
     #[command(subcommand)]
     pub target: Target,
+
+    /// Output format.
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
 }
 
 /// Landscape file to validate.
 #[derive(Subcommand)]
 pub enum Target {
+    /// Validate all landscape files, checking also the references between them.
+    All(ValidateAllArgs),
+
     /// Validate landscape data file.
-    Data(DataSource),
+    Data(ValidateDataArgs),
 
     /// Validate landscape games file.
     Games(GamesSource),
@@ -31,22 +46,173 @@ pub enum Target {
     /// Validate landscape guide file.
     Guide(GuideSource),
 
+    /// Audit landscape items logos.
+    Logos(ValidateLogosArgs),
+
     /// Validate landscape settings file.
     Settings(SettingsSource),
 }
 
+/// Validate data command arguments.
+#[derive(clap::Args)]
+pub struct ValidateDataArgs {
+    /// Cache directory (used when checking links).
+    #[arg(long, requires = "check_links")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Check that the items links are not broken (requires network access).
+    #[arg(long, default_value_t = false)]
+    pub check_links: bool,
+
+    /// Data source.
+    #[command(flatten)]
+    pub data_source: DataSource,
+}
+
+/// Validate all command arguments.
+#[derive(clap::Args)]
+pub struct ValidateAllArgs {
+    /// Data source.
+    #[command(flatten)]
+    pub data_source: DataSource,
+
+    /// Games source.
+    #[command(flatten)]
+    pub games_source: GamesSource,
+
+    /// Guide source.
+    #[command(flatten)]
+    pub guide_source: GuideSource,
+
+    /// Settings source.
+    #[command(flatten)]
+    pub settings_source: SettingsSource,
+}
+
+/// Validate logos command arguments.
+#[derive(clap::Args)]
+pub struct ValidateLogosArgs {
+    /// Data source.
+    #[command(flatten)]
+    pub data_source: DataSource,
+
+    /// Logos source.
+    #[command(flatten)]
+    pub logos_source: LogosSource,
+
+    /// Report the problems found in the logos as errors instead of warnings.
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+}
+
+/// Format used to output the validation results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// GitHub Actions workflow commands (annotations).
+    Github,
+    /// JSON document with all the diagnostics.
+    Json,
+    /// Human readable text.
+    Text,
+}
+
+/// Validate all landscape files, checking also that the references between
+/// them (i.e. settings or guide referring to data categories) are consistent.
+///
+/// # Errors
+/// Returns an error if any of the landscape files is invalid or cannot be read.
+#[instrument(skip_all)]
+pub async fn validate_all(args: &ValidateAllArgs, format: Format) -> Result<()> {
+    let mut diagnostics = vec![];
+
+    // Validate each of the files
+    let data_file = source_location(
+        args.data_source.data_file.as_ref(),
+        args.data_source.data_url.as_ref(),
+    );
+    let landscape_data = LandscapeData::new(&args.data_source).await;
+    diagnostics.extend(get_diagnostics(&data_file, &landscape_data));
+
+    let games_file = source_location(
+        args.games_source.games_file.as_ref(),
+        args.games_source.games_url.as_ref(),
+    );
+    let games = LandscapeGames::new(&args.games_source).await;
+    diagnostics.extend(get_diagnostics(&games_file, &games));
+
+    let guide_file = source_location(
+        args.guide_source.guide_file.as_ref(),
+        args.guide_source.guide_url.as_ref(),
+    );
+    let guide = LandscapeGuide::new(&args.guide_source).await;
+    diagnostics.extend(get_diagnostics(&guide_file, &guide));
+
+    let settings_file = source_location(
+        args.settings_source.settings_file.as_ref(),
+        args.settings_source.settings_url.as_ref(),
+    );
+    let settings = LandscapeSettings::new(&args.settings_source).await;
+    diagnostics.extend(get_diagnostics(&settings_file, &settings));
+
+    // Check references between files (only possible when data and settings
+    // files could be loaded)
+    if let (Ok(landscape_data), Ok(settings)) = (&landscape_data, &settings) {
+        let guide = guide.as_ref().ok().and_then(Option::as_ref);
+        let games = games.as_ref().ok().and_then(Option::as_ref);
+        let mut report = check_cross_references(landscape_data, settings, guide, games);
+
+        for (file, local_file, report) in [
+            (
+                &games_file,
+                args.games_source.games_file.as_ref(),
+                &mut report.games,
+            ),
+            (
+                &guide_file,
+                args.guide_source.guide_file.as_ref(),
+                &mut report.guide,
+            ),
+            (
+                &settings_file,
+                args.settings_source.settings_file.as_ref(),
+                &mut report.settings,
+            ),
+        ] {
+            if let Some(raw_data) = local_file.and_then(|path| fs::read_to_string(path).ok()) {
+                report.set_locations(&raw_data);
+            }
+            diagnostics.extend(get_report_diagnostics(file, report));
+        }
+    }
+
+    output_results("all", &diagnostics, format)
+}
+
 /// Validate landscape data file.
 ///
 /// # Errors
 /// Returns an error if the landscape data file is invalid or cannot be read.
 #[instrument(skip_all)]
-pub async fn validate_data(data_source: &DataSource) -> Result<()> {
-    LandscapeData::new(data_source)
-        .await
-        .context("the landscape data file provided is not valid")?;
+pub async fn validate_data(args: &ValidateDataArgs, format: Format) -> Result<()> {
+    let data_source = &args.data_source;
+    let file = source_location(data_source.data_file.as_ref(), data_source.data_url.as_ref());
+    let result = LandscapeData::new(data_source).await;
+    let mut diagnostics = get_diagnostics(&file, &result);
 
-    println!("The landscape data file provided is valid!");
-    Ok(())
+    // Check items links if requested
+    if args.check_links
+        && let Ok(landscape_data) = &result
+    {
+        let cache = Cache::new(args.cache_dir.as_ref())?;
+        let mut report = links::check_links(&cache, landscape_data).await?;
+        if let Some(raw_data) = data_source.data_file.as_ref().and_then(|path| fs::read_to_string(path).ok())
+        {
+            report.set_locations(&raw_data);
+        }
+        diagnostics.extend(get_report_diagnostics(&file, &report));
+    }
+
+    output_results("data", &diagnostics, format)
 }
 
 /// Validate landscape games file.
@@ -54,13 +220,10 @@ pub async fn validate_data(data_source: &DataSource) -> Result<()> {
 /// # Errors
 /// Returns an error if the landscape games file is invalid or cannot be read.
 #[instrument(skip_all)]
-pub async fn validate_games(games_source: &GamesSource) -> Result<()> {
-    LandscapeGames::new(games_source)
-        .await
-        .context("the landscape games file provided is not valid")?;
-
-    println!("The landscape games file provided is valid!");
-    Ok(())
+pub async fn validate_games(games_source: &GamesSource, format: Format) -> Result<()> {
+    let file = source_location(games_source.games_file.as_ref(), games_source.games_url.as_ref());
+    let result = LandscapeGames::new(games_source).await;
+    output_results("games", &get_diagnostics(&file, &result), format)
 }
 
 /// Validate landscape guide file.
@@ -68,13 +231,34 @@ pub async fn validate_games(games_source: &GamesSource) -> Result<()> {
 /// # Errors
 /// Returns an error if the landscape guide file is invalid or cannot be read.
 #[instrument(skip_all)]
-pub async fn validate_guide(guide_source: &GuideSource) -> Result<()> {
-    LandscapeGuide::new(guide_source)
-        .await
-        .context("the landscape guide file provided is not valid")?;
+pub async fn validate_guide(guide_source: &GuideSource, format: Format) -> Result<()> {
+    let file = source_location(guide_source.guide_file.as_ref(), guide_source.guide_url.as_ref());
+    let result = LandscapeGuide::new(guide_source).await;
+    output_results("guide", &get_diagnostics(&file, &result), format)
+}
 
-    println!("The landscape guide file provided is valid!");
-    Ok(())
+/// Audit landscape items logos.
+///
+/// # Errors
+/// Returns an error if the landscape data file is invalid or cannot be read, or
+/// if any problems are found in the logos in strict mode.
+#[instrument(skip_all)]
+pub async fn validate_logos(args: &ValidateLogosArgs, format: Format) -> Result<()> {
+    let data_source = &args.data_source;
+    let file = source_location(data_source.data_file.as_ref(), data_source.data_url.as_ref());
+    let result = LandscapeData::new(data_source).await;
+    let mut diagnostics = get_diagnostics(&file, &result);
+
+    if let Ok(landscape_data) = &result {
+        let mut report = logos::audit_logos(landscape_data, &args.logos_source, args.strict).await;
+        if let Some(raw_data) = data_source.data_file.as_ref().and_then(|path| fs::read_to_string(path).ok())
+        {
+            report.set_locations(&raw_data);
+        }
+        diagnostics.extend(get_report_diagnostics(&file, &report));
+    }
+
+    output_results("logos", &diagnostics, format)
 }
 
 /// Validate landscape settings file.
@@ -82,11 +266,215 @@ pub async fn validate_guide(guide_source: &GuideSource) -> Result<()> {
 /// # Errors
 /// Returns an error if the landscape settings file is invalid or cannot be read.
 #[instrument(skip_all)]
-pub async fn validate_settings(settings_source: &SettingsSource) -> Result<()> {
-    LandscapeSettings::new(settings_source)
-        .await
-        .context("the landscape settings file provided is not valid")?;
+pub async fn validate_settings(settings_source: &SettingsSource, format: Format) -> Result<()> {
+    let file = source_location(
+        settings_source.settings_file.as_ref(),
+        settings_source.settings_url.as_ref(),
+    );
+    let result = LandscapeSettings::new(settings_source).await;
+    output_results("settings", &get_diagnostics(&file, &result), format)
+}
 
-    println!("The landscape settings file provided is valid!");
+/// Diagnostic produced when validating a landscape file.
+#[derive(Debug, Clone, Serialize)]
+struct Diagnostic {
+    severity: Severity,
+    file: String,
+    path: Vec<String>,
+    message: String,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+/// Validation results, as output in JSON format.
+#[derive(Debug, Serialize)]
+struct ValidationResults<'a> {
+    valid: bool,
+    diagnostics: &'a [Diagnostic],
+}
+
+/// Output the results of the validation of the landscape files in the format
+/// provided, returning an error if any errors were found.
+fn output_results(kind: &str, diagnostics: &[Diagnostic], format: Format) -> Result<()> {
+    let subject = match kind {
+        "all" => "files provided are".to_string(),
+        "logos" => "logos provided are".to_string(),
+        _ => format!("{kind} file provided is"),
+    };
+    let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    let warnings = diagnostics.len() - errors;
+
+    match format {
+        Format::Github => {
+            for diagnostic in diagnostics {
+                println!("{}", github_annotation(diagnostic));
+            }
+        }
+        Format::Json => {
+            let results = ValidationResults {
+                valid: errors == 0,
+                diagnostics,
+            };
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+        Format::Text => {
+            // When no details are available, just return the error as is
+            if let [diagnostic] = diagnostics
+                && diagnostic.path.is_empty()
+                && diagnostic.line.is_none()
+            {
+                return Err(format_err!("{}", diagnostic.message)
+                    .context(format!("the landscape {subject} not valid")));
+            }
+
+            if errors == 0 {
+                if warnings > 0 {
+                    eprintln!("{warnings} warnings found:");
+                    print_text_messages(kind, diagnostics);
+                }
+                println!("The landscape {subject} valid!");
+            } else {
+                let found = if warnings > 0 {
+                    format!("{errors} errors and {warnings} warnings")
+                } else {
+                    format!("{errors} errors")
+                };
+                eprintln!("The landscape {subject} not valid ({found} found):");
+                print_text_messages(kind, diagnostics);
+            }
+        }
+    }
+
+    if errors > 0 {
+        return Err(format_err!("the landscape {subject} not valid"));
+    }
     Ok(())
 }
+
+/// Print the diagnostics provided as text messages to stderr.
+fn print_text_messages(kind: &str, diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        if kind == "all" {
+            eprintln!("  - {}: {}", diagnostic.file, text_message(diagnostic));
+        } else {
+            eprintln!("  - {}", text_message(diagnostic));
+        }
+    }
+}
+
+/// Get the diagnostics from the result of loading a landscape file. When the
+/// error contains a validation report, a diagnostic is returned for each of
+/// the entries in it.
+fn get_diagnostics<T>(file: &str, result: &Result<T>) -> Vec<Diagnostic> {
+    let Err(err) = result else {
+        return vec![];
+    };
+    let Some(report) = err.chain().find_map(|err| err.downcast_ref::<ValidationReport>()) else {
+        return vec![Diagnostic {
+            severity: Severity::Error,
+            file: file.to_string(),
+            path: vec![],
+            message: format!("{err:#}"),
+            line: None,
+            column: None,
+        }];
+    };
+    get_report_diagnostics(file, report)
+}
+
+/// Get the diagnostics for the entries in the validation report provided.
+fn get_report_diagnostics(file: &str, report: &ValidationReport) -> Vec<Diagnostic> {
+    report
+        .errors
+        .iter()
+        .map(|error| Diagnostic {
+            severity: error.severity,
+            file: file.to_string(),
+            path: error.path.clone(),
+            message: error.message.clone(),
+            line: error.line,
+            column: error.column,
+        })
+        .collect()
+}
+
+/// Format the diagnostic provided as a GitHub Actions workflow command, which
+/// will be displayed as an annotation.
+fn github_annotation(diagnostic: &Diagnostic) -> String {
+    let mut properties = vec![format!("file={}", escape_property(&diagnostic.file))];
+    if let Some(line) = diagnostic.line {
+        properties.push(format!("line={line}"));
+    }
+    if let Some(column) = diagnostic.column {
+        properties.push(format!("col={column}"));
+    }
+    if !diagnostic.path.is_empty() {
+        properties.push(format!("title={}", escape_property(&diagnostic.path.join(" > "))));
+    }
+
+    format!(
+        "::{} {}::{}",
+        diagnostic.severity,
+        properties.join(","),
+        escape_data(&diagnostic.message)
+    )
+}
+
+/// Format the diagnostic provided as a text message.
+fn text_message(diagnostic: &Diagnostic) -> String {
+    let severity = match diagnostic.severity {
+        Severity::Error => "",
+        Severity::Warning => "warning: ",
+    };
+    let path = if diagnostic.path.is_empty() {
+        String::new()
+    } else {
+        format!("[{}] ", diagnostic.path.join(" > "))
+    };
+    let location = match (diagnostic.line, diagnostic.column) {
+        (Some(line), Some(column)) => format!(" (line {line}, column {column})"),
+        _ => String::new(),
+    };
+    format!("{severity}{path}{}{location}", diagnostic.message)
+}
+
+/// Escape the data of a GitHub workflow command.
+fn escape_data(value: &str) -> String {
+    value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escape a property of a GitHub workflow command.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Return the location of the landscape file source provided.
+fn source_location(file: Option<&PathBuf>, url: Option<&String>) -> String {
+    match (file, url) {
+        (Some(file), _) => file.display().to_string(),
+        (None, Some(url)) => url.clone(),
+        (None, None) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn github_annotation_escapes_values() {
+        let diagnostic = Diagnostic {
+            severity: Severity::Error,
+            file: "data.yml".to_string(),
+            path: vec!["Category".to_string(), "Item, Inc.".to_string()],
+            message: "invalid url: 100%\nbad".to_string(),
+            line: Some(10),
+            column: Some(5),
+        };
+
+        assert_eq!(
+            github_annotation(&diagnostic),
+            "::error file=data.yml,line=10,col=5,title=Category > Item%2C Inc.::invalid url: 100%25%0Abad"
+        );
+    }
+}
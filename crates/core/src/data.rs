@@ -6,25 +6,45 @@
 //! legacy format used by the existing landscapes data files. To maintain
 //! backwards compatibility, this module provides a `legacy` submodule that
 //! allows parsing the legacy format and convert it to the new one.
+//!
+//! Landscapes data files can also use the native format, defined in the
+//! `native` submodule, which maps directly onto the representation used in
+//! this module. Native data files are identified by the `version` field at
+//! the top level.
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
+    sync::LazyLock,
 };
 
 use anyhow::{Context, Result, bail};
 use chrono::{DateTime, NaiveDate, Utc};
 use clap::Args;
+use regex::Regex;
 use reqwest::StatusCode;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, instrument, warn};
 
-use crate::util::normalize_name;
+use crate::{
+    util::{normalize_name, validate_url},
+    validation::{self, ValidationReport},
+};
 
 use super::settings::{self, LandscapeSettings};
 
 mod legacy;
+mod native;
+
+/// Item id regular expression.
+static ITEM_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z0-9][a-z0-9\-]*$").expect("exprs in ITEM_ID to be valid"));
+
+/// TAG name regular expression.
+static TAG_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z\-]+$").expect("exprs in TAG_NAME to be valid"));
 
 /// Format used for dates across the landscape data file.
 #[allow(dead_code)]
@@ -42,6 +62,10 @@ pub type CrunchbaseUrl = String;
 /// Type alias to represent some repositories' GitHub data.
 pub type GithubData = BTreeMap<RepositoryUrl, RepositoryGithubData>;
 
+/// Type alias to represent the aliases of some items ids (previous id ->
+/// current id).
+pub type ItemsAliases = BTreeMap<String, String>;
+
 /// Type alias to represent a GitHub repository url.
 pub type RepositoryUrl = String;
 
@@ -125,16 +149,39 @@ impl LandscapeData {
         Ok(landscape_data)
     }
 
-    /// Create a new landscape data instance from the raw legacy data provided.
+    /// Create a new landscape data instance from the raw data provided. The
+    /// data format (legacy or native) is detected automatically.
     fn new_from_raw_data(raw_data: &str) -> Result<Self> {
+        let value: serde_yaml::Value = serde_yaml::from_str(raw_data).context("invalid yaml file")?;
+
+        // Native format (versioned)
+        if value.get("version").is_some() {
+            let native_data: native::LandscapeData =
+                serde_yaml::from_value(value).context("invalid landscape data file (native format)")?;
+            native_data.validate().map_err(|err| validation::set_locations(err, raw_data))?;
+            return Ok(LandscapeData::from(native_data));
+        }
+
+        // Legacy format
         let legacy_data: legacy::LandscapeData =
-            serde_yaml::from_str(raw_data).context("invalid yaml file")?;
-        legacy_data.validate()?;
+            serde_yaml::from_value(value).context("invalid yaml file")?;
+        legacy_data.validate().map_err(|err| validation::set_locations(err, raw_data))?;
         let landscape_data = LandscapeData::from(legacy_data);
 
         Ok(landscape_data)
     }
 
+    /// Return the landscape data in native format (YAML).
+    ///
+    /// # Errors
+    /// Returns an error if the data cannot be serialized.
+    pub fn to_native_yaml(&self) -> Result<String> {
+        let native_data = native::LandscapeData::from(self);
+        let raw_data = serde_yaml::to_string(&native_data)?;
+
+        Ok(raw_data)
+    }
+
     /// Add items Crunchbase data.
     #[instrument(skip_all)]
     pub fn add_crunchbase_data(&mut self, crunchbase_data: &CrunchbaseData) {
@@ -351,6 +398,7 @@ impl From<legacy::LandscapeData> for LandscapeData {
                         enduser: legacy_item.enduser,
                         joined_at: legacy_item.joined,
                         homepage_url: legacy_item.homepage_url,
+                        id: legacy_item.id.unwrap_or_default(),
                         logo: legacy_item.logo,
                         maturity: legacy_item.project,
                         openssf_best_practices_url: legacy_item.url_for_bestpractices,
@@ -613,6 +661,11 @@ pub struct Item {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub graduated_at: Option<NaiveDate>,
 
+    /// Whether the item's id was set explicitly in the data file (even if it
+    /// matches the one generated from its location).
+    #[serde(skip)]
+    pub id_explicit: bool,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub incubating_at: Option<NaiveDate>,
 
@@ -625,6 +678,9 @@ pub struct Item {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub linkedin_url: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_derivatives: Option<Vec<ItemLogoDerivative>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mailing_list_url: Option<String>,
 
@@ -725,14 +781,18 @@ impl Item {
             .and_then(|repos| repos.iter().find(|r| r.primary.unwrap_or_default()))
     }
 
-    /// Generate and set the item's id.
+    /// Get the item's id when it has been set explicitly in the data file.
+    #[must_use]
+    pub fn explicit_id(&self) -> Option<&String> {
+        self.id_explicit.then_some(&self.id)
+    }
+
+    /// Generate and set the item's id, unless an explicit one was provided.
     fn set_id(&mut self) {
-        self.id = format!(
-            "{}--{}--{}",
-            normalize_name(&self.category),
-            normalize_name(&self.subcategory),
-            normalize_name(&self.name)
-        );
+        self.id_explicit = !self.id.is_empty();
+        if !self.id_explicit {
+            self.id = generate_item_id(&self.category, &self.subcategory, &self.name);
+        }
     }
 
     /// Set item's website.
@@ -771,7 +831,7 @@ pub struct Acquisition {
 }
 
 /// Additional category/subcategory an item can belong to.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AdditionalCategory {
     pub category: CategoryName,
     pub subcategory: SubcategoryName,
@@ -805,7 +865,7 @@ pub struct FundingRound {
 }
 
 /// Landscape item audit information.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ItemAudit {
     pub date: NaiveDate,
     #[serde(rename = "type")]
@@ -825,14 +885,22 @@ pub struct ItemFeatured {
 }
 
 /// Landscape item link.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ItemLink {
     pub name: String,
     pub url: String,
 }
 
-/// Landscape item summary.
+/// Landscape item logo raster derivative.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemLogoDerivative {
+    pub format: String,
+    pub path: String,
+    pub size: u32,
+}
+
+/// Landscape item summary.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ItemSummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub business_use_case: Option<String>,
@@ -970,6 +1038,134 @@ pub struct RepositoryGithubData {
     pub license: Option<String>,
 }
 
+/// Item fields checked by the validation shared by all the data file formats.
+#[derive(Debug)]
+struct ItemFields<'a> {
+    name: &'a str,
+    id: Option<&'a String>,
+    homepage_url: &'a String,
+    logo: &'a str,
+    other_links: &'a [ItemLink],
+    tags: &'a [String],
+    /// Urls to validate, along with their kind (i.e. blog).
+    urls: Vec<(&'static str, Option<&'a String>)>,
+}
+
+/// Validator that runs the item checks shared by all the data file formats.
+#[derive(Debug, Default)]
+struct ItemsValidator {
+    /// Used to check for duplicate items ids across the landscape.
+    ids_seen: HashSet<String>,
+    /// Used to check for duplicate items names within a subcategory.
+    names_seen: HashSet<(String, String, String)>,
+}
+
+impl ItemsValidator {
+    /// Validate the item provided, adding the errors found to the report. The
+    /// path used to report the item's errors is returned, so that it can be
+    /// used by the format specific checks as well.
+    fn validate(
+        &mut self,
+        report: &mut ValidationReport,
+        category: &str,
+        subcategory: &str,
+        item_index: usize,
+        item: &ItemFields,
+    ) -> [String; 3] {
+        // Prepare path for errors
+        let item_name = if item.name.trim().is_empty() {
+            format!("{item_index}")
+        } else {
+            item.name.to_string()
+        };
+        let path = [category.to_string(), subcategory.to_string(), item_name];
+
+        // Check name
+        let name_key = (
+            category.to_string(),
+            subcategory.to_string(),
+            item.name.to_string(),
+        );
+        let duplicate_name = !self.names_seen.insert(name_key);
+        if item.name.trim().is_empty() {
+            report.add_error(&path, "item name is required");
+        } else if duplicate_name {
+            report.add_error(&path, "duplicate item name");
+        }
+
+        // Check id (explicit or generated from the item's location)
+        let id = match item.id {
+            Some(id) => {
+                if !ITEM_ID.is_match(id) {
+                    report.add_error(
+                        &path,
+                        format!("invalid id {id} (must use only lowercase letters, numbers and hyphens)"),
+                    );
+                }
+                id.clone()
+            }
+            None => generate_item_id(category, subcategory, item.name),
+        };
+        if !self.ids_seen.insert(id.clone()) && !duplicate_name {
+            report.add_error(&path, format!("duplicate item id {id}"));
+        }
+
+        // Check homepage
+        if item.homepage_url.trim().is_empty() {
+            report.add_error(&path, "homepage url is required");
+        }
+
+        // Check logo
+        if item.logo.trim().is_empty() {
+            report.add_error(&path, "logo is required");
+        }
+
+        // Check other links
+        for link in item.other_links {
+            if link.name.trim().is_empty() {
+                report.add_error(&path, "link name is required");
+            }
+            if link.url.trim().is_empty() {
+                report.add_error(&path, "link url is required");
+            }
+        }
+
+        // Check TAGs names
+        for tag in item.tags {
+            if !TAG_NAME.is_match(tag) {
+                report.add_error(
+                    &path,
+                    format!("invalid tag {tag} (must use only lowercase letters and hyphens)"),
+                );
+            }
+        }
+
+        // Check urls
+        // (an empty homepage url is already reported as a missing one)
+        if !item.homepage_url.trim().is_empty() {
+            report.check(&path, validate_url("homepage", Some(item.homepage_url)));
+        }
+        for (kind, url) in &item.urls {
+            report.check(&path, validate_url(kind, *url));
+        }
+        for link in item.other_links {
+            report.check(&path, validate_url("other_link", Some(&link.url)));
+        }
+
+        path
+    }
+}
+
+/// Generate the id of an item from its category, subcategory and name.
+fn generate_item_id(category: &str, subcategory: &str, name: &str) -> String {
+    format!(
+        "{}--{}--{}",
+        normalize_name(category),
+        normalize_name(subcategory),
+        normalize_name(name)
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::settings::{self, EndUserRule, FeaturedItemRule, FeaturedItemRuleOption, TagRule};
@@ -1061,6 +1257,26 @@ mod tests {
         let _ = LandscapeData::new_from_raw_data(&raw_data).unwrap();
     }
 
+    #[test]
+    fn landscape_data_new_from_raw_data_native_format() {
+        let raw_data = fs::read_to_string(TESTS_DATA_FILE).unwrap();
+        let landscape_data = LandscapeData::new_from_raw_data(&raw_data).unwrap();
+
+        let raw_native_data = landscape_data.to_native_yaml().unwrap();
+        assert!(raw_native_data.starts_with("version: 1\n"));
+        assert_eq!(
+            LandscapeData::new_from_raw_data(&raw_native_data).unwrap(),
+            landscape_data
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unsupported landscape data format version")]
+    fn landscape_data_new_from_raw_data_native_format_unsupported_version() {
+        let raw_data = "version: 99\ncategories: []\n";
+        LandscapeData::new_from_raw_data(raw_data).unwrap();
+    }
+
     #[test]
     fn landscape_data_add_crunchbase_data() {
         let mut landscape_data = LandscapeData::default();
@@ -1447,6 +1663,7 @@ mod tests {
                             training_type: Some("training_type".to_string()),
                             youtube_url: Some("youtube_url".to_string()),
                         }),
+                        id: None,
                         joined: Some(date),
                         license: Some("license".to_string()),
                         project: Some("graduated".to_string()),
@@ -1509,10 +1726,12 @@ mod tests {
                 github_discussions_url: Some("github_discussions_url".to_string()),
                 gitter_url: Some("gitter_url".to_string()),
                 graduated_at: Some(date),
+                id_explicit: false,
                 incubating_at: Some(date),
                 joined_at: Some(date),
                 lfx_slug: Some("lfx_slug".to_string()),
                 linkedin_url: Some("linkedin_url".to_string()),
+                logo_derivatives: None,
                 mailing_list_url: Some("mailing_list_url".to_string()),
                 maturity: Some("graduated".to_string()),
                 member_subcategory: None,
@@ -1694,6 +1913,39 @@ mod tests {
 
         item.set_id();
         assert_eq!(item.id, "category--subcategory--item".to_string());
+        assert!(item.explicit_id().is_none());
+    }
+
+    #[test]
+    fn item_set_id_explicit_id_is_kept() {
+        let mut item = Item {
+            category: "Category".to_string(),
+            id: "item".to_string(),
+            subcategory: "Subcategory".to_string(),
+            name: "Item".to_string(),
+            ..Default::default()
+        };
+
+        item.set_id();
+        assert_eq!(item.id, "item".to_string());
+        assert_eq!(item.explicit_id(), Some(&"item".to_string()));
+    }
+
+    #[test]
+    fn item_set_id_explicit_id_matching_generated_one_is_kept() {
+        let mut item = Item {
+            category: "Category".to_string(),
+            id: "category--subcategory--item".to_string(),
+            subcategory: "Subcategory".to_string(),
+            name: "Item".to_string(),
+            ..Default::default()
+        };
+
+        item.set_id();
+        assert_eq!(
+            item.explicit_id(),
+            Some(&"category--subcategory--item".to_string())
+        );
     }
 
     #[test]
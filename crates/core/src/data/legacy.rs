@@ -1,20 +1,15 @@
 //! This module defines some types used to parse the landscape data file in
 //! legacy format and convert it to the new one.
 
-use std::{collections::HashMap, sync::LazyLock};
+use std::collections::HashMap;
 
-use anyhow::{Context, Result, bail, format_err};
+use anyhow::Result;
 use chrono::NaiveDate;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use crate::util::validate_url;
+use crate::validation::ValidationReport;
 
-use super::{ItemAudit, ItemLink};
-
-/// TAG name regular expression.
-static TAG_NAME: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^[a-z\-]+$").expect("exprs in TAG_NAME to be valid"));
+use super::{ItemAudit, ItemFields, ItemLink, ItemsValidator};
 
 /// Landscape data (legacy format).
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -24,91 +19,42 @@ pub(super) struct LandscapeData {
 }
 
 impl LandscapeData {
-    /// Validate landscape data.
+    /// Validate landscape data, collecting all the errors found.
     pub fn validate(&self) -> Result<()> {
+        let mut report = ValidationReport::new();
+        let mut items_validator = ItemsValidator::default();
+
         for (category_index, category) in self.landscape.iter().enumerate() {
             // Check category name
             if category.name.trim().is_empty() {
-                bail!("category [{category_index}] name is required");
+                report.add_error::<&str>(&[], format!("category [{category_index}] name is required"));
+                continue;
             }
 
             for (subcategory_index, subcategory) in category.subcategories.iter().enumerate() {
-                // Used to check for duplicate items within this subcategory
-                let mut items_seen = Vec::new();
-
                 // Check subcategory name
                 if subcategory.name.trim().is_empty() {
-                    bail!(
-                        "subcategory [{subcategory_index}] name is required (category: [{}]) ",
-                        category.name
+                    report.add_error(
+                        &[&category.name],
+                        format!("subcategory [{subcategory_index}] name is required"),
                     );
+                    continue;
                 }
 
+                // Check items
                 for (item_index, item) in subcategory.items.iter().enumerate() {
-                    // Prepare context for errors
-                    let item_id = if item.name.trim().is_empty() {
-                        format!("{item_index}")
-                    } else {
-                        item.name.clone()
-                    };
-                    let ctx = format!(
-                        "item [{}] is not valid (category: [{}] | subcategory: [{}])",
-                        item_id, category.name, subcategory.name
+                    items_validator.validate(
+                        &mut report,
+                        &category.name,
+                        &subcategory.name,
+                        item_index,
+                        &item.fields(),
                     );
-
-                    // Check name
-                    if item.name.trim().is_empty() {
-                        return Err(format_err!("item name is required")).context(ctx);
-                    }
-                    if items_seen.contains(&item.name) {
-                        return Err(format_err!("duplicate item name")).context(ctx);
-                    }
-                    items_seen.push(item.name.clone());
-
-                    // Check homepage
-                    if item.homepage_url.trim().is_empty() {
-                        return Err(format_err!("homepage url is required")).context(ctx);
-                    }
-
-                    // Check logo
-                    if item.logo.trim().is_empty() {
-                        return Err(format_err!("logo is required")).context(ctx);
-                    }
-
-                    // Check some values in extra
-                    if let Some(extra) = &item.extra {
-                        // Check other links
-                        if let Some(other_links) = &extra.other_links {
-                            for link in other_links {
-                                if link.name.trim().is_empty() {
-                                    return Err(format_err!("link name is required")).context(ctx);
-                                }
-                                if link.url.trim().is_empty() {
-                                    return Err(format_err!("link url is required")).context(ctx);
-                                }
-                            }
-                        }
-
-                        // Check TAGs names
-                        if let Some(tags) = &extra.tag {
-                            for tag in tags {
-                                if !TAG_NAME.is_match(tag) {
-                                    return Err(format_err!(
-                                        "invalid tag (must use only lowercase letters and hyphens)"
-                                    ))
-                                    .context(ctx);
-                                }
-                            }
-                        }
-                    }
-
-                    // Check urls
-                    validate_urls(item).context(ctx)?;
                 }
             }
         }
 
-        Ok(())
+        report.into_result()
     }
 }
 
@@ -138,6 +84,7 @@ pub(super) struct Item {
     pub description: Option<String>,
     pub enduser: Option<bool>,
     pub extra: Option<ItemExtra>,
+    pub id: Option<String>,
     pub joined: Option<NaiveDate>,
     pub license: Option<String>,
     pub project: Option<String>,
@@ -205,75 +152,57 @@ pub(super) struct Repository {
     pub license: Option<String>,
 }
 
-/// Validate the urls of the item provided.
-fn validate_urls(item: &Item) -> Result<()> {
-    // Check urls in item
-    let homepage_url = Some(item.homepage_url.clone());
-    let urls = [
-        ("best_practices", &item.url_for_bestpractices),
-        ("crunchbase", &item.crunchbase),
-        ("homepage", &homepage_url),
-        ("repository", &item.repo_url),
-        ("twitter", &item.twitter),
-    ];
-    for (name, url) in urls {
-        validate_url(name, url.as_ref())?;
-    }
-
-    // Check additional repositories
-    if let Some(additional_repos) = &item.additional_repos {
-        for r in additional_repos {
-            let repo_url = Some(r.repo_url.clone());
-            validate_url("additional_repository", repo_url.as_ref())?;
-        }
-    }
-
-    // Check urls in item extra
-    if let Some(extra) = &item.extra {
-        let urls = [
-            ("annual_review", &extra.annual_review_url),
-            ("artwork", &extra.artwork_url),
-            ("blog", &extra.blog_url),
-            ("bluesky", &extra.bluesky_url),
-            ("dev_stats", &extra.dev_stats_url),
-            ("discord", &extra.discord_url),
-            ("docker", &extra.docker_url),
-            ("documentation", &extra.documentation_url),
-            ("facebook", &extra.facebook_url),
-            ("funding", &extra.funding_url),
-            ("github_discussions", &extra.github_discussions_url),
-            ("gitter", &extra.gitter_url),
-            ("linkedin", &extra.linkedin_url),
-            ("mailing_list", &extra.mailing_list_url),
-            ("package_manager", &extra.package_manager_url),
-            ("pinterest", &extra.pinterest_url),
-            ("reddit", &extra.reddit_url),
-            ("slack", &extra.slack_url),
-            ("stack_overflow", &extra.stack_overflow_url),
-            ("youtube", &extra.youtube_url),
+impl Item {
+    /// Return the item fields checked by the shared validation.
+    fn fields(&self) -> ItemFields<'_> {
+        let extra = self.extra.as_ref();
+        let mut urls = vec![
+            ("best_practices", self.url_for_bestpractices.as_ref()),
+            ("crunchbase", self.crunchbase.as_ref()),
+            ("repository", self.repo_url.as_ref()),
+            ("twitter", self.twitter.as_ref()),
         ];
-        for (name, url) in urls {
-            validate_url(name, url.as_ref())?;
+        for repo in self.additional_repos.iter().flatten() {
+            urls.push(("additional_repository", Some(&repo.repo_url)));
         }
-
-        // Check audits urls
-        if let Some(audits) = &extra.audits {
-            for a in audits {
-                let audit_url = Some(a.url.clone());
-                validate_url("audit", audit_url.as_ref())?;
+        if let Some(extra) = extra {
+            urls.extend([
+                ("annual_review", extra.annual_review_url.as_ref()),
+                ("artwork", extra.artwork_url.as_ref()),
+                ("blog", extra.blog_url.as_ref()),
+                ("bluesky", extra.bluesky_url.as_ref()),
+                ("dev_stats", extra.dev_stats_url.as_ref()),
+                ("discord", extra.discord_url.as_ref()),
+                ("docker", extra.docker_url.as_ref()),
+                ("documentation", extra.documentation_url.as_ref()),
+                ("facebook", extra.facebook_url.as_ref()),
+                ("funding", extra.funding_url.as_ref()),
+                ("github_discussions", extra.github_discussions_url.as_ref()),
+                ("gitter", extra.gitter_url.as_ref()),
+                ("linkedin", extra.linkedin_url.as_ref()),
+                ("mailing_list", extra.mailing_list_url.as_ref()),
+                ("package_manager", extra.package_manager_url.as_ref()),
+                ("pinterest", extra.pinterest_url.as_ref()),
+                ("reddit", extra.reddit_url.as_ref()),
+                ("slack", extra.slack_url.as_ref()),
+                ("stack_overflow", extra.stack_overflow_url.as_ref()),
+                ("youtube", extra.youtube_url.as_ref()),
+            ]);
+            for audit in extra.audits.iter().flatten() {
+                urls.push(("audit", Some(&audit.url)));
             }
         }
 
-        // Other links
-        if let Some(other_links) = &extra.other_links {
-            for link in other_links {
-                let link_url = Some(link.url.clone());
-                validate_url("other_link", link_url.as_ref())?;
-            }
+        ItemFields {
+            name: &self.name,
+            id: self.id.as_ref(),
+            homepage_url: &self.homepage_url,
+            logo: &self.logo,
+            other_links: extra.and_then(|extra| extra.other_links.as_deref()).unwrap_or_default(),
+            tags: extra.and_then(|extra| extra.tag.as_deref()).unwrap_or_default(),
+            urls,
         }
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -481,6 +410,34 @@ mod tests {
         landscape.validate().unwrap();
     }
 
+    #[test]
+    #[should_panic(expected = "duplicate item id")]
+    fn landscape_data_validate_duplicate_item_id() {
+        let mut landscape = LandscapeData::default();
+        let item = Item {
+            name: "Item".to_string(),
+            homepage_url: "https://example.com".to_string(),
+            logo: "logo".to_string(),
+            id: Some("item".to_string()),
+            ..Default::default()
+        };
+        landscape.landscape.push(Category {
+            name: "Category".to_string(),
+            subcategories: vec![SubCategory {
+                name: "Subcategory".to_string(),
+                items: vec![
+                    item.clone(),
+                    Item {
+                        name: "Item 2".to_string(),
+                        ..item
+                    },
+                ],
+            }],
+        });
+
+        landscape.validate().unwrap();
+    }
+
     #[test]
     #[should_panic(expected = "invalid tag")]
     fn landscape_data_validate_invalid_tag() {
@@ -524,4 +481,35 @@ mod tests {
 
         landscape.validate().unwrap();
     }
+
+    #[test]
+    fn landscape_data_validate_collects_all_errors() {
+        let mut landscape = LandscapeData::default();
+        landscape.landscape.push(Category {
+            name: "Category".to_string(),
+            subcategories: vec![SubCategory {
+                name: "Subcategory".to_string(),
+                items: vec![
+                    Item {
+                        name: "Item 1".to_string(),
+                        logo: "logo".to_string(),
+                        ..Default::default()
+                    },
+                    Item {
+                        name: "Item 2".to_string(),
+                        homepage_url: "https://example.com".to_string(),
+                        ..Default::default()
+                    },
+                ],
+            }],
+        });
+
+        let err = landscape.validate().unwrap_err();
+        let report = err.downcast::<ValidationReport>().unwrap();
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(report.errors[0].path, vec!["Category", "Subcategory", "Item 1"]);
+        assert_eq!(report.errors[0].message, "homepage url is required");
+        assert_eq!(report.errors[1].path, vec!["Category", "Subcategory", "Item 2"]);
+        assert_eq!(report.errors[1].message, "logo is required");
+    }
 }
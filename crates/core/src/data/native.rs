@@ -0,0 +1,746 @@
+//! This module defines some types used to parse the landscape data file in
+//! native format, as well as to convert landscape data to it.
+//!
+//! The native format maps directly onto the landscape data representation used
+//! across the landscape2 crates, and it's versioned so that it can evolve
+//! without breaking existing data files.
+//!
+//! Unknown fields are rejected so that typos in the data file are reported
+//! instead of silently ignored. This doesn't apply to the nested types shared
+//! with the landscape datasets (audits, links, summary, etc), which are used
+//! to parse files generated by other versions of the tool as well.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{util::normalize_name, validation::ValidationReport};
+
+use super::{AdditionalCategory, ItemAudit, ItemFields, ItemLink, ItemSummary, ItemsValidator};
+
+/// Current version of the native data format.
+pub(super) const VERSION: u32 = 1;
+
+/// Landscape data (native format).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct LandscapeData {
+    pub version: u32,
+    pub categories: Vec<Category>,
+}
+
+impl LandscapeData {
+    /// Validate landscape data, collecting all the errors found.
+    pub fn validate(&self) -> Result<()> {
+        // Check version
+        if self.version != VERSION {
+            bail!(
+                "unsupported landscape data format version: {} (expected: {VERSION})",
+                self.version
+            );
+        }
+
+        let mut report = ValidationReport::new();
+        let mut items_validator = ItemsValidator::default();
+
+        for (category_index, category) in self.categories.iter().enumerate() {
+            // Check category name
+            if category.name.trim().is_empty() {
+                report.add_error::<&str>(&[], format!("category [{category_index}] name is required"));
+                continue;
+            }
+
+            for (subcategory_index, subcategory) in category.subcategories.iter().enumerate() {
+                // Check subcategory name
+                if subcategory.name.trim().is_empty() {
+                    report.add_error(
+                        &[&category.name],
+                        format!("subcategory [{subcategory_index}] name is required"),
+                    );
+                    continue;
+                }
+
+                for (item_index, item) in subcategory.items.iter().enumerate() {
+                    // Checks shared by all data file formats
+                    let path = items_validator.validate(
+                        &mut report,
+                        &category.name,
+                        &subcategory.name,
+                        item_index,
+                        &item.fields(),
+                    );
+
+                    // Check additional categories
+                    if let Some(additional_categories) = &item.additional_categories {
+                        for additional_category in additional_categories {
+                            if additional_category.category.trim().is_empty()
+                                || additional_category.subcategory.trim().is_empty()
+                            {
+                                report.add_error(
+                                    &path,
+                                    "additional category and subcategory names are required",
+                                );
+                            }
+                        }
+                    }
+
+                    // Check repositories
+                    if let Some(repositories) = &item.repositories
+                        && repositories.iter().filter(|r| r.primary.unwrap_or_default()).count() > 1
+                    {
+                        report.add_error(&path, "only one primary repository is allowed");
+                    }
+                }
+            }
+        }
+
+        report.into_result()
+    }
+}
+
+impl From<&super::LandscapeData> for LandscapeData {
+    fn from(landscape_data: &super::LandscapeData) -> Self {
+        let mut data = LandscapeData {
+            version: VERSION,
+            categories: vec![],
+        };
+
+        // Categories
+        for category in &landscape_data.categories {
+            let mut native_category = Category {
+                name: category.name.clone(),
+                subcategories: vec![],
+            };
+
+            // Subcategories
+            for subcategory in &category.subcategories {
+                let items = landscape_data
+                    .items
+                    .iter()
+                    .filter(|i| i.category == category.name && i.subcategory == subcategory.name)
+                    .map(Item::from)
+                    .collect();
+                native_category.subcategories.push(Subcategory {
+                    name: subcategory.name.clone(),
+                    items,
+                });
+            }
+
+            data.categories.push(native_category);
+        }
+
+        data
+    }
+}
+
+impl From<LandscapeData> for super::LandscapeData {
+    fn from(native_data: LandscapeData) -> Self {
+        let mut data = super::LandscapeData::default();
+
+        // Categories
+        for native_category in native_data.categories {
+            let mut category = super::Category {
+                name: native_category.name.clone(),
+                normalized_name: normalize_name(&native_category.name),
+                subcategories: vec![],
+            };
+
+            // Subcategories
+            for native_subcategory in native_category.subcategories {
+                category.subcategories.push(super::Subcategory {
+                    name: native_subcategory.name.clone(),
+                    normalized_name: normalize_name(&native_subcategory.name),
+                });
+
+                // Items
+                for native_item in native_subcategory.items {
+                    let mut item = native_item.into_item(&native_category.name, &native_subcategory.name);
+                    item.set_id();
+                    item.set_website();
+                    data.items.push(item);
+                }
+            }
+
+            data.categories.push(category);
+        }
+
+        data
+    }
+}
+
+/// Landscape category.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Category {
+    pub name: String,
+    pub subcategories: Vec<Subcategory>,
+}
+
+/// Landscape subcategory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Subcategory {
+    pub name: String,
+    pub items: Vec<Item>,
+}
+
+/// Landscape item (project, product, member, etc).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Item {
+    pub name: String,
+    pub homepage_url: String,
+    pub logo: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted_at: Option<NaiveDate>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_categories: Option<Vec<AdditionalCategory>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<BTreeMap<String, String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<NaiveDate>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artwork_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audits: Option<Vec<ItemAudit>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blog_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bluesky_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_channel: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clomonitor_name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crunchbase_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devstats_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discord_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docker_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enduser: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facebook_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub funding_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_discussions_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gitter_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graduated_at: Option<NaiveDate>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incubating_at: Option<NaiveDate>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub joined_at: Option<NaiveDate>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_annual_review_at: Option<NaiveDate>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_annual_review_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lfx_slug: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linkedin_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mailing_list_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maturity: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub openssf_best_practices_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other_links: Option<Vec<ItemLink>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package_manager_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_project: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinterest_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reddit_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repositories: Option<Vec<Repository>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slack_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub specification: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack_overflow_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ItemSummary>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub training_certifications: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub training_type: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitter_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unnamed_organization: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub youtube_url: Option<String>,
+}
+
+impl Item {
+    /// Return the item fields checked by the shared validation.
+    fn fields(&self) -> ItemFields<'_> {
+        let mut urls = vec![
+            ("annual_review", self.latest_annual_review_url.as_ref()),
+            ("artwork", self.artwork_url.as_ref()),
+            ("best_practices", self.openssf_best_practices_url.as_ref()),
+            ("blog", self.blog_url.as_ref()),
+            ("bluesky", self.bluesky_url.as_ref()),
+            ("crunchbase", self.crunchbase_url.as_ref()),
+            ("dev_stats", self.devstats_url.as_ref()),
+            ("discord", self.discord_url.as_ref()),
+            ("docker", self.docker_url.as_ref()),
+            ("documentation", self.documentation_url.as_ref()),
+            ("facebook", self.facebook_url.as_ref()),
+            ("funding", self.funding_url.as_ref()),
+            ("github_discussions", self.github_discussions_url.as_ref()),
+            ("gitter", self.gitter_url.as_ref()),
+            ("linkedin", self.linkedin_url.as_ref()),
+            ("mailing_list", self.mailing_list_url.as_ref()),
+            ("package_manager", self.package_manager_url.as_ref()),
+            ("pinterest", self.pinterest_url.as_ref()),
+            ("reddit", self.reddit_url.as_ref()),
+            ("slack", self.slack_url.as_ref()),
+            ("stack_overflow", self.stack_overflow_url.as_ref()),
+            ("twitter", self.twitter_url.as_ref()),
+            ("youtube", self.youtube_url.as_ref()),
+        ];
+        for repo in self.repositories.iter().flatten() {
+            urls.push(("repository", Some(&repo.url)));
+        }
+        for audit in self.audits.iter().flatten() {
+            urls.push(("audit", Some(&audit.url)));
+        }
+
+        ItemFields {
+            name: &self.name,
+            id: self.id.as_ref(),
+            homepage_url: &self.homepage_url,
+            logo: &self.logo,
+            other_links: self.other_links.as_deref().unwrap_or_default(),
+            tags: self.tag.as_deref().unwrap_or_default(),
+            urls,
+        }
+    }
+
+    /// Convert the native item into a landscape item, setting the category and
+    /// subcategory provided.
+    fn into_item(self, category: &str, subcategory: &str) -> super::Item {
+        super::Item {
+            category: category.to_string(),
+            homepage_url: self.homepage_url,
+            logo: self.logo,
+            name: self.name,
+            subcategory: subcategory.to_string(),
+            accepted_at: self.accepted_at,
+            additional_categories: self.additional_categories,
+            annotations: self.annotations.map(|a| a.into_iter().collect()),
+            archived_at: self.archived_at,
+            artwork_url: self.artwork_url,
+            audits: self.audits,
+            blog_url: self.blog_url,
+            bluesky_url: self.bluesky_url,
+            chat_channel: self.chat_channel,
+            clomonitor_name: self.clomonitor_name,
+            crunchbase_url: self.crunchbase_url,
+            description: self.description,
+            devstats_url: self.devstats_url,
+            discord_url: self.discord_url,
+            docker_url: self.docker_url,
+            documentation_url: self.documentation_url,
+            enduser: self.enduser,
+            facebook_url: self.facebook_url,
+            funding_url: self.funding_url,
+            github_discussions_url: self.github_discussions_url,
+            gitter_url: self.gitter_url,
+            graduated_at: self.graduated_at,
+            id: self.id.unwrap_or_default(),
+            incubating_at: self.incubating_at,
+            joined_at: self.joined_at,
+            latest_annual_review_at: self.latest_annual_review_at,
+            latest_annual_review_url: self.latest_annual_review_url,
+            lfx_slug: self.lfx_slug,
+            linkedin_url: self.linkedin_url,
+            mailing_list_url: self.mailing_list_url,
+            maturity: self.maturity,
+            openssf_best_practices_url: self.openssf_best_practices_url,
+            other_links: self.other_links,
+            package_manager_url: self.package_manager_url,
+            parent_project: self.parent_project,
+            pinterest_url: self.pinterest_url,
+            reddit_url: self.reddit_url,
+            repositories: self
+                .repositories
+                .map(|repos| repos.into_iter().map(super::Repository::from).collect()),
+            slack_url: self.slack_url,
+            specification: self.specification,
+            stack_overflow_url: self.stack_overflow_url,
+            summary: self.summary,
+            tag: self.tag,
+            training_certifications: self.training_certifications,
+            training_type: self.training_type,
+            twitter_url: self.twitter_url,
+            unnamed_organization: self.unnamed_organization,
+            youtube_url: self.youtube_url,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&super::Item> for Item {
+    fn from(item: &super::Item) -> Self {
+        Self {
+            name: item.name.clone(),
+            homepage_url: item.homepage_url.clone(),
+            logo: item.logo.clone(),
+            accepted_at: item.accepted_at,
+            additional_categories: item.additional_categories.clone(),
+            annotations: item.annotations.as_ref().map(|a| a.clone().into_iter().collect()),
+            archived_at: item.archived_at,
+            artwork_url: item.artwork_url.clone(),
+            audits: item.audits.clone(),
+            blog_url: item.blog_url.clone(),
+            bluesky_url: item.bluesky_url.clone(),
+            chat_channel: item.chat_channel.clone(),
+            clomonitor_name: item.clomonitor_name.clone(),
+            crunchbase_url: item.crunchbase_url.clone(),
+            description: item.description.clone(),
+            devstats_url: item.devstats_url.clone(),
+            discord_url: item.discord_url.clone(),
+            docker_url: item.docker_url.clone(),
+            documentation_url: item.documentation_url.clone(),
+            enduser: item.enduser,
+            facebook_url: item.facebook_url.clone(),
+            funding_url: item.funding_url.clone(),
+            github_discussions_url: item.github_discussions_url.clone(),
+            gitter_url: item.gitter_url.clone(),
+            graduated_at: item.graduated_at,
+            id: item.explicit_id().cloned(),
+            incubating_at: item.incubating_at,
+            joined_at: item.joined_at,
+            latest_annual_review_at: item.latest_annual_review_at,
+            latest_annual_review_url: item.latest_annual_review_url.clone(),
+            lfx_slug: item.lfx_slug.clone(),
+            linkedin_url: item.linkedin_url.clone(),
+            mailing_list_url: item.mailing_list_url.clone(),
+            maturity: item.maturity.clone(),
+            openssf_best_practices_url: item.openssf_best_practices_url.clone(),
+            other_links: item.other_links.clone(),
+            package_manager_url: item.package_manager_url.clone(),
+            parent_project: item.parent_project.clone(),
+            pinterest_url: item.pinterest_url.clone(),
+            reddit_url: item.reddit_url.clone(),
+            repositories: item
+                .repositories
+                .as_ref()
+                .map(|repos| repos.iter().map(Repository::from).collect()),
+            slack_url: item.slack_url.clone(),
+            specification: item.specification,
+            stack_overflow_url: item.stack_overflow_url.clone(),
+            summary: item.summary.clone(),
+            tag: item.tag.clone(),
+            training_certifications: item.training_certifications.clone(),
+            training_type: item.training_type.clone(),
+            twitter_url: item.twitter_url.clone(),
+            unnamed_organization: item.unnamed_organization,
+            youtube_url: item.youtube_url.clone(),
+        }
+    }
+}
+
+/// Landscape item repository.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Repository {
+    pub url: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary: Option<bool>,
+}
+
+impl From<Repository> for super::Repository {
+    fn from(repo: Repository) -> Self {
+        Self {
+            url: repo.url,
+            branch: repo.branch,
+            github_data: None,
+            license: repo.license,
+            primary: repo.primary,
+        }
+    }
+}
+
+impl From<&super::Repository> for Repository {
+    fn from(repo: &super::Repository) -> Self {
+        Self {
+            url: repo.url.clone(),
+            branch: repo.branch.clone(),
+            license: repo.license.clone(),
+            primary: repo.primary,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::legacy;
+    use super::*;
+
+    const TESTS_DATA_FILE: &str = "src/testdata/data.yml";
+
+    fn valid_item() -> Item {
+        Item {
+            name: "Item".to_string(),
+            homepage_url: "https://example.com".to_string(),
+            logo: "logo".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn landscape_with_item(item: Item) -> LandscapeData {
+        LandscapeData {
+            version: VERSION,
+            categories: vec![Category {
+                name: "Category".to_string(),
+                subcategories: vec![Subcategory {
+                    name: "Subcategory".to_string(),
+                    items: vec![item],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn landscape_data_validate_succeeds() {
+        let landscape = landscape_with_item(Item {
+            repositories: Some(vec![Repository {
+                url: "https://repo.url".to_string(),
+                primary: Some(true),
+                ..Default::default()
+            }]),
+            other_links: Some(vec![ItemLink {
+                name: "email".to_string(),
+                url: "mailto:team@example.com".to_string(),
+            }]),
+            ..valid_item()
+        });
+
+        landscape.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "unsupported landscape data format version")]
+    fn landscape_data_validate_unsupported_version() {
+        let landscape = LandscapeData {
+            version: VERSION + 1,
+            categories: vec![],
+        };
+
+        landscape.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate item name")]
+    fn landscape_data_validate_duplicate_item_name() {
+        let mut landscape = landscape_with_item(valid_item());
+        landscape.categories[0].subcategories[0].items.push(valid_item());
+
+        landscape.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate item id")]
+    fn landscape_data_validate_duplicate_item_id() {
+        let mut landscape = landscape_with_item(valid_item());
+        landscape.categories[0].subcategories.push(Subcategory {
+            name: "Subcategory 2".to_string(),
+            items: vec![Item {
+                id: Some("category--subcategory--item".to_string()),
+                ..valid_item()
+            }],
+        });
+
+        landscape.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid id")]
+    fn landscape_data_validate_invalid_item_id() {
+        let landscape = landscape_with_item(Item {
+            id: Some("Invalid Id".to_string()),
+            ..valid_item()
+        });
+
+        landscape.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "only one primary repository is allowed")]
+    fn landscape_data_validate_multiple_primary_repositories() {
+        let repository = Repository {
+            url: "https://repo.url".to_string(),
+            primary: Some(true),
+            ..Default::default()
+        };
+        let landscape = landscape_with_item(Item {
+            repositories: Some(vec![repository.clone(), repository]),
+            ..valid_item()
+        });
+
+        landscape.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid tag")]
+    fn landscape_data_validate_invalid_tag() {
+        let landscape = landscape_with_item(Item {
+            tag: Some(vec!["Invalid Tag".to_string()]),
+            ..valid_item()
+        });
+
+        landscape.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid homepage url")]
+    fn landscape_data_validate_invalid_url() {
+        let landscape = landscape_with_item(Item {
+            homepage_url: "invalid".to_string(),
+            ..valid_item()
+        });
+
+        landscape.validate().unwrap();
+    }
+
+    #[test]
+    fn landscape_data_unknown_fields_are_rejected() {
+        let raw_data = r"
+version: 1
+categories:
+  - name: Category
+    subcategories:
+      - name: Subcategory
+        items:
+          - name: Item
+            homepage_url: https://example.com
+            logo: logo.svg
+            homepage: https://typo.example.com
+";
+        let err = serde_yaml::from_str::<LandscapeData>(raw_data).unwrap_err();
+        assert!(err.to_string().contains("unknown field `homepage`"));
+    }
+
+    #[test]
+    fn explicit_id_matching_generated_one_is_kept() {
+        let raw_data = r"
+version: 1
+categories:
+  - name: Category
+    subcategories:
+      - name: Subcategory
+        items:
+          - name: Item
+            id: category--subcategory--item
+            homepage_url: https://example.com
+            logo: logo.svg
+";
+        let native_data: LandscapeData = serde_yaml::from_str(raw_data).unwrap();
+        let landscape_data = super::super::LandscapeData::from(native_data);
+
+        // Convert it back to the native format and load it again
+        let native_data = LandscapeData::from(&landscape_data);
+        let raw_native_data = serde_yaml::to_string(&native_data).unwrap();
+        let native_data: LandscapeData = serde_yaml::from_str(&raw_native_data).unwrap();
+        let landscape_data = super::super::LandscapeData::from(native_data);
+
+        assert!(raw_native_data.contains("id: category--subcategory--item"));
+        assert_eq!(
+            landscape_data.items[0].explicit_id(),
+            Some(&"category--subcategory--item".to_string())
+        );
+    }
+
+    #[test]
+    fn legacy_data_migration_is_lossless() {
+        // Load legacy data
+        let raw_data = std::fs::read_to_string(TESTS_DATA_FILE).unwrap();
+        let legacy_data: legacy::LandscapeData = serde_yaml::from_str(&raw_data).unwrap();
+        let landscape_data = super::super::LandscapeData::from(legacy_data);
+
+        // Convert it to the native format and load it back
+        let native_data = LandscapeData::from(&landscape_data);
+        let raw_native_data = serde_yaml::to_string(&native_data).unwrap();
+        let native_data: LandscapeData = serde_yaml::from_str(&raw_native_data).unwrap();
+        native_data.validate().unwrap();
+
+        assert_eq!(super::super::LandscapeData::from(native_data), landscape_data);
+    }
+}
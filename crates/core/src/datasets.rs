@@ -7,7 +7,7 @@
 //! consumed by other applications, as they can change at any time.
 
 use crate::{
-    data::{CrunchbaseData, GithubData, LandscapeData},
+    data::{CrunchbaseData, GithubData, ItemsAliases, LandscapeData},
     games::LandscapeGames,
     guide::LandscapeGuide,
     settings::LandscapeSettings,
@@ -23,6 +23,7 @@ pub struct NewDatasetsInput<'a> {
     pub games: &'a Option<LandscapeGames>,
     pub github_data: &'a GithubData,
     pub guide: &'a Option<LandscapeGuide>,
+    pub items_aliases: &'a ItemsAliases,
     pub landscape_data: &'a LandscapeData,
     pub qr_code: &'a String,
     pub settings: &'a LandscapeSettings,
@@ -49,7 +50,14 @@ impl Datasets {
     #[must_use]
     pub fn new(i: &NewDatasetsInput) -> Self {
         Datasets {
-            base: Base::new(i.landscape_data, i.settings, i.guide, i.games, i.qr_code),
+            base: Base::new(
+                i.landscape_data,
+                i.settings,
+                i.guide,
+                i.games,
+                i.items_aliases,
+                i.qr_code,
+            ),
             embed: Embed::new(i.landscape_data, i.settings),
             full: Full::new(i.landscape_data, i.crunchbase_data, i.github_data),
             stats: Stats::new(i.landscape_data, i.settings),
@@ -63,7 +71,7 @@ impl Datasets {
 /// the initial page and power the features available on it.
 pub mod base {
     use crate::{
-        data::{self, AdditionalCategory, Category, CategoryName, ItemFeatured, LandscapeData},
+        data::{self, AdditionalCategory, Category, CategoryName, ItemFeatured, ItemsAliases, LandscapeData},
         games::LandscapeGames,
         guide::LandscapeGuide,
         settings::{
@@ -128,6 +136,9 @@ pub mod base {
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         pub items: Vec<Item>,
 
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        pub items_aliases: ItemsAliases,
+
         #[serde(skip_serializing_if = "Option::is_none")]
         pub members_category: Option<String>,
 
@@ -146,6 +157,7 @@ pub mod base {
             settings: &LandscapeSettings,
             guide: &Option<LandscapeGuide>,
             games: &Option<LandscapeGames>,
+            items_aliases: &ItemsAliases,
             qr_code: &str,
         ) -> Self {
             let mut base = Base {
@@ -168,6 +180,7 @@ pub mod base {
                 hide_organization_section_in_projects: settings.hide_organization_section_in_projects,
                 images: settings.images.clone(),
                 items: vec![],
+                items_aliases: items_aliases.clone(),
                 members_category: settings.members_category.clone(),
                 upcoming_event: settings.upcoming_event.clone(),
                 view_mode: settings.view_mode.clone(),
@@ -616,6 +629,7 @@ mod tests {
             games: &None,
             github_data: &GithubData::default(),
             guide: &None,
+            items_aliases: &ItemsAliases::default(),
             landscape_data: &LandscapeData::default(),
             qr_code: &String::default(),
             settings: &LandscapeSettings::default(),
@@ -734,10 +748,20 @@ mod tests {
             quiz: Some(Quiz { questions: vec![] }),
         };
 
+        // Items aliases
+        let items_aliases = ItemsAliases::from([("previous-id".to_string(), "id".to_string())]);
+
         // QR code
         let qr_code = "QR_CODE".to_string();
 
-        let base = Base::new(&landscape_data, &settings, &Some(guide), &Some(games), &qr_code);
+        let base = Base::new(
+            &landscape_data,
+            &settings,
+            &Some(guide),
+            &Some(games),
+            &items_aliases,
+            &qr_code,
+        );
         let expected_base = Base {
             finances_available: true,
             foundation: "Foundation".to_string(),
@@ -767,6 +791,7 @@ mod tests {
             hide_organization_section_in_projects: Some(true),
             images,
             items: vec![(&item).into()],
+            items_aliases,
             members_category: Some("Members".to_string()),
             upcoming_event: Some(upcoming_event),
             view_mode: Some(ViewMode::Grid),
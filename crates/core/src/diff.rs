@@ -0,0 +1,471 @@
+//! This module provides the functionality used to compare two versions of the
+//! landscape data, reporting the semantic differences between them (items
+//! added, removed, renamed, moved or whose fields have changed).
+
+use std::{collections::HashMap, hash::Hash};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    data::{Item, ItemsAliases, LandscapeData},
+    util::normalize_name,
+};
+
+/// Item fields that are not compared, as they are derived from other fields
+/// or reported in a different way (renames, moves).
+const IGNORED_FIELDS: [&str; 5] = ["category", "id", "name", "subcategory", "website"];
+
+/// Differences between two versions of the landscape data.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LandscapeDataDiff {
+    pub added: Vec<ItemKey>,
+    pub removed: Vec<ItemKey>,
+    pub renamed: Vec<ItemMove>,
+    pub moved: Vec<ItemMove>,
+    pub changed: Vec<ItemChanges>,
+}
+
+impl LandscapeDataDiff {
+    /// Compare the two versions of the landscape data provided.
+    ///
+    /// Items are matched by their id and then by their category, subcategory
+    /// and name. Items that cannot be matched this way are matched by name
+    /// (moved items), and then by their primary repository or homepage url
+    /// (renamed items), as long as the match is not ambiguous.
+    #[must_use]
+    pub fn new(old: &LandscapeData, new: &LandscapeData) -> Self {
+        let matcher = ItemsMatcher::match_all(&old.items, &new.items);
+
+        let mut diff = LandscapeDataDiff::default();
+        for (old_item, new_item) in matcher.pairs() {
+            let (from, to) = (ItemKey::from(old_item), ItemKey::from(new_item));
+            if from.name != to.name {
+                diff.renamed.push(ItemMove {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+            if from.category != to.category || from.subcategory != to.subcategory {
+                diff.moved.push(ItemMove {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+            let changes = compare_fields(old_item, new_item);
+            if !changes.is_empty() {
+                diff.changed.push(ItemChanges { item: to, changes });
+            }
+        }
+        diff.added = matcher.unmatched_new().map(ItemKey::from).collect();
+        diff.removed = matcher.unmatched_old().map(ItemKey::from).collect();
+        diff.added.sort();
+        diff.removed.sort();
+
+        diff
+    }
+
+    /// Check if there are no differences.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.moved.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+/// Return the aliases for the items whose id has changed between the two
+/// versions of the landscape data provided (previous id -> current id).
+///
+/// Items are matched in the same way as when computing the differences, so
+/// that renamed and moved items are detected.
+#[must_use]
+pub fn items_ids_changes(old: &LandscapeData, new: &LandscapeData) -> ItemsAliases {
+    ItemsMatcher::match_all(&old.items, &new.items)
+        .pairs()
+        .into_iter()
+        .filter(|(old_item, new_item)| old_item.id != new_item.id)
+        .map(|(old_item, new_item)| (old_item.id.clone(), new_item.id.clone()))
+        .collect()
+}
+
+/// Key that identifies an item in the landscape data.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ItemKey {
+    pub category: String,
+    pub subcategory: String,
+    pub name: String,
+}
+
+impl From<&Item> for ItemKey {
+    fn from(item: &Item) -> Self {
+        Self {
+            category: item.category.clone(),
+            subcategory: item.subcategory.clone(),
+            name: item.name.clone(),
+        }
+    }
+}
+
+/// Item renamed or moved to a different category/subcategory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemMove {
+    pub from: ItemKey,
+    pub to: ItemKey,
+}
+
+/// Changes in the fields of an item.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemChanges {
+    pub item: ItemKey,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Change in the value of an item's field. Values are not set when the field
+/// is not present in the corresponding version.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+}
+
+/// Helper used to match the items of two versions of the landscape data.
+struct ItemsMatcher<'a> {
+    old: &'a [Item],
+    new: &'a [Item],
+    old_matches: Vec<Option<usize>>,
+    new_matched: Vec<bool>,
+}
+
+impl<'a> ItemsMatcher<'a> {
+    /// Create a new items matcher.
+    fn new(old: &'a [Item], new: &'a [Item]) -> Self {
+        Self {
+            old,
+            new,
+            old_matches: vec![None; old.len()],
+            new_matched: vec![false; new.len()],
+        }
+    }
+
+    /// Create a new items matcher, matching the items using all the
+    /// strategies available.
+    fn match_all(old: &'a [Item], new: &'a [Item]) -> Self {
+        let mut matcher = Self::new(old, new);
+        matcher.match_by(|item| (!item.id.is_empty()).then(|| item.id.clone()));
+        matcher.match_by(|item| Some((item.category.clone(), item.subcategory.clone(), item.name.clone())));
+        matcher.match_by(|item| Some(normalize_name(&item.name)));
+        matcher
+            .match_by(|item| item.primary_repository().map(|r| r.url.trim_end_matches('/').to_lowercase()));
+        matcher.match_by(|item| Some(item.homepage_url.trim_end_matches('/').to_lowercase()));
+        matcher
+    }
+
+    /// Match the unmatched items using the key provided. Only keys that
+    /// identify a single item on each side are used.
+    fn match_by<K, F>(&mut self, key: F)
+    where
+        K: Eq + Hash,
+        F: Fn(&Item) -> Option<K>,
+    {
+        let mut candidates: HashMap<K, (Vec<usize>, Vec<usize>)> = HashMap::new();
+        for (i, item) in self.old.iter().enumerate() {
+            if self.old_matches[i].is_none()
+                && let Some(key) = key(item)
+            {
+                candidates.entry(key).or_default().0.push(i);
+            }
+        }
+        for (i, item) in self.new.iter().enumerate() {
+            if !self.new_matched[i]
+                && let Some(key) = key(item)
+            {
+                candidates.entry(key).or_default().1.push(i);
+            }
+        }
+        for (old_indexes, new_indexes) in candidates.into_values() {
+            if let ([old_index], [new_index]) = (old_indexes.as_slice(), new_indexes.as_slice()) {
+                self.old_matches[*old_index] = Some(*new_index);
+                self.new_matched[*new_index] = true;
+            }
+        }
+    }
+
+    /// Return the pairs of items matched (in the order of the new version).
+    fn pairs(&self) -> Vec<(&'a Item, &'a Item)> {
+        let mut pairs: Vec<(usize, usize)> = self
+            .old_matches
+            .iter()
+            .enumerate()
+            .filter_map(|(old_index, new_index)| new_index.map(|new_index| (old_index, new_index)))
+            .collect();
+        pairs.sort_by_key(|(_, new_index)| *new_index);
+        pairs
+            .into_iter()
+            .map(|(old_index, new_index)| (&self.old[old_index], &self.new[new_index]))
+            .collect()
+    }
+
+    /// Return the items of the new version that were not matched.
+    fn unmatched_new(&self) -> impl Iterator<Item = &'a Item> + '_ {
+        self.new.iter().enumerate().filter(|(i, _)| !self.new_matched[*i]).map(|(_, item)| item)
+    }
+
+    /// Return the items of the old version that were not matched.
+    fn unmatched_old(&self) -> impl Iterator<Item = &'a Item> + '_ {
+        self.old
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.old_matches[*i].is_none())
+            .map(|(_, item)| item)
+    }
+}
+
+/// Compare the fields of the two versions of the item provided.
+fn compare_fields(old: &Item, new: &Item) -> Vec<FieldChange> {
+    let (Ok(Value::Object(old)), Ok(Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return vec![];
+    };
+
+    let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter(|field| !IGNORED_FIELDS.contains(&field.as_str()))
+        .filter(|field| old.get(*field) != new.get(*field))
+        .map(|field| FieldChange {
+            field: field.clone(),
+            old: old.get(field).map(format_value),
+            new: new.get(field).map(format_value),
+        })
+        .collect()
+}
+
+/// Format the field value provided so that it can be displayed.
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::Repository;
+
+    use super::*;
+
+    fn item(category: &str, subcategory: &str, name: &str) -> Item {
+        Item {
+            category: category.to_string(),
+            subcategory: subcategory.to_string(),
+            name: name.to_string(),
+            homepage_url: format!("https://{}.example.com", name.to_lowercase()),
+            logo: format!("{}.svg", name.to_lowercase()),
+            ..Default::default()
+        }
+    }
+
+    fn key(category: &str, subcategory: &str, name: &str) -> ItemKey {
+        ItemKey {
+            category: category.to_string(),
+            subcategory: subcategory.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_no_changes() {
+        let data = LandscapeData {
+            items: vec![item("Category", "Subcategory", "Item")],
+            ..Default::default()
+        };
+
+        assert!(LandscapeDataDiff::new(&data, &data).is_empty());
+    }
+
+    #[test]
+    fn diff_added_and_removed() {
+        let old = LandscapeData {
+            items: vec![item("Category", "Subcategory", "Item1")],
+            ..Default::default()
+        };
+        let new = LandscapeData {
+            items: vec![item("Category", "Subcategory", "Item2")],
+            ..Default::default()
+        };
+
+        let diff = LandscapeDataDiff::new(&old, &new);
+        assert_eq!(diff.added, vec![key("Category", "Subcategory", "Item2")]);
+        assert_eq!(diff.removed, vec![key("Category", "Subcategory", "Item1")]);
+        assert!(diff.renamed.is_empty() && diff.moved.is_empty() && diff.changed.is_empty());
+    }
+
+    #[test]
+    fn diff_moved() {
+        let old = LandscapeData {
+            items: vec![item("Category", "Subcategory1", "Item")],
+            ..Default::default()
+        };
+        let new = LandscapeData {
+            items: vec![item("Category", "Subcategory2", "Item")],
+            ..Default::default()
+        };
+
+        let diff = LandscapeDataDiff::new(&old, &new);
+        assert_eq!(
+            diff.moved,
+            vec![ItemMove {
+                from: key("Category", "Subcategory1", "Item"),
+                to: key("Category", "Subcategory2", "Item"),
+            }]
+        );
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+    }
+
+    #[test]
+    fn diff_renamed() {
+        let mut old_item = item("Category", "Subcategory", "Item");
+        old_item.repositories = Some(vec![Repository {
+            url: "https://github.com/org/repo".to_string(),
+            primary: Some(true),
+            ..Default::default()
+        }]);
+        let mut new_item = old_item.clone();
+        new_item.name = "New Item".to_string();
+
+        let old = LandscapeData {
+            items: vec![old_item],
+            ..Default::default()
+        };
+        let new = LandscapeData {
+            items: vec![new_item],
+            ..Default::default()
+        };
+
+        let diff = LandscapeDataDiff::new(&old, &new);
+        assert_eq!(
+            diff.renamed,
+            vec![ItemMove {
+                from: key("Category", "Subcategory", "Item"),
+                to: key("Category", "Subcategory", "New Item"),
+            }]
+        );
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.moved.is_empty());
+    }
+
+    #[test]
+    fn diff_ambiguous_matches_are_not_used() {
+        let old = LandscapeData {
+            items: vec![
+                item("Category", "Subcategory1", "Item"),
+                item("Category", "Subcategory2", "Item"),
+            ],
+            ..Default::default()
+        };
+        let new = LandscapeData {
+            items: vec![item("Category", "Subcategory3", "Item")],
+            ..Default::default()
+        };
+
+        let diff = LandscapeDataDiff::new(&old, &new);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 2);
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn diff_changed_fields() {
+        let old_item = item("Category", "Subcategory", "Item");
+        let mut new_item = old_item.clone();
+        new_item.maturity = Some("incubating".to_string());
+        new_item.logo = "new-logo.svg".to_string();
+        new_item.website = "https://website.example.com".to_string();
+
+        let old = LandscapeData {
+            items: vec![old_item],
+            ..Default::default()
+        };
+        let new = LandscapeData {
+            items: vec![new_item],
+            ..Default::default()
+        };
+
+        let diff = LandscapeDataDiff::new(&old, &new);
+        assert_eq!(
+            diff.changed,
+            vec![ItemChanges {
+                item: key("Category", "Subcategory", "Item"),
+                changes: vec![
+                    FieldChange {
+                        field: "logo".to_string(),
+                        old: Some("item.svg".to_string()),
+                        new: Some("new-logo.svg".to_string()),
+                    },
+                    FieldChange {
+                        field: "maturity".to_string(),
+                        old: None,
+                        new: Some("incubating".to_string()),
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn diff_items_matched_by_id() {
+        let mut old_item = item("Category", "Subcategory1", "Item1");
+        old_item.id = "item".to_string();
+        let mut new_item = item("Category", "Subcategory2", "Item2");
+        new_item.id = "item".to_string();
+        let old = LandscapeData {
+            items: vec![old_item],
+            ..Default::default()
+        };
+        let new = LandscapeData {
+            items: vec![new_item],
+            ..Default::default()
+        };
+
+        let diff = LandscapeDataDiff::new(&old, &new);
+        assert_eq!(diff.renamed.len(), 1);
+        assert_eq!(diff.moved.len(), 1);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert!(items_ids_changes(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn items_ids_changes_moved_item() {
+        let mut old_item = item("Category", "Subcategory1", "Item");
+        old_item.id = "category--subcategory1--item".to_string();
+        let mut new_item = item("Category", "Subcategory2", "Item");
+        new_item.id = "category--subcategory2--item".to_string();
+        let old = LandscapeData {
+            items: vec![old_item],
+            ..Default::default()
+        };
+        let new = LandscapeData {
+            items: vec![new_item],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            items_ids_changes(&old, &new),
+            ItemsAliases::from([(
+                "category--subcategory1--item".to_string(),
+                "category--subcategory2--item".to_string()
+            )])
+        );
+    }
+}
@@ -7,8 +7,13 @@
 
 pub mod data;
 pub mod datasets;
+pub mod diff;
 pub mod games;
 pub mod guide;
+pub mod search;
 pub mod settings;
 pub mod stats;
 mod util;
+pub mod validation;
+
+pub use util::normalize_name;
@@ -0,0 +1,379 @@
+//! This module defines the types and functionality used to build and query
+//! the landscape full-text search index.
+//!
+//! The index is a compact inverted index over the items (name, description,
+//! summary and repositories topics) and the guide sections. It is generated
+//! at build time, and it's small and simple enough to be queried from the
+//! browser (i.e. using a wasm module).
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{data::LandscapeData, guide::LandscapeGuide, util::normalize_name};
+
+/// BM25 term frequency saturation parameter.
+const BM25_K1: f32 = 1.2;
+
+/// BM25 document length normalization parameter.
+const BM25_B: f32 = 0.75;
+
+/// Weight applied to the terms that match a query term by prefix.
+const PREFIX_MATCH_WEIGHT: f32 = 0.7;
+
+/// Minimum length of a term to be indexed.
+const MIN_TERM_LEN: usize = 2;
+
+/// Words not worth indexing.
+const STOP_WORDS: &[&str] = &[
+    "an", "and", "are", "as", "at", "be", "by", "com", "for", "from", "has", "have", "http", "https", "in",
+    "is", "it", "its", "of", "on", "or", "that", "the", "this", "to", "was", "which", "will", "with", "www",
+];
+
+/// Fields weights.
+const WEIGHT_ITEM_NAME: u32 = 8;
+const WEIGHT_ITEM_DESCRIPTION: u32 = 2;
+const WEIGHT_ITEM_SUMMARY: u32 = 1;
+const WEIGHT_ITEM_TOPICS: u32 = 2;
+const WEIGHT_GUIDE_TITLE: u32 = 4;
+const WEIGHT_GUIDE_KEYWORDS: u32 = 3;
+const WEIGHT_GUIDE_CONTENT: u32 = 1;
+
+/// Postings of a term (document index, weighted term frequency).
+pub type Postings = Vec<(u32, u32)>;
+
+/// Full-text search index.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchIndex {
+    /// Documents indexed.
+    pub docs: Vec<SearchDocument>,
+
+    /// Inverted index (term -> postings).
+    pub terms: BTreeMap<String, Postings>,
+}
+
+/// Document indexed (item or guide section).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchDocument {
+    pub kind: SearchDocumentKind,
+
+    /// Item id or guide section id.
+    pub id: String,
+
+    pub title: String,
+
+    /// Number of terms in the document.
+    pub len: u32,
+}
+
+/// Kind of document indexed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchDocumentKind {
+    #[default]
+    Item,
+    Guide,
+}
+
+/// Search result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub kind: SearchDocumentKind,
+    pub id: String,
+    pub title: String,
+    pub score: f32,
+}
+
+impl SearchIndex {
+    /// Create a new search index from the landscape data and guide provided.
+    #[must_use]
+    pub fn new(landscape_data: &LandscapeData, guide: Option<&LandscapeGuide>) -> Self {
+        let mut index = SearchIndex::default();
+
+        // Items
+        for item in &landscape_data.items {
+            let mut fields = vec![(item.name.as_str(), WEIGHT_ITEM_NAME)];
+            if let Some(description) = item.description() {
+                fields.push((description, WEIGHT_ITEM_DESCRIPTION));
+            }
+            if let Some(summary) = &item.summary {
+                let summary_fields = [
+                    &summary.business_use_case,
+                    &summary.integration,
+                    &summary.integrations,
+                    &summary.release_rate,
+                    &summary.use_case,
+                ];
+                for value in summary_fields.into_iter().flatten() {
+                    fields.push((value, WEIGHT_ITEM_SUMMARY));
+                }
+                for value in summary.personas.iter().chain(&summary.tags).flatten() {
+                    fields.push((value, WEIGHT_ITEM_SUMMARY));
+                }
+            }
+            for repo in item.repositories.iter().flatten() {
+                for topic in repo.github_data.iter().flat_map(|gh| &gh.topics) {
+                    fields.push((topic, WEIGHT_ITEM_TOPICS));
+                }
+            }
+            index.add_document(SearchDocumentKind::Item, &item.id, &item.name, &fields);
+        }
+
+        // Guide sections
+        for category in guide.and_then(|g| g.categories.as_ref()).into_iter().flatten() {
+            let mut fields = vec![(category.category.as_str(), WEIGHT_GUIDE_TITLE)];
+            for keyword in category.keywords.iter().flatten() {
+                fields.push((keyword, WEIGHT_GUIDE_KEYWORDS));
+            }
+            if let Some(content) = &category.content {
+                fields.push((content, WEIGHT_GUIDE_CONTENT));
+            }
+            let category_id = normalize_name(&category.category);
+            index.add_document(
+                SearchDocumentKind::Guide,
+                &category_id,
+                &category.category,
+                &fields,
+            );
+
+            for subcategory in category.subcategories.iter().flatten() {
+                let mut fields = vec![
+                    (subcategory.subcategory.as_str(), WEIGHT_GUIDE_TITLE),
+                    (subcategory.content.as_str(), WEIGHT_GUIDE_CONTENT),
+                ];
+                for keyword in subcategory.keywords.iter().flatten() {
+                    fields.push((keyword, WEIGHT_GUIDE_KEYWORDS));
+                }
+                let id = format!("{category_id}--{}", normalize_name(&subcategory.subcategory));
+                index.add_document(SearchDocumentKind::Guide, &id, &subcategory.subcategory, &fields);
+            }
+        }
+
+        index
+    }
+
+    /// Add a document to the index, indexing the fields provided (text and
+    /// weight).
+    fn add_document(&mut self, kind: SearchDocumentKind, id: &str, title: &str, fields: &[(&str, u32)]) {
+        let doc_index = u32::try_from(self.docs.len()).expect("number of documents to fit in u32");
+
+        let mut len = 0;
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        for (text, weight) in fields {
+            for term in tokenize(text) {
+                len += 1;
+                *frequencies.entry(term).or_default() += weight;
+            }
+        }
+        for (term, frequency) in frequencies {
+            self.terms.entry(term).or_default().push((doc_index, frequency));
+        }
+
+        self.docs.push(SearchDocument {
+            kind,
+            id: id.to_string(),
+            title: title.to_string(),
+            len,
+        });
+    }
+
+    /// Search the index for the query provided, returning up to `limit`
+    /// results sorted by relevance.
+    ///
+    /// Documents matching more query terms are ranked first, and then by
+    /// their BM25 score. The last query term also matches terms starting with
+    /// it, unless the query ends with a whitespace (search as you type).
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+        let query_terms: Vec<String> = tokenize(query).collect();
+        if query_terms.is_empty() || self.docs.is_empty() {
+            return vec![];
+        }
+        let docs_count = self.docs.len() as f32;
+        let avg_len = self.docs.iter().map(|d| d.len as f32).sum::<f32>() / docs_count;
+        let prefix_last_term = !query.ends_with(char::is_whitespace);
+
+        // Score documents for each of the query terms
+        let mut scores: HashMap<u32, (usize, f32)> = HashMap::new();
+        for (i, query_term) in query_terms.iter().enumerate() {
+            let mut term_scores: HashMap<u32, f32> = HashMap::new();
+            for (term, postings) in
+                self.matching_terms(query_term, prefix_last_term && i == query_terms.len() - 1)
+            {
+                let weight = if term == query_term {
+                    1.0
+                } else {
+                    PREFIX_MATCH_WEIGHT
+                };
+                let df = postings.len() as f32;
+                let idf = (1.0 + (docs_count - df + 0.5) / (df + 0.5)).ln();
+                for (doc_index, frequency) in postings {
+                    let tf = *frequency as f32;
+                    let len_norm =
+                        1.0 - BM25_B + BM25_B * self.docs[*doc_index as usize].len as f32 / avg_len;
+                    let score = weight * idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * len_norm);
+                    let term_score = term_scores.entry(*doc_index).or_default();
+                    *term_score = term_score.max(score);
+                }
+            }
+            for (doc_index, score) in term_scores {
+                let (matches, total) = scores.entry(doc_index).or_default();
+                *matches += 1;
+                *total += score;
+            }
+        }
+
+        // Rank results
+        let mut results: Vec<(u32, (usize, f32))> = scores.into_iter().collect();
+        results.sort_by(
+            |(a_index, (a_matches, a_score)), (b_index, (b_matches, b_score))| {
+                b_matches.cmp(a_matches).then(b_score.total_cmp(a_score)).then(a_index.cmp(b_index))
+            },
+        );
+        results
+            .into_iter()
+            .take(limit)
+            .map(|(doc_index, (_, score))| {
+                let doc = &self.docs[doc_index as usize];
+                SearchResult {
+                    kind: doc.kind,
+                    id: doc.id.clone(),
+                    title: doc.title.clone(),
+                    score,
+                }
+            })
+            .collect()
+    }
+
+    /// Return the terms in the index matching the query term provided.
+    fn matching_terms<'a>(
+        &'a self,
+        query_term: &'a str,
+        prefix: bool,
+    ) -> Box<dyn Iterator<Item = (&'a String, &'a Postings)> + 'a> {
+        if prefix {
+            Box::new(
+                self.terms
+                    .range(query_term.to_string()..)
+                    .take_while(move |(term, _)| term.starts_with(query_term)),
+            )
+        } else {
+            Box::new(self.terms.get_key_value(query_term).into_iter())
+        }
+    }
+}
+
+/// Split the text provided into the terms to index or search for.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.chars().count() >= MIN_TERM_LEN)
+        .map(str::to_lowercase)
+        .filter(|token| !STOP_WORDS.contains(&token.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        data::{Item, ItemSummary, Repository, RepositoryGithubData},
+        guide,
+    };
+
+    use super::*;
+
+    fn item(name: &str, description: &str) -> Item {
+        Item {
+            id: format!("category--subcategory--{}", normalize_name(name)),
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn search_index() -> SearchIndex {
+        let mut item3 = item("Item3", "Something else");
+        item3.summary = Some(ItemSummary {
+            use_case: Some("Tracing requests".to_string()),
+            ..Default::default()
+        });
+        item3.repositories = Some(vec![Repository {
+            github_data: Some(RepositoryGithubData {
+                topics: vec!["observability".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]);
+        let landscape_data = LandscapeData {
+            items: vec![
+                item("Mesh", "Service mesh for microservices"),
+                item("Observer", "Observability platform"),
+                item3,
+            ],
+            ..Default::default()
+        };
+        let guide = LandscapeGuide {
+            categories: Some(vec![guide::Category {
+                category: "Orchestration & Management".to_string(),
+                subcategories: Some(vec![guide::Subcategory {
+                    subcategory: "Service Mesh".to_string(),
+                    content: "A service mesh handles service to service communication.".to_string(),
+                    keywords: None,
+                }]),
+                ..Default::default()
+            }]),
+        };
+
+        SearchIndex::new(&landscape_data, Some(&guide))
+    }
+
+    #[test]
+    fn tokenize_succeeds() {
+        assert_eq!(
+            tokenize("The Service-Mesh is a <b>great</b> thing!").collect::<Vec<_>>(),
+            vec!["service", "mesh", "great", "thing"]
+        );
+    }
+
+    #[test]
+    fn search_index_new_succeeds() {
+        let index = search_index();
+        assert_eq!(index.docs.len(), 5);
+        assert_eq!(index.docs[4].kind, SearchDocumentKind::Guide);
+        assert_eq!(index.docs[4].id, "orchestration-management--service-mesh");
+        assert_eq!(index.terms["observability"], vec![(1, 2), (2, 2)]);
+        assert_eq!(index.terms["tracing"], vec![(2, 1)]);
+    }
+
+    #[test]
+    fn search_ranks_results() {
+        let index = search_index();
+        let ids =
+            |query: &str| -> Vec<String> { index.search(query, 10).into_iter().map(|r| r.id).collect() };
+
+        // Item name matches rank first
+        assert_eq!(
+            ids("mesh "),
+            vec![
+                "category--subcategory--mesh",
+                "orchestration-management--service-mesh"
+            ]
+        );
+
+        // Documents matching all the terms rank first
+        assert_eq!(
+            ids("service communication"),
+            vec![
+                "orchestration-management--service-mesh",
+                "category--subcategory--mesh"
+            ]
+        );
+
+        // Last term is matched by prefix
+        assert_eq!(
+            ids("observ"),
+            vec!["category--subcategory--observer", "category--subcategory--item3"]
+        );
+        assert!(ids("observ ").is_empty());
+        assert!(ids("").is_empty());
+    }
+}
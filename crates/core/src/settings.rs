@@ -8,7 +8,7 @@
 //! compatible with the legacy settings file used by existing landscapes.
 
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     fs,
     path::{Path, PathBuf},
     sync::LazyLock,
@@ -22,10 +22,16 @@ use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, instrument};
 
-use crate::util::{is_url, normalize_name, validate_url};
+use crate::{
+    util::{is_url, normalize_name, validate_url},
+    validation::{self, ValidationReport},
+};
 
 use super::data::{CategoryName, SubcategoryName};
 
+/// Maximum size allowed for the logos raster derivatives (in pixels).
+pub const LOGOS_RASTER_MAX_SIZE: u32 = 2048;
+
 /// Landscape settings location.
 #[derive(Args, Default, Debug, Clone, PartialEq)]
 #[group(required = true, multiple = false)]
@@ -95,6 +101,9 @@ pub struct LandscapeSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub images: Option<Images>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logos_raster: Option<LogosRaster>,
+
     #[serde(default)]
     pub logos_viewbox: LogosViewbox,
 
@@ -107,6 +116,9 @@ pub struct LandscapeSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub screenshot_width: Option<u32>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshots: Option<Vec<Screenshot>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<BTreeMap<TagName, Vec<TagRule>>>,
 
@@ -167,7 +179,10 @@ impl LandscapeSettings {
     fn new_from_raw_data(raw_data: &str) -> Result<Self> {
         let mut settings: LandscapeSettings = serde_yaml::from_str(raw_data).context("invalid yaml file")?;
 
-        settings.validate().context("the landscape settings file provided is not valid")?;
+        settings
+            .validate()
+            .map_err(|err| validation::set_locations(err, raw_data))
+            .context("the landscape settings file provided is not valid")?;
         settings.header_motd_to_html().context("error converting header motd md text to html")?;
         settings.footer_text_to_html().context("error converting footer md text to html")?;
         settings.remove_base_path_trailing_slash();
@@ -220,52 +235,58 @@ impl LandscapeSettings {
 
     /// Validate landscape settings.
     fn validate(&self) -> Result<()> {
+        let mut report = ValidationReport::new();
+
         // Check foundation is not empty
         if self.foundation.trim().is_empty() {
-            bail!("foundation cannot be empty");
+            report.add_error(&["foundation"], "foundation cannot be empty");
         }
 
         // Check url is valid
-        validate_url("landscape", Some(self.url.clone()).as_ref())?;
-
-        self.validate_base_path()?;
-        self.validate_description()?;
-        self.validate_categories()?;
-        self.validate_colors()?;
-        self.validate_featured_items()?;
-        self.validate_footer()?;
-        self.validate_groups()?;
-        self.validate_header()?;
-        self.validate_images()?;
-        self.validate_members_category()?;
-        self.validate_osano()?;
-        self.validate_screenshot_width()?;
-        self.validate_tags()?;
+        report.check(
+            &["url"],
+            validate_url("landscape", Some(self.url.clone()).as_ref()),
+        );
 
-        Ok(())
+        self.validate_base_path(&mut report);
+        self.validate_description(&mut report);
+        self.validate_categories(&mut report);
+        self.validate_colors(&mut report);
+        self.validate_featured_items(&mut report);
+        self.validate_footer(&mut report);
+        self.validate_groups(&mut report);
+        self.validate_header(&mut report);
+        self.validate_images(&mut report);
+        self.validate_logos_raster(&mut report);
+        self.validate_members_category(&mut report);
+        self.validate_osano(&mut report);
+        self.validate_screenshot_width(&mut report);
+        self.validate_screenshots(&mut report);
+        self.validate_tags(&mut report);
+
+        report.into_result()
     }
 
     /// Check base path is valid.
-    fn validate_base_path(&self) -> Result<()> {
+    fn validate_base_path(&self, report: &mut ValidationReport) {
         let Some(base_path) = &self.base_path else {
-            return Ok(());
+            return;
         };
 
         // Check base path is not empty
         if base_path.trim().is_empty() {
-            bail!("base_path cannot be empty");
+            report.add_error(&["base_path"], "base_path cannot be empty");
+            return;
         }
 
         // Check base path starts with a slash
         if !base_path.starts_with('/') {
-            bail!("base_path must start with a slash");
+            report.add_error(&["base_path"], "base_path must start with a slash");
         }
-
-        Ok(())
     }
 
     /// Check categories are valid.
-    fn validate_categories(&self) -> Result<()> {
+    fn validate_categories(&self, report: &mut ValidationReport) {
         if let Some(categories) = &self.categories {
             for (i, category) in categories.iter().enumerate() {
                 let category_id = if category.name.trim().is_empty() {
@@ -276,23 +297,29 @@ impl LandscapeSettings {
 
                 // Name
                 if category.name.trim().is_empty() {
-                    bail!("category [{category_id}] name cannot be empty");
+                    report.add_error(
+                        &["categories"],
+                        format!("category [{category_id}] name cannot be empty"),
+                    );
                 }
 
                 // Subcategories
                 for (subcategory_index, subcategory) in category.subcategories.iter().enumerate() {
                     if subcategory.trim().is_empty() {
-                        bail!("category [{category_id}]: subcategory [{subcategory_index}] cannot be empty");
+                        report.add_error(
+                            &["categories", &category_id],
+                            format!(
+                                "category [{category_id}]: subcategory [{subcategory_index}] cannot be empty"
+                            ),
+                        );
                     }
                 }
             }
         }
-
-        Ok(())
     }
 
     /// Check colors format.
-    fn validate_colors(&self) -> Result<()> {
+    fn validate_colors(&self, report: &mut ValidationReport) {
         if let Some(colors) = &self.colors {
             let colors = [
                 ("color1", &colors.color1),
@@ -306,27 +333,26 @@ impl LandscapeSettings {
 
             for (name, value) in colors {
                 if !RGBA.is_match(value) {
-                    bail!(r#"{name} is not valid (expected format: "rgba(0, 107, 204, 1)")"#);
+                    report.add_error(
+                        &["colors", name],
+                        format!(r#"{name} is not valid (expected format: "rgba(0, 107, 204, 1)")"#),
+                    );
                 }
             }
         }
-
-        Ok(())
     }
 
     /// Check description is not empty when provided.
-    fn validate_description(&self) -> Result<()> {
+    fn validate_description(&self, report: &mut ValidationReport) {
         if let Some(description) = &self.description
             && description.trim().is_empty()
         {
-            bail!("description cannot be empty");
+            report.add_error(&["description"], "description cannot be empty");
         }
-
-        Ok(())
     }
 
     /// Check featured item rules are valid.
-    fn validate_featured_items(&self) -> Result<()> {
+    fn validate_featured_items(&self, report: &mut ValidationReport) {
         if let Some(featured_items) = &self.featured_items {
             for (i, rule) in featured_items.iter().enumerate() {
                 let rule_id = if rule.field.trim().is_empty() {
@@ -334,39 +360,38 @@ impl LandscapeSettings {
                 } else {
                     rule.field.clone()
                 };
+                let path = ["featured_items", &rule_id];
                 let ctx = format!("featured item rule [{rule_id}] is not valid");
 
                 // Field
                 if rule.field.trim().is_empty() {
-                    return Err(format_err!("field cannot be empty")).context(ctx);
+                    report.add_error(&path, format!("{ctx}: field cannot be empty"));
                 }
 
                 // Options
                 if rule.options.is_empty() {
-                    return Err(format_err!("options cannot be empty")).context(ctx);
+                    report.add_error(&path, format!("{ctx}: options cannot be empty"));
                 }
                 for option in &rule.options {
                     // Value
                     if option.value.trim().is_empty() {
-                        return Err(format_err!("option value cannot be empty")).context(ctx);
+                        report.add_error(&path, format!("{ctx}: option value cannot be empty"));
                     }
 
                     // Label
                     if let Some(label) = &option.label
                         && label.trim().is_empty()
                     {
-                        return Err(format_err!("option label cannot be empty")).context(ctx);
+                        report.add_error(&path, format!("{ctx}: option label cannot be empty"));
                     }
                 }
             }
         }
-
-        Ok(())
     }
 
     /// Check footer is valid.
-    fn validate_footer(&self) -> Result<()> {
-        let Some(footer) = &self.footer else { return Ok(()) };
+    fn validate_footer(&self, report: &mut ValidationReport) {
+        let Some(footer) = &self.footer else { return };
 
         // Links
         if let Some(links) = &footer.links {
@@ -385,7 +410,7 @@ impl LandscapeSettings {
                 ("youtube", &links.youtube),
             ];
             for (name, url) in urls {
-                validate_url(name, url.as_ref())?;
+                report.check(&["footer", "links", name], validate_url(name, url.as_ref()));
             }
         }
 
@@ -393,21 +418,19 @@ impl LandscapeSettings {
         if let Some(logo) = &footer.logo
             && is_url(logo)
         {
-            validate_url("footer logo", Some(logo))?;
+            report.check(&["footer", "logo"], validate_url("footer logo", Some(logo)));
         }
 
         // Text
         if let Some(text) = &footer.text
             && text.trim().is_empty()
         {
-            bail!("footer text cannot be empty");
+            report.add_error(&["footer", "text"], "footer text cannot be empty");
         }
-
-        Ok(())
     }
 
     /// Check groups are valid.
-    fn validate_groups(&self) -> Result<()> {
+    fn validate_groups(&self, report: &mut ValidationReport) {
         if let Some(groups) = &self.groups {
             for (i, group) in groups.iter().enumerate() {
                 let group_id = if group.name.trim().is_empty() {
@@ -418,30 +441,31 @@ impl LandscapeSettings {
 
                 // Name
                 if group.name.trim().is_empty() {
-                    bail!("group [{group_id}] name cannot be empty");
+                    report.add_error(&["groups"], format!("group [{group_id}] name cannot be empty"));
                 }
 
                 // Categories
                 for (category_index, category) in group.categories.iter().enumerate() {
                     if category.trim().is_empty() {
-                        bail!("group [{group_id}]: category [{category_index}] cannot be empty");
+                        report.add_error(
+                            &["groups", &group_id],
+                            format!("group [{group_id}]: category [{category_index}] cannot be empty"),
+                        );
                     }
                 }
             }
         }
-
-        Ok(())
     }
 
     /// Check header is valid.
-    fn validate_header(&self) -> Result<()> {
-        let Some(header) = &self.header else { return Ok(()) };
+    fn validate_header(&self, report: &mut ValidationReport) {
+        let Some(header) = &self.header else { return };
 
         // Links
         if let Some(links) = &header.links {
             let urls = [("github", &links.github)];
             for (name, url) in urls {
-                validate_url(name, url.as_ref())?;
+                report.check(&["header", "links", name], validate_url(name, url.as_ref()));
             }
         }
 
@@ -449,94 +473,180 @@ impl LandscapeSettings {
         if let Some(logo) = &header.logo
             && is_url(logo)
         {
-            validate_url("header logo", Some(logo))?;
+            report.check(&["header", "logo"], validate_url("header logo", Some(logo)));
         }
 
         // Message of the day
         if let Some(motd) = &header.motd
             && motd.trim().is_empty()
         {
-            bail!("header motd cannot be empty");
+            report.add_error(&["header", "motd"], "header motd cannot be empty");
         }
-
-        Ok(())
     }
 
     /// Check images are valid.
-    fn validate_images(&self) -> Result<()> {
-        let Some(images) = &self.images else { return Ok(()) };
+    fn validate_images(&self, report: &mut ValidationReport) {
+        let Some(images) = &self.images else { return };
 
         let urls = [("favicon", &images.favicon), ("open_graph", &images.open_graph)];
         for (name, url) in urls {
-            validate_url(name, url.as_ref())?;
+            report.check(&["images", name], validate_url(name, url.as_ref()));
         }
-
-        Ok(())
     }
 
     /// Check members category is valid.
-    fn validate_members_category(&self) -> Result<()> {
+    fn validate_members_category(&self, report: &mut ValidationReport) {
         let Some(members_category) = &self.members_category else {
-            return Ok(());
+            return;
         };
 
         // Check members category is not empty
         if members_category.trim().is_empty() {
-            bail!("members category cannot be empty");
+            report.add_error(&["members_category"], "members category cannot be empty");
         }
+    }
 
-        Ok(())
+    /// Check logos raster configuration is valid.
+    fn validate_logos_raster(&self, report: &mut ValidationReport) {
+        let Some(logos_raster) = &self.logos_raster else {
+            return;
+        };
+
+        if logos_raster.sizes.is_empty() {
+            report.add_error(&["logos_raster", "sizes"], "logos raster sizes cannot be empty");
+        }
+        for (i, size) in logos_raster.sizes.iter().enumerate() {
+            if *size == 0 || *size > LOGOS_RASTER_MAX_SIZE {
+                report.add_error(
+                    &["logos_raster", "sizes", &i.to_string()],
+                    format!(
+                        "logos raster size must be between 1 and {LOGOS_RASTER_MAX_SIZE} (found: {size})"
+                    ),
+                );
+            }
+        }
     }
 
     /// Check Osano configuration is valid.
-    fn validate_osano(&self) -> Result<()> {
-        let Some(osano) = &self.osano else { return Ok(()) };
+    fn validate_osano(&self, report: &mut ValidationReport) {
+        let Some(osano) = &self.osano else { return };
 
         // Check customer id and customer configuration id are not empty
         if osano.customer_id.trim().is_empty() {
-            bail!("osano customer id cannot be empty");
+            report.add_error(&["osano", "customer_id"], "osano customer id cannot be empty");
         }
         if osano.customer_configuration_id.trim().is_empty() {
-            bail!("osano customer configuration id cannot be empty");
+            report.add_error(
+                &["osano", "customer_configuration_id"],
+                "osano customer configuration id cannot be empty",
+            );
         }
-
-        Ok(())
     }
 
     /// Check screenshot width is valid.
-    fn validate_screenshot_width(&self) -> Result<()> {
+    fn validate_screenshot_width(&self, report: &mut ValidationReport) {
         let Some(screenshot_width) = &self.screenshot_width else {
-            return Ok(());
+            return;
         };
 
         if *screenshot_width <= 1000 {
-            bail!("screenshot width must be greater than 1000");
+            report.add_error(
+                &["screenshot_width"],
+                "screenshot width must be greater than 1000",
+            );
         }
+    }
 
-        Ok(())
+    /// Check screenshots configuration is valid.
+    fn validate_screenshots(&self, report: &mut ValidationReport) {
+        let Some(screenshots) = &self.screenshots else {
+            return;
+        };
+
+        let mut names = HashSet::new();
+        for (i, screenshot) in screenshots.iter().enumerate() {
+            let index = i.to_string();
+
+            // Name
+            let name = screenshot.name();
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                report.add_error(
+                    &["screenshots", &index, "name"],
+                    "screenshot name can only contain alphanumeric characters, hyphens and underscores",
+                );
+            } else if !names.insert(name.clone()) {
+                report.add_error(
+                    &["screenshots", &index, "name"],
+                    format!("screenshot name must be unique (found duplicate: {name})"),
+                );
+            }
+
+            // Width
+            if screenshot.width <= 1000 {
+                report.add_error(
+                    &["screenshots", &index, "width"],
+                    "screenshot width must be greater than 1000",
+                );
+            }
+
+            // Group and category filters
+            if screenshot.group.is_some() && screenshot.category.is_some() {
+                report.add_error(
+                    &["screenshots", &index],
+                    "screenshot can only be filtered by group or category, not both",
+                );
+            }
+            if let Some(group) = &screenshot.group {
+                let group_found = self.groups.iter().flatten().any(|g| {
+                    g.name == *group
+                        || g.normalized_name.as_ref() == Some(group)
+                        || normalize_name(&g.name) == *group
+                });
+                if !group_found {
+                    report.add_error(
+                        &["screenshots", &index, "group"],
+                        format!("screenshot group not found in groups: {group}"),
+                    );
+                }
+            }
+            if let Some(category) = &screenshot.category
+                && category.trim().is_empty()
+            {
+                report.add_error(
+                    &["screenshots", &index, "category"],
+                    "screenshot category cannot be empty",
+                );
+            }
+
+            // Formats
+            if screenshot.formats.as_ref().is_some_and(Vec::is_empty) {
+                report.add_error(
+                    &["screenshots", &index, "formats"],
+                    "screenshot formats cannot be empty",
+                );
+            }
+        }
     }
 
     /// Check tags are valid.
-    fn validate_tags(&self) -> Result<()> {
+    fn validate_tags(&self, report: &mut ValidationReport) {
         if let Some(tags) = &self.tags {
             for (i, tag_rules) in tags {
                 for rule in tag_rules {
                     // Category
                     if rule.category.trim().is_empty() {
-                        bail!("tag [{i}] category cannot be empty");
+                        report.add_error(&["tags", i], format!("tag [{i}] category cannot be empty"));
                     }
 
                     // Subcategories
                     if let Some(subcategories) = &rule.subcategories
                         && subcategories.is_empty()
                     {
-                        bail!("tag [{i}] subcategories cannot be empty");
+                        report.add_error(&["tags", i], format!("tag [{i}] subcategories cannot be empty"));
                     }
                 }
             }
         }
-
-        Ok(())
     }
 }
 
@@ -727,6 +837,14 @@ pub struct Images {
     pub open_graph: Option<String>,
 }
 
+/// Logos raster derivatives configuration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogosRaster {
+    /// Sizes (in pixels) of the bounding boxes the logos derivatives will be
+    /// fitted into, preserving their aspect ratio.
+    pub sizes: Vec<u32>,
+}
+
 /// Logos viewbox configuration.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LogosViewbox {
@@ -750,6 +868,99 @@ pub struct Osano {
     pub customer_configuration_id: String,
 }
 
+/// Paper size used when generating screenshots in PDF format.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaperSize {
+    A0,
+    A1,
+    A2,
+    A3,
+    A4,
+    Letter,
+    Legal,
+    Tabloid,
+}
+
+impl PaperSize {
+    /// Return the paper dimensions (width and height, in portrait
+    /// orientation) in points.
+    #[must_use]
+    pub fn dimensions(&self) -> (f64, f64) {
+        match self {
+            PaperSize::A0 => (2383.94, 3370.39),
+            PaperSize::A1 => (1683.78, 2383.94),
+            PaperSize::A2 => (1190.55, 1683.78),
+            PaperSize::A3 => (841.89, 1190.55),
+            PaperSize::A4 => (595.28, 841.89),
+            PaperSize::Letter => (612.0, 792.0),
+            PaperSize::Legal => (612.0, 1008.0),
+            PaperSize::Tabloid => (792.0, 1224.0),
+        }
+    }
+}
+
+/// Landscape screenshot configuration. Screenshots can include the whole
+/// landscape, or just a group or a category of it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Screenshot {
+    pub width: u32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<CategoryName>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formats: Option<Vec<ScreenshotFormat>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paper_size: Option<PaperSize>,
+}
+
+impl Screenshot {
+    /// Return the screenshot name, used as the file name of the files
+    /// generated. When no name is provided, it's derived from the group or
+    /// category filter (if any).
+    #[must_use]
+    pub fn name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match (&self.group, &self.category) {
+            (Some(group), _) => normalize_name(group),
+            (None, Some(category)) => normalize_name(category),
+            (None, None) => "landscape".to_string(),
+        }
+    }
+
+    /// Return the formats the screenshot will be generated in (all of them
+    /// by default).
+    #[must_use]
+    pub fn formats(&self) -> Vec<ScreenshotFormat> {
+        self.formats.clone().unwrap_or_else(|| {
+            vec![
+                ScreenshotFormat::Svg,
+                ScreenshotFormat::Png,
+                ScreenshotFormat::Pdf,
+            ]
+        })
+    }
+}
+
+/// Format of the screenshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScreenshotFormat {
+    Pdf,
+    Png,
+    Svg,
+}
+
 /// Type alias to represent a TAG name.
 pub type TagName = String;
 
@@ -1497,6 +1708,46 @@ mod tests {
         settings.validate().unwrap();
     }
 
+    #[test]
+    fn settings_validate_logos_raster_succeeds() {
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            url: "https://example.url".to_string(),
+            logos_raster: Some(LogosRaster { sizes: vec![64, 256] }),
+            ..Default::default()
+        };
+
+        settings.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "logos raster sizes cannot be empty")]
+    fn settings_validate_logos_raster_empty_sizes() {
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            url: "https://example.url".to_string(),
+            logos_raster: Some(LogosRaster { sizes: vec![] }),
+            ..Default::default()
+        };
+
+        settings.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "logos raster size must be between 1 and 2048 (found: 4096)")]
+    fn settings_validate_logos_raster_invalid_size() {
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            url: "https://example.url".to_string(),
+            logos_raster: Some(LogosRaster {
+                sizes: vec![64, 4096],
+            }),
+            ..Default::default()
+        };
+
+        settings.validate().unwrap();
+    }
+
     #[test]
     fn settings_validate_screenshot_width_succeeds() {
         let settings = LandscapeSettings {
@@ -1522,6 +1773,84 @@ mod tests {
         settings.validate().unwrap();
     }
 
+    #[test]
+    fn settings_validate_screenshots_succeeds() {
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            url: "https://example.url".to_string(),
+            groups: Some(vec![Group {
+                name: "Group 1".to_string(),
+                categories: vec!["Category 1".to_string()],
+                ..Default::default()
+            }]),
+            screenshots: Some(vec![
+                Screenshot {
+                    width: 3000,
+                    ..Default::default()
+                },
+                Screenshot {
+                    width: 3000,
+                    group: Some("group-1".to_string()),
+                    formats: Some(vec![ScreenshotFormat::Pdf]),
+                    paper_size: Some(PaperSize::A3),
+                    ..Default::default()
+                },
+                Screenshot {
+                    width: 1920,
+                    category: Some("Category 1".to_string()),
+                    formats: Some(vec![ScreenshotFormat::Png]),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        settings.validate().unwrap();
+        let screenshots = settings.screenshots.unwrap();
+        assert_eq!(screenshots[0].name(), "landscape");
+        assert_eq!(screenshots[1].name(), "group-1");
+        assert_eq!(screenshots[2].name(), "category-1");
+    }
+
+    #[test]
+    #[should_panic(expected = "screenshot name must be unique (found duplicate: landscape)")]
+    fn settings_validate_screenshots_duplicate_name() {
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            url: "https://example.url".to_string(),
+            screenshots: Some(vec![
+                Screenshot {
+                    width: 3000,
+                    ..Default::default()
+                },
+                Screenshot {
+                    width: 2000,
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        settings.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "screenshot group not found in groups: group-1")]
+    fn settings_validate_screenshots_group_not_found() {
+        let settings = LandscapeSettings {
+            foundation: "Foundation".to_string(),
+            url: "https://example.url".to_string(),
+            screenshots: Some(vec![Screenshot {
+                width: 3000,
+                group: Some("group-1".to_string()),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        settings.validate().unwrap();
+    }
+
     #[test]
     fn settings_validate_tags_succeeds() {
         let settings = LandscapeSettings {
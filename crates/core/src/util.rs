@@ -21,7 +21,8 @@ pub(crate) static VALID_CHARS: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"[\p{L}\p{N}\-\ \+]").expect("exprs in VALID_CHARS to be valid"));
 
 /// Normalize category, subcategory and item name.
-pub(crate) fn normalize_name(value: &str) -> String {
+#[must_use]
+pub fn normalize_name(value: &str) -> String {
     let mut normalized_name = value
         .replace(' ', "-")
         .chars()
@@ -0,0 +1,616 @@
+//! This module defines the types used to report the problems found when
+//! validating the landscape data sources files.
+//!
+//! Validators collect all the problems found in a file into a report, instead
+//! of stopping at the first one, so that they can all be fixed at once.
+//!
+//! It also provides the checks used to verify that the references between the
+//! different landscape files (i.e. settings, guide or games referring to the
+//! categories or items defined in the data file) are consistent.
+
+use std::{collections::BTreeSet, fmt};
+
+use serde::Serialize;
+
+use crate::{data::LandscapeData, games::LandscapeGames, guide::LandscapeGuide, settings::LandscapeSettings};
+
+/// Validation report, containing all the errors found in a landscape file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    /// Create a new empty validation report.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an error to the report.
+    pub fn add_error<S: AsRef<str>>(&mut self, path: &[S], message: impl fmt::Display) {
+        self.add(Severity::Error, path, message);
+    }
+
+    /// Add a warning to the report.
+    pub fn add_warning<S: AsRef<str>>(&mut self, path: &[S], message: impl fmt::Display) {
+        self.add(Severity::Warning, path, message);
+    }
+
+    /// Add an entry with the severity provided to the report.
+    fn add<S: AsRef<str>>(&mut self, severity: Severity, path: &[S], message: impl fmt::Display) {
+        self.errors.push(ValidationError {
+            severity,
+            path: path.iter().map(|s| s.as_ref().to_string()).collect(),
+            message: message.to_string(),
+            line: None,
+            column: None,
+        });
+    }
+
+    /// Add the error in the result provided (if any) to the report.
+    pub fn check<S: AsRef<str>>(&mut self, path: &[S], result: anyhow::Result<()>) {
+        if let Err(err) = result {
+            self.add_error(path, format!("{err:#}"));
+        }
+    }
+
+    /// Check if the report contains any entries (errors or warnings).
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Check if the report contains any entries with error severity.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.errors.iter().any(|e| e.severity == Severity::Error)
+    }
+
+    /// Convert the report into a result, which will be an error if the report
+    /// contains any errors.
+    ///
+    /// # Errors
+    /// Returns the report itself as an error when it contains any errors.
+    pub fn into_result(self) -> anyhow::Result<()> {
+        if !self.has_errors() {
+            return Ok(());
+        }
+        Err(self.into())
+    }
+
+    /// Set the location (line and column) of the errors in the report using
+    /// the raw data of the file validated.
+    ///
+    /// The location is found by looking for the path segments of each error in
+    /// the raw data, either as a key or as the value of a `name` field. When
+    /// the full path cannot be found, the location of the closest parent found
+    /// is used.
+    pub fn set_locations(&mut self, raw_data: &str) {
+        let lines: Vec<&str> = raw_data.lines().collect();
+        for error in &mut self.errors {
+            if let Some((line, column)) = locate(&lines, &error.path) {
+                error.line = Some(line);
+                error.column = Some(column);
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
+/// Validation error.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationError {
+    pub severity: Severity,
+    /// Location of the problem in the file (i.e. category, subcategory and
+    /// item names).
+    pub path: Vec<String>,
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Severity of a validation error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "[{}] ", self.path.join(" > "))?;
+        }
+        write!(f, "{}", self.message)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " (line {line}, column {column})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Set the location of the errors in the validation report contained in the
+/// error provided (if any) using the raw data of the file validated.
+pub(crate) fn set_locations(err: anyhow::Error, raw_data: &str) -> anyhow::Error {
+    match err.downcast::<ValidationReport>() {
+        Ok(mut report) => {
+            report.set_locations(raw_data);
+            report.into()
+        }
+        Err(err) => err,
+    }
+}
+
+/// Reports of the problems found when checking the references between the
+/// landscape files, grouped by the file where they were found.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CrossReferencesReport {
+    pub games: ValidationReport,
+    pub guide: ValidationReport,
+    pub settings: ValidationReport,
+}
+
+/// Check that the categories, subcategories and items referenced from the
+/// settings, guide and games files exist in the landscape data.
+#[must_use]
+pub fn check_cross_references(
+    landscape_data: &LandscapeData,
+    settings: &LandscapeSettings,
+    guide: Option<&LandscapeGuide>,
+    games: Option<&LandscapeGames>,
+) -> CrossReferencesReport {
+    let refs = DataReferences::new(landscape_data);
+    let mut report = CrossReferencesReport::default();
+
+    check_settings_references(&refs, settings, &mut report.settings);
+    if let Some(guide) = guide {
+        check_guide_references(&refs, guide, &mut report.guide);
+    }
+    if let Some(games) = games {
+        check_games_references(&refs, games, &mut report.games);
+    }
+
+    report
+}
+
+/// Names of the categories, subcategories and items defined in the landscape
+/// data, used to check the references to them from other files.
+struct DataReferences<'a> {
+    categories: BTreeSet<&'a str>,
+    subcategories: BTreeSet<(&'a str, &'a str)>,
+    items: BTreeSet<&'a str>,
+    maturities: BTreeSet<&'a str>,
+}
+
+impl<'a> DataReferences<'a> {
+    /// Create a new data references instance from the landscape data.
+    fn new(landscape_data: &'a LandscapeData) -> Self {
+        let mut refs = Self {
+            categories: BTreeSet::new(),
+            subcategories: BTreeSet::new(),
+            items: BTreeSet::new(),
+            maturities: BTreeSet::new(),
+        };
+        for category in &landscape_data.categories {
+            refs.categories.insert(&category.name);
+            for subcategory in &category.subcategories {
+                refs.subcategories.insert((&category.name, &subcategory.name));
+            }
+        }
+        for item in &landscape_data.items {
+            refs.items.insert(&item.name);
+            if let Some(maturity) = &item.maturity {
+                refs.maturities.insert(maturity);
+            }
+        }
+        refs
+    }
+
+    /// Check if the category provided exists.
+    fn has_category(&self, category: &str) -> bool {
+        self.categories.contains(category)
+    }
+
+    /// Check if the subcategory provided exists in the category provided.
+    fn has_subcategory(&self, category: &str, subcategory: &str) -> bool {
+        self.subcategories.contains(&(category, subcategory))
+    }
+
+    /// Check if the subcategory provided exists in any category.
+    fn has_any_subcategory(&self, subcategory: &str) -> bool {
+        self.subcategories.iter().any(|(_, name)| *name == subcategory)
+    }
+}
+
+/// Check the references to the landscape data in the settings file.
+fn check_settings_references(
+    refs: &DataReferences,
+    settings: &LandscapeSettings,
+    report: &mut ValidationReport,
+) {
+    // Categories
+    for category in settings.categories.iter().flatten() {
+        let path = ["categories", &category.name];
+        if !refs.has_category(&category.name) {
+            report.add_error(
+                &path,
+                format!("category [{}] not found in landscape data", category.name),
+            );
+            continue;
+        }
+        for subcategory in &category.subcategories {
+            if !refs.has_subcategory(&category.name, subcategory) {
+                report.add_error(
+                    &path,
+                    format!(
+                        "subcategory [{subcategory}] not found in category [{}]",
+                        category.name
+                    ),
+                );
+            }
+        }
+    }
+
+    // Featured items
+    for rule in settings.featured_items.iter().flatten() {
+        let path = ["featured_items", &rule.field];
+        let values = match rule.field.as_str() {
+            "maturity" => |refs: &DataReferences, value: &str| refs.maturities.contains(value),
+            "subcategory" => |refs: &DataReferences, value: &str| refs.has_any_subcategory(value),
+            _ => {
+                report.add_warning(
+                    &path,
+                    format!("field [{}] is not supported, rule will be ignored", rule.field),
+                );
+                continue;
+            }
+        };
+        for option in &rule.options {
+            if !values(refs, &option.value) {
+                report.add_warning(
+                    &path,
+                    format!("option value [{}] does not match any item", option.value),
+                );
+            }
+        }
+    }
+
+    // Groups
+    for group in settings.groups.iter().flatten() {
+        for category in &group.categories {
+            if !refs.has_category(category) {
+                report.add_error(
+                    &["groups", &group.name],
+                    format!("category [{category}] not found in landscape data"),
+                );
+            }
+        }
+    }
+
+    // Members category
+    if let Some(members_category) = &settings.members_category
+        && !refs.has_category(members_category)
+    {
+        report.add_error(
+            &["members_category"],
+            format!("members category [{members_category}] not found in landscape data"),
+        );
+    }
+
+    // Tags
+    for (tag, rules) in settings.tags.iter().flatten() {
+        for rule in rules {
+            let path = ["tags", tag];
+            if !refs.has_category(&rule.category) {
+                report.add_error(
+                    &path,
+                    format!("category [{}] not found in landscape data", rule.category),
+                );
+                continue;
+            }
+            for subcategory in rule.subcategories.iter().flatten() {
+                if !refs.has_subcategory(&rule.category, subcategory) {
+                    report.add_error(
+                        &path,
+                        format!(
+                            "subcategory [{subcategory}] not found in category [{}]",
+                            rule.category
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Check the references to the landscape data in the guide file.
+fn check_guide_references(refs: &DataReferences, guide: &LandscapeGuide, report: &mut ValidationReport) {
+    for category in guide.categories.iter().flatten() {
+        if !refs.has_category(&category.category) {
+            report.add_error(
+                &[&category.category],
+                format!("category [{}] not found in landscape data", category.category),
+            );
+            continue;
+        }
+        for subcategory in category.subcategories.iter().flatten() {
+            if !refs.has_subcategory(&category.category, &subcategory.subcategory) {
+                report.add_error(
+                    &[&category.category, &subcategory.subcategory],
+                    format!(
+                        "subcategory [{}] not found in category [{}]",
+                        subcategory.subcategory, category.category
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Check the references to the landscape data in the games file.
+fn check_games_references(refs: &DataReferences, games: &LandscapeGames, report: &mut ValidationReport) {
+    let Some(quiz) = &games.quiz else {
+        return;
+    };
+
+    for question in &quiz.questions {
+        let path = ["quiz", "questions", &question.title];
+        for option in &question.options {
+            if !refs.items.contains(option.item.as_str()) {
+                report.add_error(
+                    &path,
+                    format!("item [{}] not found in landscape data", option.item),
+                );
+            }
+            if let Some(category) = &option.category
+                && !refs.has_category(category)
+            {
+                report.add_error(
+                    &path,
+                    format!("category [{category}] not found in landscape data"),
+                );
+            }
+            if let Some(subcategory) = &option.subcategory {
+                let found = match &option.category {
+                    Some(category) => refs.has_subcategory(category, subcategory),
+                    None => refs.has_any_subcategory(subcategory),
+                };
+                if !found {
+                    report.add_error(
+                        &path,
+                        format!("subcategory [{subcategory}] not found in landscape data"),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Keys of the fields used to identify the entries in the landscape files.
+const IDENTIFIER_KEYS: [&str; 6] = ["name", "category", "subcategory", "item", "field", "title"];
+
+/// Find the location (line and column, 1-based) of the path provided in the
+/// lines of a YAML document.
+fn locate(lines: &[&str], path: &[String]) -> Option<(usize, usize)> {
+    let mut location = None;
+    let mut start = 0;
+    for segment in path {
+        let Some((i, line)) =
+            lines.iter().enumerate().skip(start).find(|(_, line)| line_matches(line, segment))
+        else {
+            break;
+        };
+        let column = line.len() - line.trim_start_matches([' ', '-']).len() + 1;
+        location = Some((i + 1, column));
+        start = i + 1;
+    }
+    location
+}
+
+/// Check if the line provided contains the path segment, either as a key or as
+/// the value of one of the fields used to identify entries (i.e. `name`).
+fn line_matches(line: &str, segment: &str) -> bool {
+    let content = line.trim_start_matches([' ', '-']).trim_end();
+    if content.starts_with('#') {
+        return false;
+    }
+    for key in IDENTIFIER_KEYS {
+        if let Some(value) = content.strip_prefix(key).and_then(|rest| rest.strip_prefix(':')) {
+            let value = value.trim().trim_matches(['"', '\'']);
+            if value == segment {
+                return true;
+            }
+        }
+    }
+    content.strip_prefix(segment).is_some_and(|rest| rest.starts_with(':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::{Category, Item, Subcategory},
+        guide,
+        settings::{FeaturedItemRule, FeaturedItemRuleOption, Group},
+    };
+
+    const RAW_DATA: &str = r"
+landscape:
+  - category:
+    name: Category 1
+    subcategories:
+      - subcategory:
+        name: Subcategory 1
+        items:
+          - item:
+            name: Item 1
+          - item:
+            name: 'Item 2'
+colors:
+  color1: invalid
+";
+
+    #[test]
+    fn report_into_result_empty() {
+        ValidationReport::new().into_result().unwrap();
+    }
+
+    #[test]
+    fn report_into_result_with_errors() {
+        let mut report = ValidationReport::new();
+        report.add_error(&["Category 1"], "first error");
+        report.check(&["Category 2"], Err(anyhow::format_err!("second error")));
+        report.check::<&str>(&[], Ok(()));
+
+        let err = report.into_result().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "[Category 1] first error\n[Category 2] second error"
+        );
+    }
+
+    #[test]
+    fn report_into_result_only_warnings() {
+        let mut report = ValidationReport::new();
+        report.add_warning(&["Category 1"], "warning");
+
+        assert!(!report.is_empty());
+        report.into_result().unwrap();
+    }
+
+    #[test]
+    fn check_cross_references_reports_dangling_references() {
+        let landscape_data = LandscapeData {
+            categories: vec![Category {
+                name: "Category 1".to_string(),
+                normalized_name: "category-1".to_string(),
+                subcategories: vec![Subcategory {
+                    name: "Subcategory 1".to_string(),
+                    normalized_name: "subcategory-1".to_string(),
+                }],
+            }],
+            items: vec![Item {
+                category: "Category 1".to_string(),
+                subcategory: "Subcategory 1".to_string(),
+                name: "Item 1".to_string(),
+                maturity: Some("graduated".to_string()),
+                ..Default::default()
+            }],
+        };
+        let settings = LandscapeSettings {
+            featured_items: Some(vec![FeaturedItemRule {
+                field: "maturity".to_string(),
+                options: vec![
+                    FeaturedItemRuleOption {
+                        value: "graduated".to_string(),
+                        ..Default::default()
+                    },
+                    FeaturedItemRuleOption {
+                        value: "incubating".to_string(),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }]),
+            groups: Some(vec![Group {
+                name: "Group 1".to_string(),
+                categories: vec!["Category 1".to_string(), "Category 2".to_string()],
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let guide = LandscapeGuide {
+            categories: Some(vec![guide::Category {
+                category: "Category 1".to_string(),
+                subcategories: Some(vec![guide::Subcategory {
+                    subcategory: "Subcategory 2".to_string(),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }]),
+        };
+
+        let report = check_cross_references(&landscape_data, &settings, Some(&guide), None);
+        assert!(report.games.is_empty());
+        assert_eq!(
+            report.guide.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                path: vec!["Category 1".to_string(), "Subcategory 2".to_string()],
+                message: "subcategory [Subcategory 2] not found in category [Category 1]".to_string(),
+                line: None,
+                column: None,
+            }]
+        );
+        assert_eq!(
+            report.settings.errors,
+            vec![
+                ValidationError {
+                    severity: Severity::Warning,
+                    path: vec!["featured_items".to_string(), "maturity".to_string()],
+                    message: "option value [incubating] does not match any item".to_string(),
+                    line: None,
+                    column: None,
+                },
+                ValidationError {
+                    severity: Severity::Error,
+                    path: vec!["groups".to_string(), "Group 1".to_string()],
+                    message: "category [Category 2] not found in landscape data".to_string(),
+                    line: None,
+                    column: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn report_set_locations() {
+        let mut report = ValidationReport::new();
+        report.add_error(&["Category 1", "Subcategory 1", "Item 2"], "error");
+        report.add_error(&["colors", "color1"], "error");
+        report.add_error(&["Category 1", "Subcategory 1", "[3]"], "error");
+        report.add_error(&["Unknown"], "error");
+        report.set_locations(RAW_DATA);
+
+        let locations: Vec<(Option<usize>, Option<usize>)> =
+            report.errors.iter().map(|e| (e.line, e.column)).collect();
+        assert_eq!(
+            locations,
+            vec![
+                (Some(12), Some(13)),
+                (Some(14), Some(3)),
+                (Some(7), Some(9)),
+                (None, None)
+            ]
+        );
+        assert_eq!(
+            report.errors[0].to_string(),
+            "[Category 1 > Subcategory 1 > Item 2] error (line 12, column 13)"
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 use anyhow::{Context, Result, bail};
 use landscape2_core::{
-    data::{DataSource, Item, LandscapeData},
+    data::{DataSource, Item, ItemsAliases, LandscapeData},
     datasets::{base::Base, full::Full},
     games::{GamesSource, LandscapeGames},
     guide::{GuideSource, LandscapeGuide},
@@ -123,7 +123,14 @@ pub async fn get_overlay_data(input: JsValue) -> Result<String, String> {
     // Prepare datasets
     let qr_code = String::new();
     let datasets = Datasets {
-        base: Base::new(&landscape_data, &settings, &guide, &games, &qr_code),
+        base: Base::new(
+            &landscape_data,
+            &settings,
+            &guide,
+            &games,
+            &ItemsAliases::default(),
+            &qr_code,
+        ),
         full: Full::new(&landscape_data, &crunchbase_data, &github_data),
         stats: Stats::new(&landscape_data, &settings),
     };
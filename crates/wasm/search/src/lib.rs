@@ -0,0 +1,111 @@
+//! This module provides the functionality used to query the landscape
+//! full-text search index (generated at build time) from the browser.
+
+use landscape2_core::search::{SearchIndex, SearchResult};
+use wasm_bindgen::prelude::*;
+
+/// Path to the search index file.
+const SEARCH_INDEX_PATH: &str = "data/search.json";
+
+/// Default maximum number of results returned.
+const DEFAULT_LIMIT: usize = 20;
+
+/// Search represents an instance of the landscape search index.
+#[wasm_bindgen]
+pub struct Search {
+    index: SearchIndex,
+}
+
+#[wasm_bindgen]
+impl Search {
+    /// Create a new Search instance, fetching the search index from the
+    /// landscape url provided.
+    #[wasm_bindgen]
+    pub async fn new(landscape_url: String) -> Result<Search, String> {
+        let index = fetch_search_index(&landscape_url).await?;
+        Ok(Search { index })
+    }
+
+    /// Search the index for the query provided, returning up to `limit`
+    /// results sorted by relevance.
+    pub fn search(&self, query: &str, limit: Option<usize>) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&self.results(query, limit)).map_err(to_str)
+    }
+}
+
+impl Search {
+    /// Return the results matching the query provided.
+    fn results(&self, query: &str, limit: Option<usize>) -> Vec<SearchResult> {
+        self.index.search(query, limit.unwrap_or(DEFAULT_LIMIT))
+    }
+}
+
+/// Fetch the search index from the landscape url provided.
+async fn fetch_search_index(landscape_url: &str) -> Result<SearchIndex, String> {
+    let url = format!("{}/{SEARCH_INDEX_PATH}", landscape_url.trim_end_matches('/'));
+    let resp = reqwest::get(&url).await.map_err(to_str)?;
+    if resp.status() != 200 {
+        return Err(format!(
+            "unexpected status code getting search index: {}",
+            resp.status()
+        ));
+    }
+    let index: SearchIndex = resp.json().await.map_err(to_str)?;
+
+    Ok(index)
+}
+
+/// Helper function to convert an error to a string.
+fn to_str<E: std::fmt::Debug>(err: E) -> String {
+    format!("{err:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use landscape2_core::data::{Item, LandscapeData};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn search_succeeds() {
+        let landscape_data = LandscapeData {
+            items: vec![Item {
+                id: "category--subcategory--item".to_string(),
+                name: "Item".to_string(),
+                description: Some("Service mesh".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let index = SearchIndex::new(&landscape_data, None);
+        let (server, mock) = setup_mock_server(200, &serde_json::to_string(&index).unwrap()).await;
+
+        let search = Search::new(server.url()).await.unwrap();
+        let results = search.results("mes", None);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "category--subcategory--item");
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "unexpected status code getting search index")]
+    async fn search_index_not_found() {
+        let (server, mock) = setup_mock_server(404, "").await;
+
+        Search::new(server.url()).await.unwrap();
+
+        mock.assert_async().await;
+    }
+
+    async fn setup_mock_server(status_code: usize, body: &str) -> (mockito::ServerGuard, mockito::Mock) {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", format!("/{SEARCH_INDEX_PATH}").as_str())
+            .with_status(status_code)
+            .with_body(body)
+            .create_async()
+            .await;
+        (server, mock)
+    }
+}
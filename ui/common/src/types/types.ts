@@ -57,6 +57,7 @@ export interface Item extends BaseItem {
   unnamed_organization?: boolean;
   youtube_url?: string;
   linkedin_url?: string;
+  logo_derivatives?: LogoDerivative[];
   audits?: SecurityAudit[];
   parent_project?: string;
   other_links?: OtherLink[];
@@ -73,6 +74,12 @@ export interface ItemSummary {
   use_case?: string;
 }
 
+export interface LogoDerivative {
+  format: string;
+  path: string;
+  size: number;
+}
+
 export interface OtherLink {
   name: string;
   url: string;
@@ -6,12 +6,23 @@ import { createContext, createSignal, ParentComponent, useContext } from 'solid-
 
 import { ITEM_PARAM } from '../../data';
 
+// Resolve the item id provided, in case it is a previous id of a renamed or
+// moved item
+const resolveItemId = (itemId?: string): string | undefined => {
+  if (itemId && window.baseDS.items_aliases && window.baseDS.items_aliases[itemId]) {
+    return window.baseDS.items_aliases[itemId];
+  }
+  return itemId;
+};
+
 function useActiveItemProvider() {
   const location = useLocation();
   const navigate = useNavigate();
   const [searchParams] = useSearchParams();
   const [activeItemId, setActiveItemId] = createSignal<string | undefined>(
-    searchParams[ITEM_PARAM] && !isArray(searchParams[ITEM_PARAM]) ? searchParams[ITEM_PARAM] : undefined
+    resolveItemId(
+      searchParams[ITEM_PARAM] && !isArray(searchParams[ITEM_PARAM]) ? searchParams[ITEM_PARAM] : undefined
+    )
   );
 
   const updateActiveItem = (itemId?: string) => {
@@ -13,6 +13,7 @@ export interface BaseData {
   categories: Category[];
   categories_overridden?: string[];
   items: BaseItem[];
+  items_aliases?: { [key: string]: string };
   members_category?: string;
   qr_code?: string;
   upcoming_event?: Event;